@@ -1,21 +1,120 @@
+use arc_swap::ArcSwap;
 use config::{Config, File};
 use dotenv::dotenv;
+use lettre::message::Mailbox;
 use once_cell::sync::OnceCell;
 use secrecy::{ExposeSecret, SecretBox};
-use serde::Deserialize;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{env, fmt};
 
+/// 默认的配置文件
+const CONFIG_FILE: &str = "config.toml";
+
 // 全局配置实例
-static CONFIG: OnceCell<AppConfig> = OnceCell::new();
+static CONFIG: OnceCell<ConfigStore> = OnceCell::new();
+
+/// 可在运行时重新加载的配置
+///
+/// 读取方通过 [`ConfigStore::get`] 拿到某一时刻的完整快照，重新加载时整体替换，
+/// 不会读到新旧混合的配置
+pub struct ConfigStore {
+    path: PathBuf,
+    current: ArcSwap<AppConfig>,
+}
+
+impl ConfigStore {
+    /// 从 `path` 与环境变量加载配置
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.into();
+        let config = AppConfig::load_from(&path, None)?;
+        Ok(Self {
+            path,
+            current: ArcSwap::from_pointee(config),
+        })
+    }
+
+    /// 当前配置的快照
+    pub fn get(&self) -> Arc<AppConfig> {
+        self.current.load_full()
+    }
+
+    /// 重新读取配置文件与环境变量，成功后替换当前配置，失败时保持不变
+    ///
+    /// 环境变量中已读不到的密钥沿用当前值
+    pub fn reload(&self) -> Result<Arc<AppConfig>, Box<dyn std::error::Error>> {
+        let previous = self.current.load();
+        let config = Arc::new(AppConfig::load_from(&self.path, Some(&previous))?);
+        self.current.store(config.clone());
+        Ok(config)
+    }
+}
+
+/// 读取环境变量，测试中可替换为固定的取值
+type EnvLookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// 依次读取环境变量，都不存在时沿用重新加载前的值
+fn secret_env(
+    env: EnvLookup<'_>,
+    names: [&str; 2],
+    previous: Option<&SecretBox<String>>,
+) -> Option<SecretBox<String>> {
+    names
+        .iter()
+        .find_map(|name| env(name))
+        .or_else(|| previous.map(|secret| secret.expose_secret().clone()))
+        .map(|value| SecretBox::new(Box::new(value)))
+}
+
+/// 缺少必需的密钥环境变量，列出全部缺失项，便于一次补齐
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingSecrets(pub Vec<String>);
+
+impl fmt::Display for MissingSecrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "missing required environment variables: {}",
+            self.0.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for MissingSecrets {}
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub port: u16,
+    /// 可信反向代理地址，只有来自这些地址的请求才采信 X-Forwarded-For 等转发头
+    pub trusted_proxies: Vec<IpAddr>,
+    /// 出站请求的 User-Agent，`{version}` 会替换为当前版本号
+    pub user_agent: String,
+    /// 响应中的 Content-Security-Policy，为空时不设置
+    pub content_security_policy: String,
+    /// 后台任务队列（邮件、通知、审计）最多排队的任务数，队列满时新任务被丢弃
+    pub background_queue_capacity: usize,
+    /// 后台任务 worker 线程数，慢任务（如 SMTP 发送）不会阻塞其他任务
+    pub background_workers: usize,
+    /// 进程退出时等待后台队列排空的最长时间（秒），超时后剩余任务被放弃
+    pub shutdown_timeout_secs: u64,
+    /// 提供 `GET /openapi.json` 接口文档
+    pub openapi: bool,
     pub github: GitHubConfig,
     pub smtp: SmtpConfig,
     pub admin: AdminConfig,
+    pub auth: AuthConfig,
     pub file_share: FileShareConfig,
+    pub submission: SubmissionConfig,
+    pub images: ImageConfig,
+    pub upload: UploadConfig,
+    pub audit: AuditConfig,
+    pub cache: CacheConfig,
+    pub health: HealthConfig,
+    pub notify: NotifyConfig,
+    pub cors: CorsConfig,
     pub log: LogConfig,
 }
 
@@ -26,6 +125,21 @@ pub struct GitHubConfig {
     pub personal_access_token: SecretBox<String>,
     pub redirect_uri: String,
     pub repo_path: String,
+    /// 投稿分支的基准分支，也是 PR 的目标分支
+    pub default_branch: String,
+    /// 以草稿形式创建投稿 PR
+    pub is_draft: bool,
+    /// 创建 PR 后添加的标签
+    pub labels: Vec<String>,
+    /// 管理员邮箱与 GitHub 用户名的对应关系，创建 PR 后请求这些管理员审阅
+    pub reviewers: Vec<ReviewerMapping>,
+}
+
+/// 管理员邮箱对应的 GitHub 用户名
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReviewerMapping {
+    pub email: String,
+    pub github: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,16 +147,307 @@ pub struct SmtpConfig {
     pub username: String,
     pub password: SecretBox<String>,
     pub host: String,
+    pub port: u16,
+    /// 连接的加密方式
+    pub tls: SmtpTls,
+    /// 建立连接及单条 SMTP 命令的超时（秒）
+    pub connect_timeout_secs: u64,
+    /// 请求中异步发送一封邮件的总超时（秒）；后台同步发送由 `connect_timeout_secs` 限制每条命令
+    pub send_timeout_secs: u64,
+    /// 请求处理中发信遇到临时性故障时的最大发送次数（含首次）
+    pub retry_attempts: u32,
+    /// 首次重试前的等待（毫秒），之后每次翻倍
+    pub retry_base_delay_ms: u64,
+    /// 后台邮件遇到临时性故障时的最大发送次数（含首次），代替 `retry_attempts`；
+    /// 超时与永久性错误同样不重发
+    pub background_retry_attempts: u32,
+    /// 后台邮件任务首次重试前的等待（毫秒），之后每次翻倍
+    pub background_retry_delay_ms: u64,
+}
+
+/// SMTP 连接的加密方式
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// 连接建立后立即进行 TLS 握手，通常使用 465 端口
+    Implicit,
+    /// 先以明文连接，再通过 STARTTLS 升级，通常使用 587 端口
+    StartTls,
+    /// 不加密，只应用于本机或内网的可信中继
+    None,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AdminConfig {
+    /// 管理员邮箱，加载时已校验格式并去重
     pub email: Vec<String>,
+    /// 管理接口的 API Key，未设置时管理接口全部拒绝
+    pub api_key: Option<SecretBox<String>>,
+    /// 为 true 时管理员邮箱列表不能为空，否则启动失败
+    pub require_admin: bool,
+    /// 逐个通知管理员时同时发送的邮件数
+    pub notify_concurrency: usize,
+    /// 把全部管理员放在密送中只发送一封通知邮件；为 false 时逐个发送，单个地址失败不影响其他人
+    pub notify_bcc: bool,
+}
+
+impl AdminConfig {
+    /// 校验每个管理员邮箱并去掉重复项（忽略大小写），保留首次出现的写法
+    ///
+    /// 存在无效地址时返回错误并列出全部无效项
+    pub fn parse_emails(raw: Vec<String>) -> Result<Vec<String>, String> {
+        let mut emails = Vec::with_capacity(raw.len());
+        let mut seen = HashSet::new();
+        let mut invalid = Vec::new();
+        for entry in raw {
+            let entry = entry.trim().to_string();
+            match entry.parse::<Mailbox>() {
+                Ok(mailbox) => {
+                    if seen.insert(mailbox.email.to_string().to_lowercase()) {
+                        emails.push(entry);
+                    } else {
+                        tracing::warn!("CONFIG: duplicate admin email {} ignored", entry);
+                    }
+                }
+                Err(_) => invalid.push(entry),
+            }
+        }
+        if !invalid.is_empty() {
+            return Err(format!(
+                "admin.emails contains invalid addresses: {}",
+                invalid.join(", ")
+            ));
+        }
+        Ok(emails)
+    }
+
+    /// 开启 `require_admin` 时校验管理员邮箱列表非空
+    pub fn validate(&self) -> Result<(), String> {
+        if self.require_admin && self.email.is_empty() {
+            return Err("admin.emails is empty but admin.require_admin is set".to_string());
+        }
+        Ok(())
+    }
+
+    /// 管理员邮箱列表为空时输出警告，所有管理员通知都会被跳过
+    pub fn warn_if_empty(&self) -> bool {
+        if self.email.is_empty() {
+            tracing::warn!("CONFIG: admin.emails is empty, admin notifications will not be sent");
+            return true;
+        }
+        false
+    }
+}
+
+/// 邮箱验证码使用的字符集
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeKind {
+    /// 仅数字，便于在手机键盘上输入
+    Numeric,
+    /// 大小写字母与数字
+    Alphanumeric,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthConfig {
+    /// 验证码长度
+    pub code_len: usize,
+    /// 验证码字符集
+    pub code_kind: CodeKind,
+    /// 单个验证码允许的错误次数，达到后验证码作废
+    pub max_attempts: u32,
+    /// 同一邮箱两次发送验证码的最小间隔（秒）
+    pub send_cooldown_secs: i64,
+    /// 验证码邮件的主题模板，`{code}`、`{ttl_minutes}` 替换为验证码与有效分钟数，为空时按请求语言使用默认文案
+    pub code_subject_template: String,
+    /// 验证码邮件的正文模板，占位符同上，为空时使用默认文案
+    pub code_body_template: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct FileShareConfig {
     pub path: PathBuf,
+    /// 允许分享的文件扩展名（不含点，不区分大小写），为空时不限制
+    pub allowed_extensions: Vec<String>,
+    /// 文件列表缓存时间（秒）
+    pub list_ttl_secs: i64,
+    /// 已上传文件信息的缓存时间（秒），应短于 tmpfile.link 下载链接的 24 小时有效期
+    pub file_ttl_secs: i64,
+    /// 命中缓存时先用 HEAD 请求确认下载链接仍可访问，失效则重新上传
+    pub verify_cached_links: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmissionConfig {
+    /// 标签为空时在 Markdown、PR 描述、邮件中统一显示的占位文本
+    pub empty_tags_placeholder: String,
+    /// 写入文章 front-matter 的分类，为空时省略
+    pub categories: Vec<String>,
+    /// 标题最大字符数
+    pub title_max_chars: usize,
+    /// 正文最大字符数
+    pub content_max_chars: usize,
+    /// 标签数量上限
+    pub max_tags: usize,
+    /// 单个标签的最大字符数
+    pub tag_max_chars: usize,
+    /// 允许使用的标签（不区分大小写），为空时不限制
+    pub allowed_tags: Vec<String>,
+    /// 作者名最少字符数（去除首尾空白后）
+    pub author_min_chars: usize,
+    /// 作者名最多字符数
+    pub author_max_chars: usize,
+    /// 附加图片数量上限（不含封面）
+    pub max_images: usize,
+    /// 单张图片解码后的最大字节数
+    pub max_image_bytes: usize,
+    /// 封面与全部图片解码后（转为 WebP 之后）的总字节数上限，超出时不创建分支
+    pub max_total_image_bytes: usize,
+    /// 维护模式下拒绝投稿时返回的提示
+    pub maintenance_message: String,
+    /// 开启试运行接口 `/submit/validate`，供前端 CI 校验投稿格式，生产环境应关闭
+    pub dry_run: bool,
+    /// 动图的处理方式
+    pub animated_images: AnimatedImagePolicy,
+    /// 同一作者、邮箱与标题的重复投稿在该时间内（秒）被拒绝，0 表示不检查
+    pub duplicate_window_secs: u64,
+    /// 携带相同 `Idempotency-Key` 的投稿请求在该时间内（秒）返回首次受理的结果，0 表示忽略该请求头
+    pub idempotency_window_secs: u64,
+    /// 开启后，标题与作者同时匹配下面两项的投稿只回复测试邮件，不创建分支与 PR
+    pub test_shortcut: bool,
+    pub test_title: String,
+    pub test_author: String,
+}
+
+impl SubmissionConfig {
+    /// 是否为走测试捷径的投稿，比较时忽略首尾空白
+    pub fn is_test_submission(&self, title: &str, author: &str) -> bool {
+        self.test_shortcut && title.trim() == self.test_title && author.trim() == self.test_author
+    }
+}
+
+/// 动图（GIF、动态 WebP）无法转为单帧图片时的处理方式
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimatedImagePolicy {
+    /// 保留原始格式提交，文件扩展名与实际格式一致
+    Preserve,
+    /// 校验阶段直接拒绝
+    Reject,
+}
+
+/// 提交到仓库的图片处理配置
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImageConfig {
+    /// 尺寸上限（像素），超出时等比缩小；0 表示该方向不限制
+    pub max_width: u32,
+    pub max_height: u32,
+    /// 提交前去除 EXIF 等元数据
+    pub strip_metadata: bool,
+    /// WebP 有损编码质量（1-100），0 表示无损编码
+    pub webp_quality: u8,
+}
+
+impl ImageConfig {
+    /// WebP 编码质量不能超过 100
+    pub fn validate(&self) -> Result<(), String> {
+        if self.webp_quality > 100 {
+            return Err(format!(
+                "images.webp_quality must be between 0 and 100, got {}",
+                self.webp_quality
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 投稿解析阶段的单字段大小上限（字节），超出时直接返回 413
+#[derive(Debug, Clone, Deserialize)]
+pub struct UploadConfig {
+    pub content_max_bytes: usize,
+    pub cover_max_bytes: usize,
+    pub image_max_bytes: usize,
+    /// 投稿接口的请求体上限（MB）
+    pub submit_max_mb: usize,
+    /// 其余 JSON 接口的请求体上限（MB）
+    pub json_max_mb: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditConfig {
+    /// 投稿审计记录（JSONL）的文件路径，未设置时不记录
+    pub path: Option<PathBuf>,
+    /// 计算审计记录中邮箱摘要（HMAC-SHA256）的密钥，设置了 `path` 时必填
+    pub email_hash_key: Option<SecretBox<String>>,
+}
+
+impl AuditConfig {
+    /// 记录审计时必须有摘要密钥，否则邮箱无法安全地写入记录
+    pub fn validate(&self) -> Result<(), String> {
+        if self.path.is_some() && self.email_hash_key.is_none() {
+            return Err(
+                "audit.path is set but QIDIAN_MINI_AUDIT_HASH_KEY (or AUDIT_HASH_KEY) is missing"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CacheConfig {
+    /// 缓存快照文件路径，启动时从中恢复、运行期间定期写入；未设置时不持久化
+    pub snapshot_path: Option<PathBuf>,
+    /// 写入快照的间隔（秒）
+    pub snapshot_interval_secs: u64,
+    /// 最大条目数，超出时淘汰最久未被访问的条目（验证码错误次数、发送冷却不计入也不淘汰）；0 表示不限制
+    pub max_entries: usize,
+}
+
+/// 健康检查中 GitHub 连通性检测的超时与重试策略
+#[derive(Debug, Clone, Deserialize)]
+pub struct HealthConfig {
+    /// 单次请求超时（毫秒）
+    pub github_timeout_ms: u64,
+    /// 失败后的重试次数
+    pub github_retries: u32,
+    /// 最近一次成功在该时长（秒）内时，失败只视为 degraded
+    pub github_grace_secs: i64,
+    /// GitHub 检测结果的缓存时长（秒），为 0 时每次都重新检测
+    pub github_cache_secs: i64,
+    /// SMTP 连通性检测的超时（毫秒），包括连接、握手与认证
+    pub smtp_timeout_ms: u64,
+    /// SMTP 检测结果的缓存时长（秒），过期后先返回旧结果并在后台重新检测；为 0 时每次都重新检测
+    pub smtp_cache_secs: u64,
+}
+
+/// 管理员通知渠道
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyChannel {
+    /// 发送邮件给 `admin.emails` 中的全部管理员
+    Email,
+    /// 以 JSON 形式 POST 到 `notify.webhook_url`
+    Webhook,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotifyConfig {
+    /// 启用的通知渠道
+    pub channels: Vec<NotifyChannel>,
+    /// Webhook 地址，未设置时 webhook 渠道不生效
+    pub webhook_url: Option<String>,
+    /// 单次 Webhook 请求超时（秒）
+    pub webhook_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorsConfig {
+    /// 允许跨域访问的来源，为空时使用内置的 qidian.space 域名列表
+    pub allowed_origins: Vec<String>,
+    /// 是否允许携带 Cookie 等凭证
+    pub allow_credentials: bool,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
@@ -113,23 +518,54 @@ impl fmt::Display for LogFormat {
 pub struct LogConfig {
     pub level: LogLevel,
     pub format: LogFormat,
+    /// 日志目录，按级别写入 {level}.YYYY-MM-DD.log，每天滚动
     pub dir: PathBuf,
-}
-
-impl LogConfig {
-    pub fn file_for_level(&self, level: LogLevel) -> PathBuf {
-        self.dir.join(format!("{}.log", level.as_str()))
-    }
+    /// 除日志文件外同时输出到 stdout，便于容器与 systemd 收集
+    pub stdout: bool,
+    /// 请求耗时超过该值（毫秒）时记录警告
+    pub slow_request_ms: u64,
 }
 
 impl AppConfig {
+    #[cfg(test)]
     fn load_config() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_from(Path::new(CONFIG_FILE), None)
+    }
+
+    /// 从配置文件与环境变量加载，`previous` 为重新加载前的配置，用于补齐读不到的密钥
+    fn load_from(
+        path: &Path,
+        previous: Option<&AppConfig>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         // 确保 .env 文件已加载
         dotenv().ok();
+        Self::load_with_env(path, previous, &|name| env::var(name).ok())
+    }
 
+    fn load_with_env(
+        path: &Path,
+        previous: Option<&AppConfig>,
+        env: EnvLookup<'_>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let config = Config::builder()
-            .add_source(File::with_name("config.toml").required(false))
+            .add_source(File::from(path).required(false))
             .set_default("app.port", "4052")?
+            .set_default(
+                "app.trusted_proxies",
+                vec!["127.0.0.1".to_string(), "::1".to_string()],
+            )?
+            .set_default(
+                "app.user_agent",
+                "QidianMini/{version} (+https://github.com/qidiankepukehuan/qidian_mini)",
+            )?
+            .set_default(
+                "app.content_security_policy",
+                "default-src 'self'; frame-ancestors 'none'; object-src 'none'; base-uri 'none'",
+            )?
+            .set_default("app.background_queue_capacity", 256)?
+            .set_default("app.background_workers", 2)?
+            .set_default("app.shutdown_timeout_secs", 30)?
+            .set_default("app.openapi", false)?
             .set_default("github.client_id", "")?
             .set_default("github.client_secret", "")?
             .set_default("github.redirect_uri", "https://contribute.qidian.space")?
@@ -137,70 +573,371 @@ impl AppConfig {
                 "github.repo_path",
                 "https://github.com/qidiankepukehuan/qidiankepukehuan",
             )?
+            .set_default("github.default_branch", "main")?
+            .set_default("github.is_draft", false)?
+            .set_default("github.labels", Vec::<String>::new())?
+            .set_default("github.reviewers", Vec::<String>::new())?
             .set_default("smtp.username", "tsblydyzbjb@qidian.space")?
             .set_default("smtp.host", "smtp.163.com")?
+            .set_default("smtp.port", 465)?
+            .set_default("smtp.tls", "implicit")?
+            .set_default("smtp.connect_timeout_secs", 5)?
+            .set_default("smtp.send_timeout_secs", 15)?
+            .set_default("smtp.retry_attempts", 3)?
+            .set_default("smtp.retry_base_delay_ms", 500)?
+            .set_default("smtp.background_retry_attempts", 3)?
+            .set_default("smtp.background_retry_delay_ms", 5000)?
             .set_default("admin.emails", vec!["tsblydyzbjb@qidian.space".to_string()])?
+            .set_default("admin.require_admin", false)?
+            .set_default("admin.notify_concurrency", 4)?
+            .set_default("admin.notify_bcc", true)?
+            .set_default("auth.code_len", 6)?
+            .set_default("auth.code_kind", "alphanumeric")?
+            .set_default("auth.max_attempts", 5)?
+            .set_default("auth.send_cooldown_secs", 60)?
+            .set_default("auth.code_subject_template", "")?
+            .set_default("auth.code_body_template", "")?
             .set_default("file.share_path", "./shared")?
+            .set_default("file.allowed_extensions", Vec::<String>::new())?
+            .set_default("file.list_ttl_secs", 600)?
+            .set_default("file.file_ttl_secs", 72000)?
+            .set_default("file.verify_cached_links", false)?
+            .set_default("submission.empty_tags_placeholder", "无")?
+            .set_default("submission.categories", Vec::<String>::new())?
+            .set_default("submission.title_max_chars", 100)?
+            .set_default("submission.content_max_chars", 100_000)?
+            .set_default("submission.max_tags", 10)?
+            .set_default("submission.tag_max_chars", 20)?
+            .set_default("submission.allowed_tags", Vec::<String>::new())?
+            .set_default("submission.author_min_chars", 1)?
+            .set_default("submission.author_max_chars", 50)?
+            .set_default("submission.max_images", 30)?
+            .set_default("submission.max_image_bytes", 10 * 1024 * 1024)?
+            .set_default("submission.max_total_image_bytes", 50 * 1024 * 1024)?
+            .set_default(
+                "submission.maintenance_message",
+                "系统维护中，暂停接收投稿，请稍后再试",
+            )?
+            .set_default("submission.dry_run", false)?
+            .set_default("submission.animated_images", "preserve")?
+            .set_default("submission.duplicate_window_secs", 600)?
+            .set_default("submission.idempotency_window_secs", 86400)?
+            .set_default("submission.test_shortcut", false)?
+            .set_default("submission.test_title", "测试")?
+            .set_default("submission.test_author", "测试")?
+            .set_default("images.max_width", 2048)?
+            .set_default("images.max_height", 2048)?
+            .set_default("images.strip_metadata", true)?
+            .set_default("images.webp_quality", 80)?
+            .set_default("upload.content_max_bytes", 2 * 1024 * 1024)?
+            .set_default("upload.cover_max_bytes", 20 * 1024 * 1024)?
+            .set_default("upload.image_max_bytes", 20 * 1024 * 1024)?
+            .set_default("upload.submit_max_mb", 250)?
+            .set_default("upload.json_max_mb", 1)?
+            .set_default("audit.path", "")?
+            .set_default("cache.snapshot_path", "")?
+            .set_default("cache.snapshot_interval_secs", 60)?
+            .set_default("cache.max_entries", 0)?
+            .set_default("health.github_timeout_ms", 3000)?
+            .set_default("health.github_retries", 1)?
+            .set_default("health.github_grace_secs", 300)?
+            .set_default("health.github_cache_secs", 30)?
+            .set_default("health.smtp_timeout_ms", 5000)?
+            .set_default("health.smtp_cache_secs", 60)?
+            .set_default("notify.channels", vec!["email"])?
+            .set_default("notify.webhook_url", "")?
+            .set_default("notify.webhook_timeout_secs", 5)?
+            .set_default("cors.allowed_origins", Vec::<String>::new())?
+            .set_default("cors.allow_credentials", false)?
             .set_default("log.level", "info")?
             .set_default("log.format", "compact")?
             .set_default("log.dir", "/var/log/qidian")?
+            .set_default("log.stdout", cfg!(debug_assertions))?
+            .set_default("log.slow_request_ms", 3000)?
             .build()?;
 
-        // 尝试从不同前缀的环境变量加载
-        let github_client_id = env::var("QIDIAN_MINI_GITHUB_CLIENT_ID")
-            .or_else(|_| env::var("GITHUB_CLIENT_ID"))
-            .map_err(|_| "Neither QIDIAN_MINI_GITHUB_CLIENT_ID nor GITHUB_CLIENT_ID found in environment")?;
+        // 尝试从不同前缀的环境变量加载，缺失项汇总后一并报告
+        let mut missing = Vec::new();
+        let mut required = |names: [&str; 2], previous: Option<&SecretBox<String>>| {
+            let secret = secret_env(env, names, previous);
+            if secret.is_none() {
+                missing.push(format!("{} (or {})", names[0], names[1]));
+            }
+            secret
+        };
+        let secrets = (
+            required(
+                ["QIDIAN_MINI_GITHUB_CLIENT_ID", "GITHUB_CLIENT_ID"],
+                previous.map(|p| &p.github.client_id),
+            ),
+            required(
+                ["QIDIAN_MINI_GITHUB_CLIENT_SECRET", "GITHUB_CLIENT_SECRET"],
+                previous.map(|p| &p.github.client_secret),
+            ),
+            required(
+                ["QIDIAN_MINI_GITHUB_PAT", "GITHUB_PAT"],
+                previous.map(|p| &p.github.personal_access_token),
+            ),
+            required(
+                ["QIDIAN_MINI_SMTP_PASSWORD", "SMTP_PASSWORD"],
+                previous.map(|p| &p.smtp.password),
+            ),
+        );
+        let (
+            Some(github_client_id),
+            Some(github_client_secret),
+            Some(github_personal_access_token),
+            Some(smtp_password),
+        ) = secrets
+        else {
+            return Err(MissingSecrets(missing).into());
+        };
+
+        // 管理接口 API Key 为可选项
+        let admin_api_key = secret_env(
+            env,
+            ["QIDIAN_MINI_ADMIN_API_KEY", "ADMIN_API_KEY"],
+            previous.and_then(|p| p.admin.api_key.as_ref()),
+        )
+        .filter(|k| !k.expose_secret().is_empty());
 
-        let github_client_secret = env::var("QIDIAN_MINI_GITHUB_CLIENT_SECRET")
-            .or_else(|_| env::var("GITHUB_CLIENT_SECRET"))
-            .map_err(|_| "Neither QIDIAN_MINI_GITHUB_CLIENT_SECRET nor GITHUB_CLIENT_SECRET found in environment")?;
+        let admin = AdminConfig {
+            email: AdminConfig::parse_emails(config.get::<Vec<String>>("admin.emails")?)?,
+            api_key: admin_api_key,
+            require_admin: config.get::<bool>("admin.require_admin")?,
+            notify_concurrency: config.get::<usize>("admin.notify_concurrency")?,
+            notify_bcc: config.get::<bool>("admin.notify_bcc")?,
+        };
+        admin.validate()?;
 
-        let github_personal_access_token = env::var("QIDIAN_MINI_GITHUB_PAT")
-            .or_else(|_| env::var("GITHUB_PAT"))
-            .map_err(|_| "Neither QIDIAN_MINI_GITHUB_PAT nor GITHUB_PAT found in environment")?;
+        let audit = AuditConfig {
+            path: Some(config.get::<String>("audit.path")?)
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from),
+            email_hash_key: secret_env(
+                env,
+                ["QIDIAN_MINI_AUDIT_HASH_KEY", "AUDIT_HASH_KEY"],
+                previous.and_then(|p| p.audit.email_hash_key.as_ref()),
+            )
+            .filter(|k| !k.expose_secret().is_empty()),
+        };
+        audit.validate()?;
 
-        let smtp_password = env::var("QIDIAN_MINI_SMTP_PASSWORD")
-            .or_else(|_| env::var("SMTP_PASSWORD"))
-            .map_err(
-                |_| "Neither QIDIAN_MINI_SMTP_PASSWORD nor SMTP_PASSWORD found in environment",
-            )?;
+        let images = ImageConfig {
+            max_width: config.get::<u32>("images.max_width")?,
+            max_height: config.get::<u32>("images.max_height")?,
+            strip_metadata: config.get::<bool>("images.strip_metadata")?,
+            webp_quality: config.get::<u8>("images.webp_quality")?,
+        };
+        images.validate()?;
 
         Ok(Self {
             port: config.get::<u16>("app.port")?,
+            trusted_proxies: config.get::<Vec<IpAddr>>("app.trusted_proxies")?,
+            user_agent: config.get::<String>("app.user_agent")?,
+            content_security_policy: config.get::<String>("app.content_security_policy")?,
+            background_queue_capacity: config.get::<usize>("app.background_queue_capacity")?,
+            background_workers: config.get::<usize>("app.background_workers")?,
+            shutdown_timeout_secs: config.get::<u64>("app.shutdown_timeout_secs")?,
+            openapi: config.get::<bool>("app.openapi")?,
             github: GitHubConfig {
-                client_id: SecretBox::new(Box::new(github_client_id)),
-                client_secret: SecretBox::new(Box::new(github_client_secret)),
-                personal_access_token: SecretBox::new(Box::new(github_personal_access_token)),
+                client_id: github_client_id,
+                client_secret: github_client_secret,
+                personal_access_token: github_personal_access_token,
                 redirect_uri: config.get::<String>("github.redirect_uri")?,
                 repo_path: config.get::<String>("github.repo_path")?,
+                default_branch: config.get::<String>("github.default_branch")?,
+                is_draft: config.get::<bool>("github.is_draft")?,
+                labels: config.get::<Vec<String>>("github.labels")?,
+                reviewers: config.get::<Vec<ReviewerMapping>>("github.reviewers")?,
             },
             smtp: SmtpConfig {
                 username: config.get::<String>("smtp.username")?,
-                password: SecretBox::new(Box::new(smtp_password)),
+                password: smtp_password,
                 host: config.get::<String>("smtp.host")?,
+                port: config.get::<u16>("smtp.port")?,
+                tls: config.get::<SmtpTls>("smtp.tls")?,
+                connect_timeout_secs: config.get::<u64>("smtp.connect_timeout_secs")?,
+                send_timeout_secs: config.get::<u64>("smtp.send_timeout_secs")?,
+                retry_attempts: config.get::<u32>("smtp.retry_attempts")?,
+                retry_base_delay_ms: config.get::<u64>("smtp.retry_base_delay_ms")?,
+                background_retry_attempts: config.get::<u32>("smtp.background_retry_attempts")?,
+                background_retry_delay_ms: config.get::<u64>("smtp.background_retry_delay_ms")?,
             },
-            admin: AdminConfig {
-                email: config.get::<Vec<String>>("admin.emails")?,
+            admin,
+            auth: AuthConfig {
+                code_len: config.get::<usize>("auth.code_len")?,
+                code_kind: config.get::<CodeKind>("auth.code_kind")?,
+                max_attempts: config.get::<u32>("auth.max_attempts")?,
+                send_cooldown_secs: config.get::<i64>("auth.send_cooldown_secs")?,
+                code_subject_template: config.get::<String>("auth.code_subject_template")?,
+                code_body_template: config.get::<String>("auth.code_body_template")?,
             },
             file_share: FileShareConfig {
                 path: config.get::<PathBuf>("file.share_path")?,
+                allowed_extensions: config.get::<Vec<String>>("file.allowed_extensions")?,
+                list_ttl_secs: config.get::<i64>("file.list_ttl_secs")?,
+                file_ttl_secs: config.get::<i64>("file.file_ttl_secs")?,
+                verify_cached_links: config.get::<bool>("file.verify_cached_links")?,
+            },
+            submission: SubmissionConfig {
+                empty_tags_placeholder: config
+                    .get::<String>("submission.empty_tags_placeholder")?,
+                categories: config.get::<Vec<String>>("submission.categories")?,
+                title_max_chars: config.get::<usize>("submission.title_max_chars")?,
+                content_max_chars: config.get::<usize>("submission.content_max_chars")?,
+                max_tags: config.get::<usize>("submission.max_tags")?,
+                tag_max_chars: config.get::<usize>("submission.tag_max_chars")?,
+                allowed_tags: config.get::<Vec<String>>("submission.allowed_tags")?,
+                author_min_chars: config.get::<usize>("submission.author_min_chars")?,
+                author_max_chars: config.get::<usize>("submission.author_max_chars")?,
+                max_images: config.get::<usize>("submission.max_images")?,
+                max_image_bytes: config.get::<usize>("submission.max_image_bytes")?,
+                max_total_image_bytes: config.get::<usize>("submission.max_total_image_bytes")?,
+                maintenance_message: config.get::<String>("submission.maintenance_message")?,
+                dry_run: config.get::<bool>("submission.dry_run")?,
+                animated_images: config.get::<AnimatedImagePolicy>("submission.animated_images")?,
+                duplicate_window_secs: config.get::<u64>("submission.duplicate_window_secs")?,
+                idempotency_window_secs: config.get::<u64>("submission.idempotency_window_secs")?,
+                test_shortcut: config.get::<bool>("submission.test_shortcut")?,
+                test_title: config.get::<String>("submission.test_title")?,
+                test_author: config.get::<String>("submission.test_author")?,
+            },
+            images,
+            upload: UploadConfig {
+                content_max_bytes: config.get::<usize>("upload.content_max_bytes")?,
+                cover_max_bytes: config.get::<usize>("upload.cover_max_bytes")?,
+                image_max_bytes: config.get::<usize>("upload.image_max_bytes")?,
+                submit_max_mb: config.get::<usize>("upload.submit_max_mb")?,
+                json_max_mb: config.get::<usize>("upload.json_max_mb")?,
+            },
+            audit,
+            cache: CacheConfig {
+                snapshot_path: Some(config.get::<String>("cache.snapshot_path")?)
+                    .filter(|p| !p.is_empty())
+                    .map(PathBuf::from),
+                snapshot_interval_secs: config.get::<u64>("cache.snapshot_interval_secs")?,
+                max_entries: config.get::<usize>("cache.max_entries")?,
+            },
+            health: HealthConfig {
+                github_timeout_ms: config.get::<u64>("health.github_timeout_ms")?,
+                github_retries: config.get::<u32>("health.github_retries")?,
+                github_grace_secs: config.get::<i64>("health.github_grace_secs")?,
+                github_cache_secs: config.get::<i64>("health.github_cache_secs")?,
+                smtp_timeout_ms: config.get::<u64>("health.smtp_timeout_ms")?,
+                smtp_cache_secs: config.get::<u64>("health.smtp_cache_secs")?,
+            },
+            notify: NotifyConfig {
+                channels: config.get::<Vec<NotifyChannel>>("notify.channels")?,
+                webhook_url: Some(config.get::<String>("notify.webhook_url")?)
+                    .filter(|u| !u.is_empty()),
+                webhook_timeout_secs: config.get::<u64>("notify.webhook_timeout_secs")?,
+            },
+            cors: CorsConfig {
+                allowed_origins: config.get::<Vec<String>>("cors.allowed_origins")?,
+                allow_credentials: config.get::<bool>("cors.allow_credentials")?,
             },
             log: LogConfig {
                 level: config.get::<LogLevel>("log.level")?,
                 format: config.get::<LogFormat>("log.format")?,
                 dir: config.get::<PathBuf>("log.dir")?,
+                stdout: config.get::<bool>("log.stdout")?,
+                slow_request_ms: config.get::<u64>("log.slow_request_ms")?,
             },
         })
     }
 
-    /// 获取全局配置实例
-    pub fn global() -> &'static Self {
-        CONFIG.get_or_init(|| Self::load_config().expect("Failed to load config"))
+    fn store() -> Result<&'static ConfigStore, Box<dyn std::error::Error>> {
+        CONFIG.get_or_try_init(|| ConfigStore::load(CONFIG_FILE))
+    }
+
+    /// 获取当前全局配置，首次加载失败时返回错误，供启动时给出明确的提示
+    pub fn try_global() -> Result<Arc<Self>, Box<dyn std::error::Error>> {
+        Ok(Self::store()?.get())
+    }
+
+    /// 获取当前全局配置
+    pub fn global() -> Arc<Self> {
+        Self::try_global().expect("Failed to load config")
     }
+
+    /// 重新加载全局配置，见 [`ConfigStore::reload`]
+    pub fn reload() -> Result<Arc<Self>, Box<dyn std::error::Error>> {
+        Self::store()?.reload()
+    }
+
+    /// 相对 `previous` 有改动、但只在启动时读取的配置项，需要重启服务才能生效
+    pub fn restart_required_changes(&self, previous: &AppConfig) -> Vec<&'static str> {
+        [
+            ("port", self.port != previous.port),
+            ("app.openapi", self.openapi != previous.openapi),
+            (
+                "background_queue_capacity",
+                self.background_queue_capacity != previous.background_queue_capacity,
+            ),
+            (
+                "background_workers",
+                self.background_workers != previous.background_workers,
+            ),
+            ("log.format", self.log.format != previous.log.format),
+            ("log.dir", self.log.dir != previous.log.dir),
+            ("log.stdout", self.log.stdout != previous.log.stdout),
+            (
+                "log.slow_request_ms",
+                self.log.slow_request_ms != previous.log.slow_request_ms,
+            ),
+            (
+                "cache.max_entries",
+                self.cache.max_entries != previous.cache.max_entries,
+            ),
+            (
+                "cache.snapshot_path",
+                self.cache.snapshot_path != previous.cache.snapshot_path,
+            ),
+            (
+                "cache.snapshot_interval_secs",
+                self.cache.snapshot_interval_secs != previous.cache.snapshot_interval_secs,
+            ),
+            (
+                "cors.allowed_origins",
+                self.cors.allowed_origins != previous.cors.allowed_origins,
+            ),
+            (
+                "cors.allow_credentials",
+                self.cors.allow_credentials != previous.cors.allow_credentials,
+            ),
+            (
+                "upload.submit_max_mb",
+                self.upload.submit_max_mb != previous.upload.submit_max_mb,
+            ),
+            (
+                "upload.json_max_mb",
+                self.upload.json_max_mb != previous.upload.json_max_mb,
+            ),
+            (
+                "submission.dry_run",
+                self.submission.dry_run != previous.submission.dry_run,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(name, _)| name)
+        .collect()
+    }
+}
+
+/// 关键配置项的检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ConfigStats {
+    /// 已正确配置的项数
+    pub ok: usize,
+    pub total: usize,
+    /// 校验、去重后的管理员邮箱数
+    pub admin_emails: usize,
 }
 
 impl AppConfig {
-    pub fn stats(&self) -> (usize, usize) {
+    pub fn stats(&self) -> ConfigStats {
         let checks = [
             !self.github.client_id.expose_secret().is_empty(),
             !self.github.client_secret.expose_secret().is_empty(),
@@ -213,19 +950,28 @@ impl AppConfig {
             !self.log.dir.as_os_str().is_empty(),
         ];
 
-        let ok = checks.iter().filter(|&&c| c).count();
-        let total = checks.len();
-        (ok, total)
+        ConfigStats {
+            ok: checks.iter().filter(|&&c| c).count(),
+            total: checks.len(),
+            admin_emails: self.admin.email.len(),
+        }
     }
 }
 
+/// 测试用：注入必需的环境变量后获取全局配置
+#[cfg(test)]
+pub fn test_global() -> Arc<AppConfig> {
+    tests::set_test_env();
+    AppConfig::global()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
 
     /// 设置测试环境变量
-    fn set_test_env() {
+    pub(super) fn set_test_env() {
         unsafe {
             env::set_var("QIDIAN_MINI_GITHUB_CLIENT_ID", "test_client_id");
         }
@@ -287,8 +1033,201 @@ mod tests {
         ));
 
         // 验证 stats 方法
-        let (ok, total) = config.stats();
-        assert_eq!(ok, total);
+        let stats = config.stats();
+        assert_eq!(stats.ok, stats.total);
+        assert_eq!(stats.admin_emails, config.admin.email.len());
+    }
+
+    fn admin(email: Vec<String>, require_admin: bool) -> AdminConfig {
+        AdminConfig {
+            email,
+            api_key: None,
+            require_admin,
+            notify_concurrency: 1,
+            notify_bcc: true,
+        }
+    }
+
+    #[test]
+    fn test_empty_admin_list() {
+        use std::io::Write;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Captured(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Captured {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(admin(vec![], false).warn_if_empty());
+            assert!(!admin(vec!["a@example.com".to_string()], false).warn_if_empty());
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output.matches("admin.emails is empty").count(), 1);
+        assert!(output.contains("WARN"));
+
+        // 开启 require_admin 后空列表视为配置错误
+        assert!(admin(vec![], false).validate().is_ok());
+        assert!(admin(vec![], true).validate().is_err());
+        assert!(
+            admin(vec!["a@example.com".to_string()], true)
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_audit_requires_hash_key() {
+        let audit = |path: Option<&str>, key: Option<&str>| AuditConfig {
+            path: path.map(PathBuf::from),
+            email_hash_key: key.map(|k| SecretBox::new(Box::new(k.to_string()))),
+        };
+        assert!(audit(None, None).validate().is_ok());
+        assert!(audit(Some("audit.jsonl"), Some("key")).validate().is_ok());
+        // 没有密钥时不能退化为可被彩虹表还原的普通摘要
+        assert!(audit(Some("audit.jsonl"), None).validate().is_err());
+    }
+
+    #[test]
+    fn test_webp_quality_range() {
+        let images = |webp_quality: u8| ImageConfig {
+            max_width: 0,
+            max_height: 0,
+            strip_metadata: true,
+            webp_quality,
+        };
+        assert!(images(0).validate().is_ok());
+        assert!(images(100).validate().is_ok());
+        assert!(images(101).validate().is_err());
+    }
+
+    #[test]
+    fn test_admin_emails_deduplicated() {
+        let emails = AdminConfig::parse_emails(vec![
+            "a@example.com".to_string(),
+            " 编辑 <b@example.com> ".to_string(),
+            "A@Example.com".to_string(),
+            "b@example.com".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(emails, ["a@example.com", "编辑 <b@example.com>"]);
+    }
+
+    #[test]
+    fn test_admin_emails_rejects_invalid() {
+        let err = AdminConfig::parse_emails(vec![
+            "a@example.com".to_string(),
+            "not-an-email".to_string(),
+            "b@".to_string(),
+        ])
+        .unwrap_err();
+        assert!(err.contains("not-an-email, b@"), "{}", err);
+        assert!(!err.contains("a@example.com"), "{}", err);
+    }
+
+    #[test]
+    fn test_reload_picks_up_file_changes() {
+        set_test_env();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let write = |emails: &str| {
+            std::fs::write(
+                &path,
+                format!("[admin]\nemails = [{emails}]\n\n[auth]\ncode_len = 6\n"),
+            )
+            .unwrap();
+        };
+
+        write(r#""a@example.com""#);
+        let store = ConfigStore::load(&path).unwrap();
+        let before = store.get();
+        assert_eq!(before.admin.email, ["a@example.com"]);
+
+        write(r#""b@example.com", "c@example.com""#);
+        let reloaded = store.reload().unwrap();
+        assert_eq!(reloaded.admin.email, ["b@example.com", "c@example.com"]);
+        assert_eq!(store.get().admin.email, reloaded.admin.email);
+        // 已取得的快照不受影响
+        assert_eq!(before.admin.email, ["a@example.com"]);
+        assert_eq!(
+            store.get().github.personal_access_token.expose_secret(),
+            "test_pat"
+        );
+
+        // 配置无效时保留原配置
+        write(r#""not-an-email""#);
+        assert!(store.reload().is_err());
+        assert_eq!(store.get().admin.email, reloaded.admin.email);
+    }
+
+    #[test]
+    fn test_reload_preserves_missing_secrets() {
+        let previous = SecretBox::new(Box::new("old".to_string()));
+        let names = ["QIDIAN_MINI_SMTP_PASSWORD", "SMTP_PASSWORD"];
+        let secret = secret_env(&|_| None, names, Some(&previous)).unwrap();
+        assert_eq!(secret.expose_secret(), "old");
+        assert!(secret_env(&|_| None, names, None).is_none());
+
+        // 环境变量仍存在时使用新值
+        let env = |name: &str| (name == "SMTP_PASSWORD").then(|| "new".to_string());
+        let secret = secret_env(&env, names, Some(&previous)).unwrap();
+        assert_eq!(secret.expose_secret(), "new");
+    }
+
+    #[test]
+    fn test_missing_secrets_reported_together() {
+        let err = AppConfig::load_with_env(Path::new(CONFIG_FILE), None, &|_| None).unwrap_err();
+        let missing = err
+            .downcast_ref::<MissingSecrets>()
+            .expect("应为缺少密钥错误");
+        assert_eq!(
+            missing.0,
+            [
+                "QIDIAN_MINI_GITHUB_CLIENT_ID (or GITHUB_CLIENT_ID)",
+                "QIDIAN_MINI_GITHUB_CLIENT_SECRET (or GITHUB_CLIENT_SECRET)",
+                "QIDIAN_MINI_GITHUB_PAT (or GITHUB_PAT)",
+                "QIDIAN_MINI_SMTP_PASSWORD (or SMTP_PASSWORD)",
+            ]
+        );
+        assert!(
+            err.to_string().starts_with(
+                "missing required environment variables: QIDIAN_MINI_GITHUB_CLIENT_ID"
+            )
+        );
+    }
+
+    #[test]
+    fn test_submission_test_shortcut() {
+        set_test_env();
+        let mut config = AppConfig::load_config().unwrap().submission;
+        config.test_title = "联调".to_string();
+        config.test_author = "前端".to_string();
+
+        // 默认关闭
+        config.test_shortcut = false;
+        assert!(!config.is_test_submission("联调", "前端"));
+
+        config.test_shortcut = true;
+        assert!(config.is_test_submission(" 联调 ", "前端\n"));
+        assert!(!config.is_test_submission("联调", "作者"));
+        assert!(!config.is_test_submission("测试", "测试"));
     }
 
     #[test]
@@ -299,6 +1238,34 @@ mod tests {
         let global2 = AppConfig::global();
 
         // 应该是同一个实例
-        assert_eq!(global1 as *const _, global2 as *const _);
+        assert!(Arc::ptr_eq(&global1, &global2));
+    }
+
+    #[test]
+    fn test_restart_required_changes() {
+        set_test_env();
+        let previous = AppConfig::load_config().unwrap();
+        let mut current = AppConfig::load_config().unwrap();
+        assert!(current.restart_required_changes(&previous).is_empty());
+
+        // 日志级别、管理员邮箱等可以直接生效的改动不在其中
+        current.log.level = LogLevel::Trace;
+        current.admin.email = vec!["x@example.com".to_string()];
+        assert!(current.restart_required_changes(&previous).is_empty());
+
+        current.port += 1;
+        current.log.dir = PathBuf::from("elsewhere");
+        assert_eq!(
+            current.restart_required_changes(&previous),
+            ["port", "log.dir"]
+        );
+
+        // 只在构造路由时读取的开关
+        current.openapi = !previous.openapi;
+        current.submission.dry_run = !previous.submission.dry_run;
+        assert_eq!(
+            current.restart_required_changes(&previous),
+            ["port", "app.openapi", "log.dir", "submission.dry_run"]
+        );
     }
 }
@@ -0,0 +1,80 @@
+use crate::config::ConfigStats;
+use crate::middleware::maintenance::Maintenance;
+use crate::middleware::request_id::RequestId;
+use crate::response::{ApiResponse, ErrorCode};
+use crate::utils::reload;
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+#[derive(Deserialize)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+}
+
+// 切换维护模式
+#[instrument(skip(payload), fields(enabled = payload.enabled))]
+pub async fn set_maintenance(
+    Extension(request_id): Extension<RequestId>,
+    Extension(maintenance): Extension<Maintenance>,
+    Json(payload): Json<MaintenanceRequest>,
+) -> ApiResponse<MaintenanceStatus> {
+    maintenance.set_enabled(payload.enabled);
+    info!(
+        target: "audit",
+        action = "maintenance",
+        enabled = payload.enabled,
+        "ADMIN_MAINTENANCE: mode updated"
+    );
+    ApiResponse::success_with_id(
+        MaintenanceStatus {
+            enabled: maintenance.is_enabled(),
+        },
+        request_id,
+    )
+}
+
+// 查询维护模式
+pub async fn get_maintenance(
+    Extension(request_id): Extension<RequestId>,
+    Extension(maintenance): Extension<Maintenance>,
+) -> ApiResponse<MaintenanceStatus> {
+    ApiResponse::success_with_id(
+        MaintenanceStatus {
+            enabled: maintenance.is_enabled(),
+        },
+        request_id,
+    )
+}
+
+// 重新加载配置文件与环境变量并应用到日志、SMTP 等组件，失败时保留原配置
+#[instrument(skip_all)]
+pub async fn reload_config(
+    Extension(request_id): Extension<RequestId>,
+) -> ApiResponse<ConfigStats> {
+    match reload::reload_config() {
+        Ok(config) => {
+            info!(
+                target: "audit",
+                action = "config_reload",
+                "ADMIN_RELOAD: config reloaded"
+            );
+            ApiResponse::success_with_id(config.stats(), request_id)
+        }
+        Err(e) => {
+            warn!("ADMIN_RELOAD: reload failed, keeping current config: {}", e);
+            ApiResponse::error_with_code(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorCode::InvalidConfig,
+                &format!("配置重新加载失败: {}", e),
+                request_id,
+            )
+        }
+    }
+}
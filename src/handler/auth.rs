@@ -1,19 +1,25 @@
+use crate::config::{AppConfig, CodeKind};
 use crate::middleware::mem_map::{MemMap, ToKey};
 use crate::middleware::request_id::RequestId;
-use crate::response::ApiResponse;
+use crate::response::{ApiResponse, ErrorCode};
 use crate::to_key;
-use crate::utils::email::{Mailer, SmtpMailer};
+use crate::utils::email::{Mailer, SmtpMailer, is_valid_email};
+use crate::utils::messages::{self, Lang, Msg};
+use axum::http::HeaderMap;
 use axum::{Extension, extract::Json, http::StatusCode};
 use chrono::Duration;
 use rand::Rng;
-use rand::distr::Alphanumeric;
-use serde::Deserialize;
+use rand::distr::{Alphanumeric, Uniform};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::{debug, info, instrument, warn};
+use tracing::{Span, debug, info, instrument, warn};
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct SendCodeRequest {
     pub email: String,
+    /// 验证码邮件的语言（`zh` / `en`），未填写时按 `Accept-Language` 选择，默认中文
+    pub lang: Option<String>,
 }
 
 pub struct EmailVerifyKey {
@@ -32,20 +38,134 @@ impl EmailVerifyKey {
 
 to_key!(EmailVerifyKey; module=module; email);
 
+/// 验证码错误次数，与验证码共用同一 TTL 窗口
+pub struct EmailAttemptKey {
+    pub module: &'static str,
+    pub email: String,
+}
+
+impl EmailAttemptKey {
+    pub fn new(email: impl Into<String>) -> Self {
+        Self {
+            module: "email-attempt",
+            email: email.into(),
+        }
+    }
+}
+
+to_key!(EmailAttemptKey; module=module; email);
+
+/// 验证码发送冷却，存在期间同一邮箱不能再次发送
+pub struct EmailCooldownKey {
+    pub module: &'static str,
+    pub email: String,
+}
+
+impl EmailCooldownKey {
+    pub fn new(email: impl Into<String>) -> Self {
+        Self {
+            module: "email-cooldown",
+            email: email.into(),
+        }
+    }
+}
+
+to_key!(EmailCooldownKey; module=module; email);
+
+#[derive(Deserialize)]
+pub struct VerifyRequest {
+    pub email: String,
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct VerifyResponse {
+    pub verification_token: String,
+}
+
+/// 一次性验证令牌，与邮箱绑定，同一邮箱只保留最新的一个
+pub struct VerificationTokenKey {
+    pub module: &'static str,
+    pub email: String,
+}
+
+impl VerificationTokenKey {
+    pub fn new(email: impl Into<String>) -> Self {
+        Self {
+            module: "email-token",
+            email: email.into(),
+        }
+    }
+}
+
+to_key!(VerificationTokenKey; module=module; email);
+
+/// 按字符集生成指定长度的验证码
+pub fn generate_code(len: usize, kind: CodeKind) -> String {
+    let rng = rand::rng();
+    match kind {
+        CodeKind::Numeric => rng
+            .sample_iter(Uniform::new_inclusive(b'0', b'9').expect("数字范围有效"))
+            .take(len)
+            .map(char::from)
+            .collect(),
+        CodeKind::Alphanumeric => rng
+            .sample_iter(&Alphanumeric)
+            .take(len)
+            .map(char::from)
+            .collect(),
+    }
+}
+
+/// 生成并发送验证码，`cooldown` 为零时不做发送频率限制，邮件与提示使用 `lang`
 #[instrument(skip(mailer, payload), fields(email = %payload.email))]
 pub async fn do_send_code(
     RequestId(request_id): RequestId,
     Json(payload): Json<SendCodeRequest>,
     mailer: Arc<dyn Mailer>,
+    cooldown: Duration,
+    lang: Lang,
 ) -> ApiResponse<String> {
+    // 地址不合法时直接拒绝，不生成也不缓存验证码
+    if !is_valid_email(&payload.email) {
+        warn!("AUTH_SEND_CODE: invalid email address");
+        return ApiResponse::error_with_code(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidEmail,
+            &format!("邮箱格式不正确: {}", payload.email),
+            request_id.into(),
+        );
+    }
+
     let cache = MemMap::global();
 
-    // 生成6位验证码
-    let code: String = rand::rng()
-        .sample_iter(&Alphanumeric)
-        .take(6)
-        .map(char::from)
-        .collect();
+    // 冷却期内拒绝再次发送；先占位再发信，检查与占位是同一次写入，并发请求只有一个能通过
+    let cooldown_key = EmailCooldownKey::new(payload.email.clone());
+    if cooldown > Duration::zero()
+        && let Err(remaining) = cache.insert_protected_if_absent(
+            EmailCooldownKey::new(payload.email.clone()),
+            (),
+            cooldown,
+        )
+    {
+        warn!(
+            remaining_secs = remaining.num_seconds(),
+            "AUTH_SEND_CODE: rejected within cooldown"
+        );
+        return ApiResponse::error_with_code(
+            StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::RateLimited,
+            &format!(
+                "验证码发送过于频繁，请 {} 秒后再试",
+                remaining.num_seconds().max(1)
+            ),
+            request_id.into(),
+        );
+    }
+
+    // 按配置生成验证码
+    let auth = &AppConfig::global().auth;
+    let code = generate_code(auth.code_len, auth.code_kind);
 
     debug!("AUTH_SEND_CODE: code generated");
 
@@ -53,21 +173,39 @@ pub async fn do_send_code(
     let key = EmailVerifyKey::new(payload.email.clone());
     let ttl = Duration::minutes(5);
     cache.insert(key, code.clone(), ttl);
+    // 新验证码重新计算错误次数
+    cache.remove(&EmailAttemptKey::new(payload.email.clone()));
     debug!(
         "AUTH_SEND_CODE: code saved to cache, ttl={}s",
         ttl.num_seconds()
     );
 
+    // 同步发信在重试退避时会 sleep，放到阻塞线程池中执行，不占用 tokio 工作线程
+    let to = payload.email.clone();
+    let ttl_minutes = ttl.num_minutes();
+    let span = Span::current();
+    let sent = tokio::task::spawn_blocking(move || {
+        span.in_scope(|| mailer.send_code(&to, &code, ttl_minutes, lang))
+    })
+    .await
+    .unwrap_or_else(|e| Err(anyhow::anyhow!("发信任务异常退出: {}", e)));
+
     // 发送验证码
-    match mailer.send_code(&payload.email, &code) {
+    match sent {
         Ok(_) => {
             info!(status = "success", "AUTH_SEND_CODE: mail sent");
-            ApiResponse::success(format!("验证码已发送到 {}", payload.email))
+            ApiResponse::success_with_id(
+                messages::render(lang, Msg::CodeSent, &[("email", &payload.email)]),
+                request_id.into(),
+            )
         }
         Err(e) => {
             warn!(status = "failed", error = %e, "AUTH_SEND_CODE: mail send failed");
-            ApiResponse::error(
+            // 未发出的验证码不占用冷却时间
+            cache.remove(&cooldown_key);
+            ApiResponse::error_with_code(
                 StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::MailFailed,
                 &format!("邮件发送失败: {}", e),
                 request_id.into(),
             )
@@ -76,38 +214,199 @@ pub async fn do_send_code(
 }
 
 // 发送验证码
-#[instrument(skip(payload), fields(email = %payload.email))]
+#[utoipa::path(
+    post,
+    path = "/auth/send",
+    tag = "auth",
+    request_body = SendCodeRequest,
+    responses(
+        (status = 200, description = "验证码已发送", body = ApiResponse<String>),
+        (status = 429, description = "发送过于频繁", body = ApiResponse<String>),
+    )
+)]
+#[instrument(skip(headers, payload), fields(email = %payload.email))]
 pub async fn send_code(
     Extension(RequestId(request_id)): Extension<RequestId>,
+    headers: HeaderMap,
     Json(payload): Json<SendCodeRequest>,
 ) -> ApiResponse<String> {
     let mailer = SmtpMailer::global();
     info!("AUTH_SEND_CODE: request received");
-    do_send_code(request_id.into(), Json::from(payload), mailer.clone()).await
+    let cooldown = Duration::seconds(AppConfig::global().auth.send_cooldown_secs);
+    let lang = Lang::negotiate(payload.lang.as_deref(), &headers);
+    do_send_code(
+        request_id.into(),
+        Json::from(payload),
+        mailer.clone(),
+        cooldown,
+        lang,
+    )
+    .await
+}
+
+/// 管理员补发验证码：不受发送冷却限制，但会记录审计事件
+#[instrument(skip(mailer, payload), fields(email = %payload.email))]
+pub async fn do_reissue_code(
+    RequestId(request_id): RequestId,
+    Json(payload): Json<SendCodeRequest>,
+    mailer: Arc<dyn Mailer>,
+) -> ApiResponse<String> {
+    info!(
+        target: "audit",
+        action = "auth_reissue",
+        email = %payload.email,
+        request_id = %request_id,
+        "ADMIN_REISSUE_CODE: code reissued by admin"
+    );
+    let lang = payload
+        .lang
+        .as_deref()
+        .and_then(Lang::parse)
+        .unwrap_or_default();
+    do_send_code(
+        request_id.into(),
+        Json::from(payload),
+        mailer,
+        Duration::zero(),
+        lang,
+    )
+    .await
+}
+
+// 管理员补发验证码
+#[instrument(skip(payload), fields(email = %payload.email))]
+pub async fn reissue_code(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    Json(payload): Json<SendCodeRequest>,
+) -> ApiResponse<String> {
+    let mailer = SmtpMailer::global();
+    info!("ADMIN_REISSUE_CODE: request received");
+    do_reissue_code(request_id.into(), Json::from(payload), mailer.clone()).await
+}
+
+/// 检查验证码但不消费；错误时累计次数，达到上限后验证码作废
+fn check_code(email: &str, code: &str) -> bool {
+    let cache = MemMap::global();
+    let key = EmailVerifyKey::new(email);
+
+    let Some(expected) = cache.get::<EmailVerifyKey, String>(&key) else {
+        return false;
+    };
+    if expected == code {
+        return true;
+    }
+
+    let attempt_key = EmailAttemptKey::new(email);
+    let attempts = cache.get::<EmailAttemptKey, u32>(&attempt_key).unwrap_or(0) + 1;
+    if attempts >= AppConfig::global().auth.max_attempts {
+        cache.remove(&key);
+        cache.remove(&attempt_key);
+        warn!(%email, attempts, "AUTH_VERIFY_CODE: too many attempts, code revoked");
+    } else if let Some(ttl) = cache.ttl_remaining(&key) {
+        // 错误次数被其他条目挤出缓存就等于重置了限制，不参与容量淘汰
+        cache.insert_protected(attempt_key, attempts, ttl);
+    }
+    false
 }
 
 // 验证验证码
 #[instrument(skip(code), fields(email = %email))]
 pub fn verify_code(email: String, code: String) -> bool {
+    let valid = check_code(&email, &code);
+
+    if valid {
+        let cache = MemMap::global();
+        cache.remove(&EmailVerifyKey::new(email.clone()));
+        cache.remove(&EmailAttemptKey::new(email.clone()));
+        info!(status = "success", %email, "AUTH_VERIFY_CODE: success");
+    } else {
+        warn!(status = "failed", %email, "AUTH_VERIFY_CODE: failed");
+    }
+
+    valid
+}
+
+/// 为已通过验证的邮箱签发一次性令牌
+fn issue_verification_token(email: &str) -> String {
+    let token: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+
+    let ttl = Duration::minutes(10);
+    MemMap::global().insert(VerificationTokenKey::new(email), token.clone(), ttl);
+    debug!(
+        "AUTH_VERIFY: verification token issued, ttl={}s",
+        ttl.num_seconds()
+    );
+    token
+}
+
+// 预先验证验证码（peek 模式）：验证码保持有效，同时返回可代替验证码的一次性令牌
+#[instrument(skip(payload), fields(email = %payload.email))]
+pub async fn verify(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    Json(payload): Json<VerifyRequest>,
+) -> ApiResponse<VerifyResponse> {
+    info!("AUTH_VERIFY: request received");
+
+    if !check_code(&payload.email, &payload.code) {
+        warn!(status = "failed", "AUTH_VERIFY: code mismatch or expired");
+        return ApiResponse::error_with_code(
+            StatusCode::UNAUTHORIZED,
+            ErrorCode::InvalidCode,
+            "验证码错误或已过期",
+            request_id.into(),
+        );
+    }
+
+    info!(status = "success", "AUTH_VERIFY: code verified");
+    ApiResponse::success_with_id(
+        VerifyResponse {
+            verification_token: issue_verification_token(&payload.email),
+        },
+        request_id.into(),
+    )
+}
+
+/// 校验并消费一次性令牌，成功后该邮箱的验证码一并失效
+#[instrument(skip(token), fields(email = %email))]
+pub fn verify_token(email: String, token: String) -> bool {
     let cache = MemMap::global();
-    let key = EmailVerifyKey::new(email.clone());
+    let key = VerificationTokenKey::new(email.clone());
 
-    let valid = matches!(cache.get::<EmailVerifyKey, String>(&key), Some(v) if v == code);
+    let valid = matches!(cache.get::<VerificationTokenKey, String>(&key), Some(v) if v == token);
 
     if valid {
         cache.remove(&key);
-        info!(status = "success", %email, "AUTH_VERIFY_CODE: success");
+        cache.remove(&EmailVerifyKey::new(email.clone()));
+        cache.remove(&EmailAttemptKey::new(email.clone()));
+        info!(status = "success", %email, "AUTH_VERIFY_TOKEN: success");
     } else {
-        warn!(status = "failed", %email, "AUTH_VERIFY_CODE: failed");
+        warn!(status = "failed", %email, "AUTH_VERIFY_TOKEN: failed");
     }
 
     valid
 }
 
+/// 优先使用一次性令牌，否则退回验证码校验
+pub fn verify_code_or_token(email: String, code: Option<String>, token: Option<String>) -> bool {
+    match (token, code) {
+        (Some(token), _) => verify_token(email, token),
+        (None, Some(code)) => verify_code(email, code),
+        (None, None) => {
+            warn!(%email, "AUTH_VERIFY: neither code nor token provided");
+            false
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::middleware::mem_map::MemMap;
+    use crate::utils::email::SendParams;
     use axum::extract::Json;
     use axum::response::IntoResponse;
     use http_body_util::BodyExt;
@@ -121,27 +420,36 @@ mod tests {
     }
 
     impl Mailer for MockMailer {
-        fn send(&self, to: &str, subject: &str, body: &str) -> anyhow::Result<()> {
-            self.sent
-                .lock()
-                .unwrap()
-                .push((to.to_string(), subject.to_string(), body.to_string()));
+        fn send_full(&self, params: SendParams<'_>) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push((
+                params.to.unwrap_or_default().to_string(),
+                params.subject.to_string(),
+                params.body.to_string(),
+            ));
             Ok(())
         }
     }
 
     #[tokio::test]
     async fn test_send_and_verify_code() {
+        crate::config::test_global();
         let email = "test@example.com".to_string();
         let mailer = Arc::new(MockMailer::default());
 
         // 发送验证码
         let send_req = SendCodeRequest {
             email: email.clone(),
+            lang: None,
         };
-        let resp = do_send_code(RequestId(Uuid::new_v4()), Json(send_req), mailer.clone())
-            .await
-            .into_response();
+        let resp = do_send_code(
+            RequestId(Uuid::new_v4()),
+            Json(send_req),
+            mailer.clone(),
+            Duration::zero(),
+            Lang::default(),
+        )
+        .await
+        .into_response();
         let body = resp.into_body();
         let bytes = body.collect().await.unwrap().to_bytes();
         let text = String::from_utf8(bytes.to_vec()).unwrap();
@@ -168,6 +476,246 @@ mod tests {
         assert!(resp);
         assert!(cache.get::<EmailVerifyKey, String>(&key).is_none());
     }
+    /// 每次发送都阻塞一段时间，模拟重试退避中的同步发信
+    struct SlowMailer(std::time::Duration);
+
+    impl Mailer for SlowMailer {
+        fn send_full(&self, _params: SendParams<'_>) -> anyhow::Result<()> {
+            std::thread::sleep(self.0);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_code_does_not_block_runtime() {
+        use std::time::Instant;
+
+        crate::config::test_global();
+        let send = async {
+            let resp = do_send_code(
+                RequestId(Uuid::new_v4()),
+                Json(SendCodeRequest {
+                    email: "slow@example.com".to_string(),
+                    lang: None,
+                }),
+                Arc::new(SlowMailer(std::time::Duration::from_millis(300))),
+                Duration::zero(),
+                Lang::default(),
+            )
+            .await;
+            (resp.code, Instant::now())
+        };
+        let tick = async {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            Instant::now()
+        };
+
+        // 单线程运行时上，发信期间其他任务仍能推进
+        let ((code, sent_at), ticked_at) = tokio::join!(send, tick);
+        assert_eq!(code, 200);
+        assert!(ticked_at < sent_at);
+    }
+
+    #[tokio::test]
+    async fn test_send_code_cooldown() {
+        crate::config::test_global();
+        let email = "cooldown@example.com".to_string();
+        let mailer = Arc::new(MockMailer::default());
+        let cooldown = Duration::milliseconds(300);
+        let send = || {
+            do_send_code(
+                RequestId(Uuid::new_v4()),
+                Json(SendCodeRequest {
+                    email: email.clone(),
+                    lang: None,
+                }),
+                mailer.clone(),
+                cooldown,
+                Lang::default(),
+            )
+        };
+
+        assert_eq!(send().await.code, 200);
+        // 冷却期内再次发送被拒绝，且不会发出邮件
+        let resp = send().await;
+        assert_eq!(resp.code, 429);
+        assert!(resp.message.contains("秒后再试"), "{}", resp.message);
+        assert_eq!(mailer.sent.lock().unwrap().len(), 1);
+
+        // 冷却结束后可以再次发送
+        tokio::time::sleep(cooldown.to_std().unwrap()).await;
+        assert_eq!(send().await.code, 200);
+        assert_eq!(mailer.sent.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reissue_code_within_cooldown() {
+        crate::config::test_global();
+        let email = "reissue@example.com".to_string();
+        let mailer = Arc::new(MockMailer::default());
+
+        // 正常发送一次
+        let send_req = SendCodeRequest {
+            email: email.clone(),
+            lang: None,
+        };
+        do_send_code(
+            RequestId(Uuid::new_v4()),
+            Json(send_req),
+            mailer.clone(),
+            Duration::seconds(60),
+            Lang::default(),
+        )
+        .await;
+
+        // 紧接着由管理员补发，应当照常发出新验证码
+        let reissue_req = SendCodeRequest {
+            email: email.clone(),
+            lang: None,
+        };
+        let resp = do_reissue_code(RequestId(Uuid::new_v4()), Json(reissue_req), mailer.clone())
+            .await
+            .into_response();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(text.contains("验证码已发送到"));
+
+        let sent = mailer.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|(to, _, _)| to == &email));
+
+        // 缓存中的验证码应为补发的那一封
+        let key = EmailVerifyKey::new(email.clone());
+        let code_in_cache = MemMap::global()
+            .get::<EmailVerifyKey, String>(&key)
+            .expect("验证码应存在缓存中");
+        assert!(sent[1].2.contains(&code_in_cache));
+    }
+
+    #[test]
+    fn test_generate_code() {
+        for len in [4, 6, 8] {
+            let code = generate_code(len, CodeKind::Numeric);
+            assert_eq!(code.chars().count(), len);
+            assert!(code.chars().all(|c| c.is_ascii_digit()), "{}", code);
+
+            let code = generate_code(len, CodeKind::Alphanumeric);
+            assert_eq!(code.len(), len);
+            assert!(code.chars().all(|c| c.is_ascii_alphanumeric()), "{}", code);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_issues_one_time_token() {
+        crate::config::test_global();
+        let email = "token@example.com".to_string();
+        MemMap::global().insert(
+            EmailVerifyKey::new(email.clone()),
+            "abc123".to_string(),
+            Duration::minutes(5),
+        );
+
+        // 错误的验证码不签发令牌
+        let resp = verify(
+            Extension(RequestId(Uuid::new_v4())),
+            Json(VerifyRequest {
+                email: email.clone(),
+                code: "wrong".to_string(),
+            }),
+        )
+        .await;
+        assert_eq!(resp.code, 401);
+
+        let resp = verify(
+            Extension(RequestId(Uuid::new_v4())),
+            Json(VerifyRequest {
+                email: email.clone(),
+                code: "abc123".to_string(),
+            }),
+        )
+        .await;
+        let token = resp.data.expect("应返回令牌").verification_token;
+
+        // peek 模式不消费验证码
+        assert!(check_code(&email, "abc123"));
+        // 令牌与邮箱绑定
+        assert!(!verify_token(
+            "other@example.com".to_string(),
+            token.clone()
+        ));
+        // 令牌只能使用一次，使用后验证码也随之失效
+        assert!(verify_code_or_token(
+            email.clone(),
+            None,
+            Some(token.clone())
+        ));
+        assert!(!verify_token(email.clone(), token));
+        assert!(!verify_code(email, "abc123".to_string()));
+    }
+
+    fn insert_code(email: &str, code: &str) {
+        MemMap::global().insert(
+            EmailVerifyKey::new(email),
+            code.to_string(),
+            Duration::minutes(5),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attempts_exhausted() {
+        let max = crate::config::test_global().auth.max_attempts;
+        let email = "brute@example.com".to_string();
+        insert_code(&email, "abc123");
+
+        for _ in 0..max {
+            assert!(!verify_code(email.clone(), "wrong".to_string()));
+        }
+        // 达到上限后验证码作废，正确的验证码也无法通过
+        assert!(
+            MemMap::global()
+                .get::<EmailVerifyKey, String>(&EmailVerifyKey::new(email.clone()))
+                .is_none()
+        );
+        assert!(!verify_code(email.clone(), "abc123".to_string()));
+        assert!(!check_code(&email, "abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_correct_code_under_limit() {
+        let max = crate::config::test_global().auth.max_attempts;
+        let email = "under-limit@example.com".to_string();
+        insert_code(&email, "abc123");
+
+        for _ in 0..max - 1 {
+            assert!(!verify_code(email.clone(), "wrong".to_string()));
+        }
+        assert!(verify_code(email.clone(), "abc123".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_attempts_reset_after_success() {
+        let max = crate::config::test_global().auth.max_attempts;
+        let email = "reset@example.com".to_string();
+        insert_code(&email, "abc123");
+
+        for _ in 0..max - 1 {
+            assert!(!verify_code(email.clone(), "wrong".to_string()));
+        }
+        assert!(verify_code(email.clone(), "abc123".to_string()));
+        assert!(
+            MemMap::global()
+                .get::<EmailAttemptKey, u32>(&EmailAttemptKey::new(email.clone()))
+                .is_none()
+        );
+
+        // 新验证码重新获得完整的尝试次数
+        insert_code(&email, "def456");
+        for _ in 0..max - 1 {
+            assert!(!verify_code(email.clone(), "wrong".to_string()));
+        }
+        assert!(verify_code(email, "def456".to_string()));
+    }
+
     #[tokio::test]
     async fn test_key_name() {
         struct TestKey {
@@ -188,4 +736,56 @@ mod tests {
         };
         assert_eq!(key.to_key(), "email-verify@test@example.com");
     }
+
+    #[tokio::test]
+    async fn test_send_code_rejects_invalid_email() {
+        crate::config::test_global();
+        let mailer = Arc::new(MockMailer::default());
+
+        let email = "not-an-email".to_string();
+        let resp = do_send_code(
+            RequestId::new(),
+            Json(SendCodeRequest {
+                email: email.clone(),
+                lang: None,
+            }),
+            mailer.clone(),
+            Duration::seconds(60),
+            Lang::default(),
+        )
+        .await;
+        assert_eq!(resp.code, 400);
+        assert!(resp.message.contains("邮箱格式不正确"), "{}", resp.message);
+
+        // 拒绝时既不发信，也不写入验证码和冷却
+        assert!(mailer.sent.lock().unwrap().is_empty());
+        let cache = MemMap::global();
+        assert!(
+            cache
+                .get::<EmailVerifyKey, String>(&EmailVerifyKey::new(email.clone()))
+                .is_none()
+        );
+        assert!(cache.ttl_remaining(&EmailCooldownKey::new(email)).is_none());
+
+        // 合法地址正常发送
+        let email = "valid-address@example.com".to_string();
+        let resp = do_send_code(
+            RequestId::new(),
+            Json(SendCodeRequest {
+                email: email.clone(),
+                lang: None,
+            }),
+            mailer.clone(),
+            Duration::zero(),
+            Lang::default(),
+        )
+        .await;
+        assert_eq!(resp.code, 200, "{}", resp.message);
+        assert_eq!(mailer.sent.lock().unwrap().len(), 1);
+        assert!(
+            cache
+                .get::<EmailVerifyKey, String>(&EmailVerifyKey::new(email))
+                .is_some()
+        );
+    }
 }
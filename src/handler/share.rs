@@ -1,27 +1,47 @@
-use crate::config::AppConfig;
-use crate::handler::auth::verify_code;
+use crate::handler::auth::verify_code_or_token;
+use crate::middleware::background::notify_admins_background;
 use crate::middleware::request_id::RequestId;
-use crate::response::ApiResponse;
-use crate::utils::email::{Mailer, SmtpMailer};
-use crate::utils::file::ShareFile;
+use crate::response::{ApiResponse, ErrorCode, prefers_raw};
+use crate::utils::email::{AsyncMailer, AsyncSmtpMailer};
+use crate::utils::file::{ListSort, ShareFile, ShareFileInfo, SortOrder, sort_entries};
+use crate::utils::messages::{self, Lang, Msg};
+use crate::utils::notify::{Notification, NotificationEvent};
+use crate::utils::upload::TmpfileBackend;
 use anyhow::Context;
-use axum::http::StatusCode;
+use axum::extract::Query;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
 use axum::{Extension, Json};
-use chrono::{DateTime, Local, Utc};
-use serde::Deserialize;
-use tracing::{error, info, instrument, warn};
-use crate::middleware::background::send_mail_background;
+use chrono::{DateTime, Duration, Local, Utc};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, instrument, warn};
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct ShareRequest {
     pub applicant: String,
     pub apply_for: String,
     pub email: String,
-    pub email_code: String,
+    pub email_code: Option<String>,
+    /// `/auth/verify` 签发的一次性令牌，可代替 `email_code`
+    pub verification_token: Option<String>,
+    /// 通知邮件的语言（`zh` / `en`），未填写时按 `Accept-Language` 选择，默认中文
+    pub lang: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/share/get_file",
+    tag = "share",
+    request_body = ShareRequest,
+    responses(
+        (status = 200, description = "下载链接已发送至邮箱", body = ApiResponse<String>),
+        (status = 401, description = "验证码错误或已过期", body = ApiResponse<String>),
+    )
+)]
 #[instrument(
-    skip(payload),
+    skip(headers, payload),
     fields(
         applicant = %payload.applicant,
         email     = %payload.email,
@@ -30,15 +50,21 @@ pub struct ShareRequest {
 )]
 pub async fn share_files(
     Extension(RequestId(request_id)): Extension<RequestId>,
+    headers: HeaderMap,
     Json(payload): Json<ShareRequest>,
 ) -> ApiResponse<()> {
     info!("SHARE_FILES: request received");
 
-    // 校验验证码（不记录 code）
-    if !verify_code(payload.email.clone(), payload.email_code.clone()) {
+    // 校验验证码或一次性令牌（不记录 code）
+    if !verify_code_or_token(
+        payload.email.clone(),
+        payload.email_code.clone(),
+        payload.verification_token.clone(),
+    ) {
         warn!("SHARE_FILES: verify_code failed");
-        return ApiResponse::error(
+        return ApiResponse::error_with_code(
             StatusCode::UNAUTHORIZED,
+            ErrorCode::InvalidCode,
             "验证码错误或已过期",
             request_id.into(),
         );
@@ -46,7 +72,7 @@ pub async fn share_files(
     info!("SHARE_FILES: verify_code success");
 
     // 获取文件（缓存 + 上传 tmpfile.link）
-    let file = match ShareFile::get(&payload.apply_for).await {
+    let file = match ShareFile::get(&payload.apply_for, &TmpfileBackend).await {
         Ok(file) => {
             info!(
                 "SHARE_FILES: file fetched, name={}, size={}",
@@ -56,8 +82,9 @@ pub async fn share_files(
         }
         Err(e) => {
             error!("SHARE_FILES: get file failed: {:#}", e);
-            return ApiResponse::error(
+            return ApiResponse::error_with_code(
                 StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::StorageError,
                 &format!("获取文件失败: {:#}", e),
                 request_id.into(),
             );
@@ -74,30 +101,14 @@ pub async fn share_files(
         })
         .unwrap_or_else(|| format!("无效时间戳: {}", file.timestamp));
 
-    // 邮件构造
-    let subject_user = format!("文件分享通知 - {}", file.file_name);
-    let body_user = format!(
-        "尊敬的 {}，您好：\n\n\
-        您申请的文件已准备就绪，可通过以下链接下载：\n\n\
-        下载地址：{}\n\
-        文件名：{}\n\
-        文件大小：{} 字节\n\
-        生成时间：{}\n\n\
-        链接有效期为 24 小时，请尽快下载。\n\n\
-        —— 系统自动发送，请勿回复。",
-        payload.applicant, file.download_link, file.file_name, file.size, formatted_time,
-    );
-
-    let mailer = SmtpMailer::global();
-
     // 发给用户
-    if let Err(e) = mailer
-        .send(&payload.email, &subject_user, &body_user)
-        .context("发送文件通知邮件失败")
-    {
+    let mailer = AsyncSmtpMailer::global();
+    let lang = Lang::negotiate(payload.lang.as_deref(), &headers);
+    if let Err(e) = send_share_mail(mailer.as_ref(), &payload, &file, &formatted_time, lang).await {
         error!("SHARE_FILES: send mail to user failed: {:#}", e);
-        return ApiResponse::error(
+        return ApiResponse::error_with_code(
             StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::MailFailed,
             &format!("邮件发送失败: {:#}", e),
             request_id.into(),
         );
@@ -105,7 +116,6 @@ pub async fn share_files(
     info!("SHARE_FILES: mail sent to user");
 
     // 通知管理员（不会阻断主流程）
-    let admin_emails = AppConfig::global().admin.email.clone();
     let subject_admin = format!("用户申请文件下载 - {}", payload.applicant);
     let body_admin = format!(
         "用户 {} ({}) 申请下载文件：{}\n\
@@ -114,35 +124,524 @@ pub async fn share_files(
         payload.applicant, payload.email, file.file_name, file.download_link, formatted_time,
     );
 
-    for admin_email in admin_emails {
-        send_mail_background(
-            mailer.clone(),
-            admin_email.clone(),
-            subject_admin.clone(),
-            body_admin.clone(),
-        );
+    if let Err(e) = notify_admins_background(Notification {
+        event: NotificationEvent::Share,
+        subject: subject_admin,
+        body: body_admin,
+        url: Some(file.download_link.clone()),
+        reply_to: Some(payload.email.clone()),
+    }) {
+        warn!("SHARE_FILES: admin notification dropped: {}", e);
     }
 
     info!("SHARE_FILES: completed");
-    ApiResponse::success(())
+    ApiResponse::success_with_id((), request_id.into())
 }
 
-#[instrument(name = "share_list_files", fields(module = "share"))]
-pub async fn list_files(
+/// 把下载链接发给申请人
+async fn send_share_mail(
+    mailer: &dyn AsyncMailer,
+    payload: &ShareRequest,
+    file: &ShareFile,
+    formatted_time: &str,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let subject = messages::render(lang, Msg::ShareSubject, &[("file", &file.file_name)]);
+    let body = messages::render(
+        lang,
+        Msg::ShareBody,
+        &[
+            ("applicant", &payload.applicant),
+            ("link", &file.download_link),
+            ("file", &file.file_name),
+            ("size", &file.size.to_string()),
+            ("md5", &file.md5),
+            ("sha256", &file.sha256),
+            ("time", formatted_time),
+        ],
+    );
+
+    mailer
+        .send(&payload.email, &subject, &body)
+        .await
+        .context("发送文件通知邮件失败")
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileInfoQuery {
+    pub name: String,
+}
+
+/// 查询分享文件的大小与校验值，不会触发上传
+#[instrument(name = "share_file_info", fields(module = "share"))]
+pub async fn file_info(
     Extension(RequestId(request_id)): Extension<RequestId>,
-) -> ApiResponse<Vec<String>> {
-    match ShareFile::list().await {
-        Ok(files) => {
-            info!("SHARE_LIST: list files success, count={}", files.len());
-            ApiResponse::success(files)
+    Query(query): Query<FileInfoQuery>,
+) -> ApiResponse<ShareFileInfo> {
+    match ShareFile::info(&query.name).await {
+        Ok(info) => {
+            info!(
+                "SHARE_FILE_INFO: success, name={}, size={}",
+                info.file_name, info.size
+            );
+            ApiResponse::success_with_id(info, request_id.into())
+        }
+        Err(e) => {
+            error!("SHARE_FILE_INFO: failed: {:#}", e);
+            ApiResponse::error_with_code(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::StorageError,
+                &format!("获取文件信息失败: {:#}", e),
+                request_id.into(),
+            )
+        }
+    }
+}
+
+/// 只给出 `per_page` 时的默认页码与只给出 `page` 时的默认每页数量
+const DEFAULT_PAGE: usize = 1;
+const DEFAULT_PER_PAGE: usize = 20;
+/// 每页数量上限
+const MAX_PER_PAGE: usize = 100;
+
+/// 文件列表的查询参数
+///
+/// 页码从 1 开始，分页参数都未给出时返回全部文件；`detail=true` 时返回大小与 MIME 类型
+#[derive(Debug, Default, Deserialize)]
+pub struct ListQuery {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+    #[serde(default)]
+    pub detail: bool,
+    /// 排序字段，默认按文件名
+    #[serde(default)]
+    pub sort: ListSort,
+    /// 排序方向，默认升序
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+/// 分页后的文件列表
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct FilePage<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+/// 文件列表响应：未分页时保持原来的数组
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum FileList<T> {
+    All(Vec<T>),
+    Page(FilePage<T>),
+}
+
+impl ListQuery {
+    /// 从已排序的列表中截取对应页，超出范围的页为空；未指定分页参数时返回 `None`
+    pub fn paginate<T: Clone>(&self, files: &[T]) -> Option<FilePage<T>> {
+        if self.page.is_none() && self.per_page.is_none() {
+            return None;
         }
+
+        let page = self.page.unwrap_or(DEFAULT_PAGE).max(1);
+        let per_page = self
+            .per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE);
+        let items = files
+            .iter()
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .cloned()
+            .collect();
+
+        Some(FilePage {
+            items,
+            total: files.len(),
+            page,
+            per_page,
+        })
+    }
+}
+
+/// 由响应数据计算 ETag
+fn list_etag<T: Serialize + ?Sized>(data: &T, raw: bool) -> String {
+    let digest = Md5::digest(serde_json::to_vec(data).unwrap_or_default());
+    // 包装与未包装两种表示的内容不同，ETag 也需区分
+    if raw {
+        format!("\"{:x}-raw\"", digest)
+    } else {
+        format!("\"{:x}\"", digest)
+    }
+}
+
+/// 判断 If-None-Match 是否命中当前 ETag
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// 按分页参数截取列表，并附带与服务端缓存一致的缓存头
+fn list_response<T: Clone + Serialize>(
+    files: Vec<T>,
+    query: &ListQuery,
+    headers: &HeaderMap,
+    ttl_remaining: Option<Duration>,
+    request_id: RequestId,
+) -> Response {
+    // Accept 为 RAW_MEDIA_TYPE 时直接返回列表本身
+    let raw = prefers_raw(headers);
+
+    info!("SHARE_LIST: list files success, count={}", files.len());
+    let files = match query.paginate(&files) {
+        Some(page) => FileList::Page(page),
+        None => FileList::All(files),
+    };
+
+    // 缓存头与服务端列表缓存的剩余时间保持一致
+    let max_age = ttl_remaining
+        .map(|ttl| ttl.num_seconds().max(0))
+        .unwrap_or(0);
+    let etag = list_etag(&files, raw);
+    let cache_headers = [
+        (
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", max_age),
+        ),
+        (header::ETAG, etag.clone()),
+        (header::VARY, header::ACCEPT.to_string()),
+    ];
+
+    if etag_matches(headers, &etag) {
+        debug!("SHARE_LIST: etag matched, not modified");
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    (
+        cache_headers,
+        ApiResponse::success_with_id(files, request_id).negotiate(raw),
+    )
+        .into_response()
+}
+
+#[instrument(name = "share_list_files", skip(headers), fields(module = "share"))]
+pub async fn list_files(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    Query(query): Query<ListQuery>,
+    headers: HeaderMap,
+) -> Response {
+    // 缓存的文件名列表已按名称升序排列，其他排序方式需要详细列表中的修改时间
+    let default_order = query.sort == ListSort::Name && query.order == SortOrder::Asc;
+
+    let listed = if query.detail || !default_order {
+        ShareFile::list_detailed().await.map(|mut files| {
+            sort_entries(&mut files, query.sort, query.order);
+            let ttl = ShareFile::list_detailed_ttl_remaining();
+            if query.detail {
+                list_response(files, &query, &headers, ttl, RequestId(request_id))
+            } else {
+                let names = files.into_iter().map(|f| f.file_name).collect::<Vec<_>>();
+                list_response(names, &query, &headers, ttl, RequestId(request_id))
+            }
+        })
+    } else {
+        ShareFile::list().await.map(|files| {
+            list_response(
+                files,
+                &query,
+                &headers,
+                ShareFile::list_ttl_remaining(),
+                RequestId(request_id),
+            )
+        })
+    };
+
+    match listed {
+        Ok(resp) => resp,
         Err(e) => {
             error!("SHARE_LIST: list files failed: {:#}", e);
-            ApiResponse::error(
+            ApiResponse::<()>::error_with_code(
                 StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::StorageError,
                 &format!("读取文件列表失败: {:#}", e),
                 request_id.into(),
             )
+            .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use axum::http::HeaderValue;
+    use futures_util::future::BoxFuture;
+    use std::sync::Mutex;
+
+    /// 记录已发送邮件的异步 Mailer
+    #[derive(Default)]
+    struct MockAsyncMailer {
+        sent: Mutex<Vec<(String, String, String)>>,
+    }
+
+    impl AsyncMailer for MockAsyncMailer {
+        fn send<'a>(
+            &'a self,
+            to: &'a str,
+            subject: &'a str,
+            body: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<()>> {
+            Box::pin(async move {
+                // 让出一次执行权，确认调用方确实在 await 发送结果
+                tokio::task::yield_now().await;
+                self.sent.lock().unwrap().push((
+                    to.to_string(),
+                    subject.to_string(),
+                    body.to_string(),
+                ));
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_share_mail_sent_async() {
+        let payload = ShareRequest {
+            applicant: "申请人".to_string(),
+            apply_for: "book.pdf".to_string(),
+            email: "reader@example.com".to_string(),
+            email_code: None,
+            verification_token: None,
+            lang: None,
+        };
+        let file = ShareFile {
+            file_name: "book.pdf".to_string(),
+            timestamp: 0,
+            download_link: "https://tmpfile.link/book.pdf".to_string(),
+            download_link_encoded: "https://tmpfile.link/book.pdf".to_string(),
+            size: 42,
+            mime_type: "application/pdf".to_string(),
+            md5: "5eb63bbbe01eeed093cb22bb8f5acdc3".to_string(),
+            sha256: "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+            expires_at: 0,
+        };
+
+        let mailer = MockAsyncMailer::default();
+        send_share_mail(&mailer, &payload, &file, "2024-01-01 00:00:00", Lang::Zh)
+            .await
+            .unwrap();
+        send_share_mail(&mailer, &payload, &file, "2024-01-01 00:00:00", Lang::En)
+            .await
+            .unwrap();
+
+        let sent = mailer.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        let (_, subject, body) = &sent[1];
+        assert_eq!(subject, "File ready for download - book.pdf");
+        assert!(body.starts_with("Dear 申请人,") && body.contains("Size: 42 bytes\n"));
+        let (to, subject, body) = &sent[0];
+        assert_eq!(to, "reader@example.com");
+        assert_eq!(subject, "文件分享通知 - book.pdf");
+        assert!(body.contains("申请人") && body.contains("https://tmpfile.link/book.pdf"));
+        assert!(
+            body.contains("MD5：5eb63bbbe01eeed093cb22bb8f5acdc3\n"),
+            "{}",
+            body
+        );
+        assert!(
+            body.contains(
+                "SHA-256：b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9\n"
+            ),
+            "{}",
+            body
+        );
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = list_etag(&["a.txt".to_string(), "b.txt".to_string()], false);
+        let mut headers = HeaderMap::new();
+        assert!(!etag_matches(&headers, &etag));
+
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", W/{}", etag)).unwrap(),
+        );
+        assert!(etag_matches(&headers, &etag));
+
+        // 内容变化后 ETag 随之变化
+        assert_ne!(etag, list_etag(&["a.txt".to_string()], false));
+        // 两种表示的 ETag 不同
+        assert_ne!(
+            etag,
+            list_etag(&["a.txt".to_string(), "b.txt".to_string()], true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_files_cache_headers() {
+        config::test_global();
+
+        let resp = list_files(
+            Extension(RequestId::new()),
+            Query(ListQuery::default()),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let cache_control = resp.headers()[header::CACHE_CONTROL].to_str().unwrap();
+        let max_age: i64 = cache_control
+            .strip_prefix("public, max-age=")
+            .expect("应包含 max-age")
+            .parse()
+            .unwrap();
+        assert!(max_age > 0 && max_age <= 600);
+
+        // 携带匹配的 ETag 再次请求应返回 304
+        let etag = resp.headers()[header::ETAG].clone();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag);
+        let resp = list_files(
+            Extension(RequestId::new()),
+            Query(ListQuery::default()),
+            headers,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn test_list_files_envelope_negotiation() {
+        use crate::response::RAW_MEDIA_TYPE;
+        use http_body_util::BodyExt;
+
+        config::test_global();
+
+        async fn body_json(resp: Response) -> serde_json::Value {
+            let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice(&bytes).unwrap()
+        }
+
+        // 默认返回包装结构
+        let resp = list_files(
+            Extension(RequestId::new()),
+            Query(ListQuery::default()),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(resp.headers()[header::VARY], "accept");
+        let wrapped = body_json(resp).await;
+        assert_eq!(wrapped["code"], 200);
+        assert!(wrapped["data"].is_array());
+
+        // 要求原始数据时直接返回数组
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_str(&format!("{}, application/json;q=0.5", RAW_MEDIA_TYPE)).unwrap(),
+        );
+        let raw = body_json(
+            list_files(
+                Extension(RequestId::new()),
+                Query(ListQuery::default()),
+                headers,
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(raw, wrapped["data"]);
+    }
+
+    fn names(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("{:02}.pdf", i)).collect()
+    }
+
+    fn query(page: Option<usize>, per_page: Option<usize>) -> ListQuery {
+        ListQuery {
+            page,
+            per_page,
+            ..ListQuery::default()
+        }
+    }
+
+    #[test]
+    fn test_paginate() {
+        let files = names(5);
+
+        // 未指定参数时返回全部，保持兼容
+        assert_eq!(query(None, None).paginate(&files), None);
+
+        let first = query(Some(1), Some(2)).paginate(&files).unwrap();
+        assert_eq!(first.items, ["00.pdf", "01.pdf"]);
+        assert_eq!((first.total, first.page, first.per_page), (5, 1, 2));
+
+        let middle = query(Some(2), Some(2)).paginate(&files).unwrap();
+        assert_eq!(middle.items, ["02.pdf", "03.pdf"]);
+
+        let last = query(Some(3), Some(2)).paginate(&files).unwrap();
+        assert_eq!(last.items, ["04.pdf"]);
+
+        let out_of_range = query(Some(4), Some(2)).paginate(&files).unwrap();
+        assert!(out_of_range.items.is_empty());
+        assert_eq!(out_of_range.total, 5);
+
+        // 只给出一个参数时另一个取默认值
+        let defaults = query(None, Some(3)).paginate(&files).unwrap();
+        assert_eq!((defaults.page, defaults.items.len()), (1, 3));
+        let defaults = query(Some(1), None).paginate(&files).unwrap();
+        assert_eq!(
+            (defaults.per_page, defaults.items.len()),
+            (DEFAULT_PER_PAGE, 5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_files_paginated_response() {
+        use http_body_util::BodyExt;
+
+        config::test_global();
+
+        let resp = list_files(
+            Extension(RequestId::new()),
+            Query(query(Some(1), Some(1))),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"]["page"], 1);
+        assert_eq!(body["data"]["per_page"], 1);
+        assert!(body["data"]["items"].as_array().unwrap().len() <= 1);
+        assert!(body["data"]["total"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_list_files_detail() {
+        use http_body_util::BodyExt;
+
+        config::test_global();
+
+        let query = ListQuery {
+            detail: true,
+            ..ListQuery::default()
+        };
+        let resp = list_files(Extension(RequestId::new()), Query(query), HeaderMap::new()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        for entry in body["data"].as_array().unwrap() {
+            assert!(entry["file_name"].is_string());
+            assert!(entry["size"].is_u64());
+            assert!(entry["mime_type"].is_string());
         }
     }
 }
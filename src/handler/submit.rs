@@ -1,34 +1,409 @@
-use crate::response::ApiResponse;
-use axum::http::StatusCode;
-use axum::{Extension, Json};
+use crate::response::{ApiResponse, ErrorCode, FieldError};
+use axum::Extension;
+use axum::extract::multipart::Field;
+use axum::extract::{Multipart, Path, Query};
+use axum::http::{HeaderMap, StatusCode};
 
-use crate::config::AppConfig;
-use crate::handler::auth::verify_code;
+use crate::config::{AppConfig, UploadConfig};
+use crate::handler::auth::verify_code_or_token;
+use crate::middleware::background::{notify_background, send_html_mail_background};
 use crate::middleware::request_id::RequestId;
-use crate::utils::email::{Mailer, SmtpMailer};
-use crate::utils::github::Submission;
-use crate::utils::picture::Base64Image;
+use crate::middleware::streaming_json::{FieldTooLarge, FromJsonReader, StreamingJson};
+use crate::middleware::submission_queue::{self, IdempotentSubmission, SubmissionStatus};
+use crate::utils::audit::record_submission_background;
+use crate::utils::email::{AsyncMailer, AsyncSmtpMailer, Mailer, SmtpMailer};
+use crate::utils::github::{
+    PullRequestStatus, Submission, ValidationError, is_branch_owner, is_contrib_branch,
+    pull_request_status,
+};
+use crate::utils::markdown::ToHexo;
+use crate::utils::messages::Lang;
+use crate::utils::notify::{Notifier, configured_notifiers};
+use crate::utils::picture::{Base64Image, RawImage};
+use anyhow::Context;
 use axum_macros::debug_handler;
-use serde::Deserialize;
-use tracing::{error, info, instrument, warn};
-use crate::middleware::background::send_mail_background;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+use tracing::{debug, info, instrument, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-#[derive(Deserialize)]
+#[derive(ToSchema)]
 pub struct SubmissionRequest {
     pub author: String,
     pub content: String,
-    pub cover: Base64Image,
+    #[schema(value_type = Base64Image)]
+    pub cover: RawImage,
     pub email: String,
-    pub email_code: String,
-    pub images: Vec<Base64Image>,
+    pub email_code: Option<String>,
+    /// `/auth/verify` 签发的一次性令牌，可代替 `email_code`
+    pub verification_token: Option<String>,
+    #[schema(value_type = Vec<Base64Image>)]
+    pub images: Vec<RawImage>,
     pub tags: Vec<String>,
     pub title: String,
+    /// 可选的自定义 slug，用作文件名与资源目录
+    pub slug: Option<String>,
+    /// 根据反馈修改后重新投稿时填写原投稿分支（`contrib-<uuid>`），更新原分支与 PR；
+    /// 只能更新同一邮箱投稿创建的分支
+    pub branch: Option<String>,
+    /// 通知邮件的语言（`zh` / `en`），未填写时按 `Accept-Language` 选择，默认中文
+    pub lang: Option<String>,
 }
 
+/// 投稿已被接受，通过 `submission_id` 查询后台处理进度
+///
+/// PR 在后台创建，地址见 `/submit/status/{submission_id}` 的 `pr_url`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmitAccepted {
+    pub submission_id: Uuid,
+    /// 投稿分支名，测试投稿不创建分支时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// 重复的 `Idempotency-Key` 请求且 PR 已创建时附带 PR 地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr_url: Option<String>,
+}
+
+/// 客户端为同一次投稿的所有重试携带相同的值，避免重复创建 PR
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// 幂等键的最大长度
+const IDEMPOTENCY_KEY_MAX_LEN: usize = 255;
+
+/// 读取 `Idempotency-Key` 请求头，未携带或未开启时返回 None
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, &'static str> {
+    if AppConfig::global().submission.idempotency_window_secs == 0 {
+        return Ok(None);
+    }
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| "Idempotency-Key 只能包含可见 ASCII 字符")?
+        .trim();
+    if key.is_empty() || key.len() > IDEMPOTENCY_KEY_MAX_LEN {
+        return Err("Idempotency-Key 长度应为 1 到 255 个字符");
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// 重复请求直接返回首次受理的结果，PR 已创建时一并返回地址
+fn replay(prior: IdempotentSubmission, request_id: Uuid) -> ApiResponse<SubmitAccepted> {
+    info!(
+        "SUBMIT_ARTICLE: idempotent replay of submission_id={}",
+        prior.submission_id
+    );
+    let pr_url = match submission_queue::get_status(prior.submission_id) {
+        Some(SubmissionStatus::Done { pr_url }) => pr_url,
+        _ => None,
+    };
+    ApiResponse::accepted(
+        SubmitAccepted {
+            submission_id: prior.submission_id,
+            branch: Some(prior.branch),
+            pr_url,
+        },
+        request_id.into(),
+    )
+}
+
+/// 试运行校验结果
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub errors: Vec<ValidationError>,
+}
+
+/// 投稿预览：最终提交到仓库的 Hexo Markdown 与各文件路径
+#[derive(Debug, Serialize)]
+pub struct SubmissionPreview {
+    pub markdown: String,
+    pub files: Vec<String>,
+}
+
+/// 读取单个表单字段，超出上限时立即中止读取
+async fn read_field_limited(
+    mut field: Field<'_>,
+    name: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .with_context(|| format!("读取字段失败: {}", name))?
+    {
+        FieldTooLarge::check(name, buf.len() + chunk.len(), limit)?;
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(buf)
+}
+
+impl SubmissionRequest {
+    /// 按字段大小上限从 JSON 流中解析投稿请求
+    pub fn parse_json<R: Read>(reader: R, limits: &UploadConfig) -> anyhow::Result<Self> {
+        let too_large = Cell::new(None);
+        let seed = SubmissionSeed {
+            limits,
+            too_large: &too_large,
+        };
+
+        let mut de = serde_json::Deserializer::from_reader(BufReader::new(reader));
+        let parsed = seed
+            .deserialize(&mut de)
+            .and_then(|req| de.end().map(|_| req));
+
+        match (parsed, too_large.take()) {
+            (_, Some(field)) => Err(field.into()),
+            (Ok(req), None) => Ok(req),
+            (Err(e), None) => Err(e.into()),
+        }
+    }
+
+    /// 从 multipart/form-data 表单构造投稿请求
+    ///
+    /// 文本字段与 JSON 接口同名；`cover` 与 `images` 为文件字段，`tags` 可重复出现
+    pub async fn from_multipart(
+        mut multipart: Multipart,
+        limits: &UploadConfig,
+    ) -> anyhow::Result<Self> {
+        let mut author = None;
+        let mut content = None;
+        let mut cover = None;
+        let mut email = None;
+        let mut email_code = None;
+        let mut verification_token = None;
+        let mut images = Vec::new();
+        let mut tags = Vec::new();
+        let mut title = None;
+        let mut slug = None;
+        let mut branch = None;
+        let mut lang = None;
+
+        while let Some(field) = multipart.next_field().await.context("读取表单字段失败")? {
+            let name = field.name().unwrap_or_default().to_string();
+            match name.as_str() {
+                "cover" | "images" => {
+                    let file_name = field
+                        .file_name()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| name.clone());
+                    let (label, limit) = if name == "cover" {
+                        (name.clone(), limits.cover_max_bytes)
+                    } else {
+                        (format!("images[{}]", images.len()), limits.image_max_bytes)
+                    };
+                    let bytes = read_field_limited(field, &label, limit).await?;
+                    let image = RawImage {
+                        name: file_name,
+                        bytes,
+                    };
+                    if name == "cover" {
+                        cover = Some(image);
+                    } else {
+                        images.push(image);
+                    }
+                }
+                "content" => {
+                    let bytes = read_field_limited(field, &name, limits.content_max_bytes).await?;
+                    content =
+                        Some(String::from_utf8(bytes).context("字段 content 不是有效的 UTF-8")?);
+                }
+                "author" | "email" | "email_code" | "verification_token" | "tags" | "title"
+                | "slug" | "branch" | "lang" => {
+                    let value = field
+                        .text()
+                        .await
+                        .with_context(|| format!("读取字段失败: {}", name))?;
+                    match name.as_str() {
+                        "author" => author = Some(value),
+                        "email" => email = Some(value),
+                        "email_code" => email_code = Some(value),
+                        "verification_token" => verification_token = Some(value),
+                        "title" => title = Some(value),
+                        "slug" => slug = Some(value),
+                        "branch" => branch = Some(value),
+                        "lang" => lang = Some(value),
+                        _ if !value.trim().is_empty() => tags.push(value),
+                        _ => {}
+                    }
+                }
+                _ => debug!("SUBMIT_MULTIPART: ignored unknown field {}", name),
+            }
+        }
+
+        Ok(Self {
+            author: author.context("缺少字段: author")?,
+            content: content.context("缺少字段: content")?,
+            cover: cover.context("缺少字段: cover")?,
+            email: email.context("缺少字段: email")?,
+            email_code,
+            verification_token,
+            images,
+            tags,
+            title: title.context("缺少字段: title")?,
+            slug,
+            branch,
+            lang,
+        })
+    }
+}
+
+impl FromJsonReader for SubmissionRequest {
+    fn from_json_reader(reader: Box<dyn Read + Send>) -> anyhow::Result<Self> {
+        Self::parse_json(reader, &AppConfig::global().upload)
+    }
+}
+
+/// 逐字段反序列化投稿请求，每读完一个字段立即检查大小
+struct SubmissionSeed<'a> {
+    limits: &'a UploadConfig,
+    too_large: &'a Cell<Option<FieldTooLarge>>,
+}
+
+impl SubmissionSeed<'_> {
+    fn check<E: de::Error>(
+        &self,
+        field: impl Into<String>,
+        size: usize,
+        limit: usize,
+    ) -> Result<(), E> {
+        FieldTooLarge::check(field, size, limit).map_err(|e| {
+            let err = E::custom(&e);
+            self.too_large.set(Some(e));
+            err
+        })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for SubmissionSeed<'_> {
+    type Value = SubmissionRequest;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SubmissionSeed<'_> {
+    type Value = SubmissionRequest;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("投稿请求对象")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut author = None;
+        let mut content = None;
+        let mut cover = None;
+        let mut email = None;
+        let mut email_code = None;
+        let mut verification_token = None;
+        let mut images = None;
+        let mut tags = None;
+        let mut title = None;
+        let mut slug = None;
+        let mut branch = None;
+        let mut lang = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "author" => author = Some(map.next_value()?),
+                "content" => {
+                    let value: String = map.next_value()?;
+                    self.check("content", value.len(), self.limits.content_max_bytes)?;
+                    content = Some(value);
+                }
+                "cover" => {
+                    let image: RawImage = map.next_value()?;
+                    self.check("cover", image.bytes.len(), self.limits.cover_max_bytes)?;
+                    cover = Some(image);
+                }
+                "email" => email = Some(map.next_value()?),
+                "email_code" => email_code = map.next_value()?,
+                "verification_token" => verification_token = map.next_value()?,
+                "images" => images = Some(map.next_value_seed(ImagesSeed(&self))?),
+                "tags" => tags = Some(map.next_value()?),
+                "title" => title = Some(map.next_value()?),
+                "slug" => slug = map.next_value()?,
+                "branch" => branch = map.next_value()?,
+                "lang" => lang = map.next_value()?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(SubmissionRequest {
+            author: author.ok_or_else(|| de::Error::missing_field("author"))?,
+            content: content.ok_or_else(|| de::Error::missing_field("content"))?,
+            cover: cover.ok_or_else(|| de::Error::missing_field("cover"))?,
+            email: email.ok_or_else(|| de::Error::missing_field("email"))?,
+            email_code,
+            verification_token,
+            images: images.ok_or_else(|| de::Error::missing_field("images"))?,
+            tags: tags.ok_or_else(|| de::Error::missing_field("tags"))?,
+            title: title.ok_or_else(|| de::Error::missing_field("title"))?,
+            slug,
+            branch,
+            lang,
+        })
+    }
+}
+
+/// 逐张反序列化附加图片，每解码一张立即检查大小
+struct ImagesSeed<'s, 'a>(&'s SubmissionSeed<'a>);
+
+impl<'de> DeserializeSeed<'de> for ImagesSeed<'_, '_> {
+    type Value = Vec<RawImage>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ImagesSeed<'_, '_> {
+    type Value = Vec<RawImage>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("图片数组")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut images = Vec::new();
+        while let Some(image) = seq.next_element::<RawImage>()? {
+            let field = format!("images[{}]", images.len());
+            self.0
+                .check(field, image.bytes.len(), self.0.limits.image_max_bytes)?;
+            images.push(image);
+        }
+        Ok(images)
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/submit",
+    tag = "submit",
+    request_body = SubmissionRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "同一次投稿的重试携带相同的值，避免重复创建 PR"),
+    ),
+    responses(
+        (status = 202, description = "投稿已受理，PR 在后台创建", body = ApiResponse<SubmitAccepted>),
+        (status = 401, description = "验证码错误或已过期", body = ApiResponse<String>),
+        (status = 409, description = "重复投稿", body = ApiResponse<String>),
+        (status = 422, description = "投稿内容校验未通过", body = ApiResponse<String>),
+    )
+)]
 #[debug_handler]
 #[instrument(
     name = "submit_article_handler",
-    skip(payload),
+    skip(headers, notifiers, payload),
     fields(
         module     = "submit",
         request_id = %request_id,
@@ -39,101 +414,1311 @@ pub struct SubmissionRequest {
 )]
 pub async fn submit_article(
     Extension(RequestId(request_id)): Extension<RequestId>,
-    Json(payload): Json<SubmissionRequest>,
-) -> ApiResponse<()> {
+    headers: HeaderMap,
+    notifiers: Option<Extension<PublishNotifiers>>,
+    StreamingJson(payload): StreamingJson<SubmissionRequest>,
+) -> ApiResponse<SubmitAccepted> {
     info!("SUBMIT_ARTICLE: request received");
+    process_submission(
+        request_id,
+        &headers,
+        payload,
+        PublishNotifiers::from_extension(notifiers),
+    )
+    .await
+}
 
-    // 先校验验证码
-    if !verify_code(payload.email.clone(), payload.email_code.clone()) {
-        warn!("SUBMIT_ARTICLE: verify_code failed");
-        return ApiResponse::error(
-            StatusCode::UNAUTHORIZED,
-            "验证码错误或已过期",
+/// multipart/form-data 版本的投稿接口，图片以文件字段上传，无需 Base64
+#[debug_handler]
+#[instrument(
+    name = "submit_article_multipart_handler",
+    skip(headers, notifiers, multipart),
+    fields(
+        module     = "submit",
+        request_id = %request_id,
+    )
+)]
+pub async fn submit_article_multipart(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    headers: HeaderMap,
+    notifiers: Option<Extension<PublishNotifiers>>,
+    multipart: Multipart,
+) -> ApiResponse<SubmitAccepted> {
+    info!("SUBMIT_ARTICLE: multipart request received");
+
+    let limits = &AppConfig::global().upload;
+    let payload = match SubmissionRequest::from_multipart(multipart, limits).await {
+        Ok(payload) => payload,
+        Err(e) if e.downcast_ref::<FieldTooLarge>().is_some() => {
+            warn!("SUBMIT_ARTICLE: multipart field too large: {:#}", e);
+            return ApiResponse::error_with_code(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ErrorCode::PayloadTooLarge,
+                &e.to_string(),
+                request_id.into(),
+            );
+        }
+        Err(e) => {
+            warn!("SUBMIT_ARTICLE: multipart parse failed: {:#}", e);
+            return ApiResponse::error_with_code(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidBody,
+                &format!("表单解析失败: {:#}", e),
+                request_id.into(),
+            );
+        }
+    };
+    info!(
+        "SUBMIT_ARTICLE: multipart parsed, email={}, author={}, title={}",
+        payload.email, payload.author, payload.title
+    );
+
+    process_submission(
+        request_id,
+        &headers,
+        payload,
+        PublishNotifiers::from_extension(notifiers),
+    )
+    .await
+}
+
+/// 试运行校验投稿：执行全部校验与图片解码，不校验验证码，也不推送或发信
+///
+/// 仅在配置 `submission.dry_run` 开启时注册
+#[debug_handler]
+#[instrument(
+    name = "validate_submission_handler",
+    skip(payload),
+    fields(module = "submit", request_id = %request_id)
+)]
+pub async fn validate_submission(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    StreamingJson(payload): StreamingJson<SubmissionRequest>,
+) -> ApiResponse<ValidationReport> {
+    info!("SUBMIT_VALIDATE: request received");
+    let submission = Submission::from_request(payload);
+
+    let mut errors = Vec::new();
+    if let Err(e) = submission.validate(&AppConfig::global().submission) {
+        errors.extend(e);
+    }
+    if let Err(e) = submission.validate_images() {
+        errors.extend(e);
+    }
+
+    info!("SUBMIT_VALIDATE: finished, errors={}", errors.len());
+    ApiResponse::success_with_id(
+        ValidationReport {
+            valid: errors.is_empty(),
+            errors,
+        },
+        request_id.into(),
+    )
+}
+
+/// 预览投稿：校验并转换图片后返回渲染好的 Markdown 与文件路径
+///
+/// 不校验验证码，也不创建分支、PR 或发送邮件
+#[debug_handler]
+#[instrument(
+    name = "preview_submission_handler",
+    skip(payload),
+    fields(module = "submit", request_id = %request_id)
+)]
+pub async fn preview_submission(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    StreamingJson(payload): StreamingJson<SubmissionRequest>,
+) -> ApiResponse<SubmissionPreview> {
+    info!("SUBMIT_PREVIEW: request received");
+    let submission = Submission::from_request(payload);
+
+    let mut errors = Vec::new();
+    if let Err(e) = submission.validate(&AppConfig::global().submission) {
+        errors.extend(e);
+    }
+    if let Err(e) = submission.validate_images() {
+        errors.extend(e);
+    }
+    if !errors.is_empty() {
+        warn!("SUBMIT_PREVIEW: validation failed, errors={}", errors.len());
+        return ApiResponse::validation_error(
+            errors.into_iter().map(FieldError::from).collect(),
             request_id.into(),
         );
     }
-    info!("SUBMIT_ARTICLE: verify_code success");
 
-    let mailer = SmtpMailer::global();
+    // 与正式投稿一样先转为 WebP，预览中的文件名与扩展名才与最终提交一致
+    let limits = AppConfig::global().images.clone();
+    let converted = tokio::task::spawn_blocking(move || {
+        let mut submission = submission;
+        submission
+            .convert_images_to_webp(&limits)
+            .map(|_| submission)
+    })
+    .await;
+    let submission = match converted {
+        Ok(Ok(submission)) => submission,
+        Ok(Err(e)) => {
+            warn!("SUBMIT_PREVIEW: image conversion failed: {:#}", e);
+            return ApiResponse::error_with_code(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorCode::InvalidBody,
+                &format!("图片转换失败: {:#}", e),
+                request_id.into(),
+            );
+        }
+        Err(e) => {
+            warn!("SUBMIT_PREVIEW: conversion task failed: {}", e);
+            return ApiResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "图片转换失败",
+                request_id.into(),
+            );
+        }
+    };
+
+    let preview = SubmissionPreview {
+        markdown: submission.to_hexo(),
+        files: submission.files().into_iter().map(|f| f.path).collect(),
+    };
+    info!("SUBMIT_PREVIEW: finished, files={}", preview.files.len());
+    ApiResponse::success_with_id(preview, request_id.into())
+}
+
+/// 验证码错误或已过期的响应
+fn invalid_code(request_id: Uuid) -> ApiResponse<SubmitAccepted> {
+    ApiResponse::error_with_code(
+        StatusCode::UNAUTHORIZED,
+        ErrorCode::InvalidCode,
+        "验证码错误或已过期",
+        request_id.into(),
+    )
+}
+
+/// 与 `existing` 重复的投稿被拒绝，PR 已创建时在提示中附带地址
+fn duplicate(
+    existing: Uuid,
+    submission: &Submission,
+    request_id: Uuid,
+) -> ApiResponse<SubmitAccepted> {
+    let message = match submission_queue::get_status(existing) {
+        Some(SubmissionStatus::Done {
+            pr_url: Some(pr_url),
+        }) => format!("请勿重复投稿，该投稿已创建 PR: {}", pr_url),
+        _ => format!(
+            "请勿重复投稿，该投稿正在处理中（submission_id: {}）",
+            existing
+        ),
+    };
+    warn!(
+        "SUBMIT_ARTICLE: duplicate of submission {}, email={}, title={}",
+        existing, submission.email, submission.title
+    );
+    ApiResponse::error_with_code(
+        StatusCode::CONFLICT,
+        ErrorCode::DuplicateSubmission,
+        &message,
+        request_id.into(),
+    )
+}
+
+/// 后台发布投稿后通知投稿人与管理员的渠道
+///
+/// 路由上挂有该类型的 Extension 时使用它，否则每次请求按当前配置构造
+#[derive(Clone)]
+pub struct PublishNotifiers {
+    /// 给投稿人发送结果邮件，重试由 mailer 自身负责
+    mailer: Arc<dyn Mailer>,
+    admins: Vec<Arc<dyn Notifier>>,
+}
+
+impl PublishNotifiers {
+    /// 使用给定的渠道构造，测试中注入到路由以免真实发信
+    #[cfg(test)]
+    pub fn new(mailer: Arc<dyn Mailer>, admins: Vec<Arc<dyn Notifier>>) -> Self {
+        Self { mailer, admins }
+    }
+
+    /// 按配置构造：后台 SMTP 发信，并通知全部已配置的管理员渠道
+    fn configured() -> Self {
+        Self {
+            mailer: SmtpMailer::background(),
+            admins: configured_notifiers(),
+        }
+    }
+
+    /// 优先使用路由注入的渠道，未注入时按配置构造
+    fn from_extension(notifiers: Option<Extension<Self>>) -> Self {
+        notifiers.map_or_else(Self::configured, |Extension(notifiers)| notifiers)
+    }
+}
 
-    if payload.title.trim() == "测试" && payload.author.trim() == "测试" {
+/// JSON 与 multipart 两种投稿方式共用的处理流程
+async fn process_submission(
+    request_id: Uuid,
+    headers: &HeaderMap,
+    mut payload: SubmissionRequest,
+    notifiers: PublishNotifiers,
+) -> ApiResponse<SubmitAccepted> {
+    // 前端因网络问题重试时，同一幂等键直接返回首次受理的结果，验证码此时已被消费
+    let idempotency_key = match idempotency_key(headers) {
+        Ok(key) => key,
+        Err(message) => {
+            warn!("SUBMIT_ARTICLE: {}", message);
+            return ApiResponse::error_with_code(
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidIdempotencyKey,
+                message,
+                request_id.into(),
+            );
+        }
+    };
+    if let Some(prior) = idempotency_key
+        .as_deref()
+        .and_then(submission_queue::find_idempotent)
+    {
+        return replay(prior, request_id);
+    }
+
+    if AppConfig::global()
+        .submission
+        .is_test_submission(&payload.title, &payload.author)
+    {
+        if !verify_code_or_token(
+            payload.email.clone(),
+            payload.email_code.clone(),
+            payload.verification_token.clone(),
+        ) {
+            warn!("SUBMIT_ARTICLE: verify_code failed");
+            return invalid_code(request_id);
+        }
         info!(
             "SUBMIT_ARTICLE: test submission shortcut, email={}",
             payload.email
         );
         // 给提交人发一封“测试通过”邮件
-        if let Err(e) = mailer.send(
-            &payload.email,
-            "投稿测试：已通过",
-            "测试通过：系统已成功接收测试提交（未执行真实创建分支/PR/发图等逻辑）。",
-        ) {
+        if let Err(e) = AsyncSmtpMailer::global()
+            .send(
+                &payload.email,
+                "投稿测试：已通过",
+                "测试通过：系统已成功接收测试提交（未执行真实创建分支/PR/发图等逻辑）。",
+            )
+            .await
+        {
             warn!(
                 "SUBMIT_ARTICLE: test mail send failed for {}: {:#}",
                 payload.email, e
             );
         }
-        return ApiResponse::success(());
+        submission_queue::set_status(request_id, SubmissionStatus::Done { pr_url: None });
+        return ApiResponse::success_with_id(
+            SubmitAccepted {
+                submission_id: request_id,
+                branch: None,
+                pr_url: None,
+            },
+            request_id.into(),
+        );
     }
 
     // 构造 Submission
-    let submission = Submission::from_request(payload);
+    let email_code = payload.email_code.take();
+    let verification_token = payload.verification_token.take();
+    let lang = Lang::negotiate(payload.lang.as_deref(), headers);
+    let mut submission = Submission::from_request(payload);
+    submission.lang = lang;
     info!(
         "SUBMIT_ARTICLE: submission built, email={}, title={}",
         submission.email, submission.title,
     );
 
-    // 调用同步 push_branch
-    if let Err(e) = submission.push_branch().await {
-        error!("SUBMIT_ARTICLE: push_branch failed: {:#}", e);
-        return ApiResponse::error(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            &format!("推送分支失败: {}", e),
+    // 不需要验证码的检查放在校验验证码之前，内容有误时验证码不会被消费，修改后可以直接重新提交
+    if let Err(errors) = submission.validate(&AppConfig::global().submission) {
+        let message = errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        warn!("SUBMIT_ARTICLE: validation failed: {}", message);
+        return ApiResponse::validation_error(
+            errors.into_iter().map(FieldError::from).collect(),
             request_id.into(),
         );
     }
+
+    // 连点提交按钮等短时间内的重复投稿直接拒绝，避免创建多个相同的分支与 PR；
+    // 重新投稿更新的是已有分支，作者、邮箱与标题通常不变，不算重复
+    let window = AppConfig::global().submission.duplicate_window_secs;
+    let check_duplicate = window > 0 && !submission.resubmission;
+    let fingerprint = submission.fingerprint();
+    if check_duplicate && let Some(existing) = submission_queue::find_fingerprint(&fingerprint) {
+        return duplicate(existing, &submission, request_id);
+    }
+
+    if !verify_code_or_token(submission.email.clone(), email_code, verification_token) {
+        warn!("SUBMIT_ARTICLE: verify_code failed");
+        return invalid_code(request_id);
+    }
+    info!("SUBMIT_ARTICLE: verify_code success");
+
+    // 重新投稿只能更新本邮箱创建的分支，分支名公开可见，不能作为凭证
+    if submission.resubmission {
+        match is_branch_owner(&submission.branch, &submission.email).await {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(
+                    "SUBMIT_ARTICLE: branch {} not owned by {}",
+                    submission.branch, submission.email
+                );
+                return ApiResponse::error_with_code(
+                    StatusCode::FORBIDDEN,
+                    ErrorCode::BranchNotOwned,
+                    "该分支不是由此邮箱投稿创建的，无法重新投稿",
+                    request_id.into(),
+                );
+            }
+            Err(e) => {
+                warn!("SUBMIT_ARTICLE: check branch owner failed: {:#}", e);
+                return ApiResponse::error_with_code(
+                    StatusCode::BAD_GATEWAY,
+                    ErrorCode::GithubError,
+                    "查询原投稿失败，请稍后再试",
+                    request_id.into(),
+                );
+            }
+        }
+    }
+
+    // 验证码通过后才登记指纹，未通过验证的请求不能占用他人的投稿指纹；
+    // 上面的检查与登记之间可能有并发的相同投稿，这里再确认一次
+    if check_duplicate
+        && let Err(existing) = submission_queue::claim_fingerprint(
+            &fingerprint,
+            request_id,
+            chrono::Duration::seconds(window as i64),
+        )
+        .await
+    {
+        return duplicate(existing, &submission, request_id);
+    }
+
+    let branch = submission.branch.clone();
+    if let Some(key) = &idempotency_key {
+        let window = AppConfig::global().submission.idempotency_window_secs;
+        let claimed = submission_queue::claim_idempotency_key(
+            key,
+            IdempotentSubmission {
+                submission_id: request_id,
+                branch: branch.clone(),
+            },
+            chrono::Duration::seconds(window as i64),
+        )
+        .await;
+        if let Err(prior) = claimed {
+            return replay(prior, request_id);
+        }
+    }
+
+    // 校验通过、验证码已消费，后续的推送与建 PR 交给后台处理
+    submission_queue::enqueue(request_id, move || {
+        publish_or_report(request_id, submission, notifiers)
+    });
+    info!(
+        "SUBMIT_ARTICLE: submission queued, submission_id={}",
+        request_id
+    );
+
+    ApiResponse::accepted(
+        SubmitAccepted {
+            submission_id: request_id,
+            branch: Some(branch),
+            pr_url: None,
+        },
+        request_id.into(),
+    )
+}
+
+/// 后台处理投稿，失败时邮件告知投稿人并通知管理员
+///
+/// 请求早已返回，错误无法再通过响应告知前端
+async fn publish_or_report(
+    request_id: Uuid,
+    submission: Submission,
+    notifiers: PublishNotifiers,
+) -> anyhow::Result<String> {
+    let email = submission.email.clone();
+    let subject = format!("投稿处理失败：{}", submission.title);
+    let html = submission.to_contributor_failure_html(request_id);
+    let text = submission.to_contributor_failure(request_id);
+    let mut notification = submission.to_failure_notification(request_id);
+
+    let result = publish_submission(request_id, submission, &notifiers).await;
+    if let Err(e) = &result {
+        warn!(
+            "SUBMIT_ARTICLE: publish failed, notifying {}: {:#}",
+            email, e
+        );
+        if let Err(dropped) =
+            send_html_mail_background(notifiers.mailer, email.clone(), subject, html, text)
+        {
+            warn!(
+                "SUBMIT_ARTICLE: failure mail to {} dropped: {}",
+                email, dropped
+            );
+        }
+        notification.body.push_str(&format!("\n错误: {:#}", e));
+        if let Err(dropped) = notify_background(notifiers.admins, notification) {
+            warn!("SUBMIT_ARTICLE: failure notification dropped: {}", dropped);
+        }
+    }
+    result
+}
+
+/// 后台执行：图片转为 WebP、推送分支、创建 PR、发送通知邮件并写审计记录，返回 PR 地址
+async fn publish_submission(
+    request_id: Uuid,
+    submission: Submission,
+    notifiers: &PublishNotifiers,
+) -> anyhow::Result<String> {
+    // 图片重新编码与去除元数据较耗 CPU，放到阻塞线程池中执行
+    let limits = AppConfig::global().images.clone();
+    let submission = tokio::task::spawn_blocking(move || {
+        let mut submission = submission;
+        submission.convert_images_to_webp(&limits)?;
+        if limits.strip_metadata {
+            submission.strip_image_metadata()?;
+        }
+        anyhow::Ok(submission)
+    })
+    .await
+    .context("图片转换任务失败")??;
+    info!("SUBMIT_ARTICLE: images converted to webp");
+
+    submission.push_branch().await.context("推送分支失败")?;
     info!("SUBMIT_ARTICLE: push_branch success");
 
+    // 分支由本次投稿创建，建 PR 失败时删除，避免残留无主分支；重新投稿的原分支保留
     let url = match submission.pull_request().await {
-        Ok(url) => {
-            info!("SUBMIT_ARTICLE: pull_request created: {}", url);
-            url
-        }
+        Ok(url) => url,
         Err(e) => {
-            error!("SUBMIT_ARTICLE: pull_request failed: {:#}", e);
-            return ApiResponse::error(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                &format!("提交失败: {}", e),
-                request_id.into(),
-            );
+            if !submission.resubmission {
+                submission.delete_branch().await;
+            }
+            return Err(e.context("提交失败"));
         }
     };
+    info!("SUBMIT_ARTICLE: pull_request created: {}", url);
 
-    if let Err(e) = mailer.send(
-        &submission.email,
-        &submission.to_title(),
-        &submission.to_contributor(&url),
+    // PR 已创建，后续任务被丢弃不影响投稿结果，只记录日志便于人工补发
+    if let Err(e) = send_html_mail_background(
+        notifiers.mailer.clone(),
+        submission.email.clone(),
+        submission.to_title(),
+        submission.to_contributor_html(&url),
+        submission.to_contributor(&url),
     ) {
         warn!(
-            "SUBMIT_ARTICLE: mail to contributor {} failed: {:#}",
-            submission.email, e
+            "SUBMIT_ARTICLE: confirmation mail to {} dropped: {}, pr_url={}",
+            submission.email, e, url
         );
-    } else {
-        info!(
-            "SUBMIT_ARTICLE: mail sent to contributor {}",
-            submission.email
+    }
+
+    if let Err(e) = notify_background(notifiers.admins.clone(), submission.to_notification(&url)) {
+        warn!(
+            "SUBMIT_ARTICLE: admin notification dropped: {}, pr_url={}",
+            e, url
         );
     }
 
-    let admin_emails = AppConfig::global().admin.email.clone();
-    for admin_email in admin_emails {
-        send_mail_background(
-            mailer.clone(),
-            admin_email.clone(), 
-            submission.to_title(), 
-            submission.to_info()
+    if let Err(e) = record_submission_background(
+        &submission.author,
+        &submission.email,
+        &submission.title,
+        &url,
+        request_id,
+    ) {
+        warn!(
+            "SUBMIT_ARTICLE: audit record dropped: {}, pr_url={}",
+            e, url
         );
     }
 
     info!("SUBMIT_ARTICLE: completed");
-    ApiResponse::success(())
+    Ok(url)
+}
+
+/// 查询投稿处理状态 -> GET /submit/status/{id}
+#[instrument(skip_all, fields(module = "submit", submission_id = %id))]
+pub async fn submission_status(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    Path(id): Path<Uuid>,
+) -> ApiResponse<SubmissionStatus> {
+    match submission_queue::get_status(id) {
+        Some(status) => ApiResponse::success_with_id(status, request_id.into()),
+        None => {
+            debug!("SUBMIT_STATUS: unknown submission {}", id);
+            ApiResponse::error_with_code(
+                StatusCode::NOT_FOUND,
+                ErrorCode::NotFound,
+                "投稿不存在或状态已过期",
+                request_id.into(),
+            )
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BranchQuery {
+    pub branch: String,
+}
+
+/// 按投稿分支查询 PR 状态 -> GET /submit/status?branch=contrib-...
+///
+/// 只接受 `contrib-<uuid>` 形式的分支，避免被用来查询仓库中的任意分支
+#[instrument(skip_all, fields(module = "submit", branch = %query.branch))]
+pub async fn branch_pr_status(
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    Query(query): Query<BranchQuery>,
+) -> ApiResponse<PullRequestStatus> {
+    if !is_contrib_branch(&query.branch) {
+        warn!("SUBMIT_STATUS: rejected branch {:?}", query.branch);
+        return ApiResponse::error_with_code(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidBranch,
+            "分支名无效，应为 contrib-<uuid>",
+            request_id.into(),
+        );
+    }
+
+    match pull_request_status(&query.branch).await {
+        Ok(Some(status)) => ApiResponse::success_with_id(status, request_id.into()),
+        Ok(None) => {
+            debug!("SUBMIT_STATUS: no pull request for {}", query.branch);
+            ApiResponse::error_with_code(
+                StatusCode::NOT_FOUND,
+                ErrorCode::NotFound,
+                "该分支没有对应的 PR",
+                request_id.into(),
+            )
+        }
+        Err(e) => {
+            warn!("SUBMIT_STATUS: query pull request failed: {:#}", e);
+            ApiResponse::error_with_code(
+                StatusCode::BAD_GATEWAY,
+                ErrorCode::GithubError,
+                "查询 PR 状态失败，请稍后再试",
+                request_id.into(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use axum::body::Body;
+    use axum::extract::FromRequest;
+    use axum::http::{Request, header};
+
+    use crate::utils::email::SendParams;
+    use crate::utils::notify::{Notification, NotificationEvent};
+    use std::sync::Mutex;
+
+    const BOUNDARY: &str = "qidian-test-boundary";
+
+    /// 记录发给投稿人的邮件与管理员通知，测试中不会真的发信
+    #[derive(Default)]
+    struct RecordingNotifiers {
+        mails: Mutex<Vec<String>>,
+        notifications: Mutex<Vec<Notification>>,
+    }
+
+    impl Mailer for RecordingNotifiers {
+        fn send_full(&self, params: SendParams<'_>) -> anyhow::Result<()> {
+            self.mails
+                .lock()
+                .unwrap()
+                .push(params.to.unwrap_or_default().to_string());
+            Ok(())
+        }
+    }
+
+    impl Notifier for RecordingNotifiers {
+        fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+            self.notifications
+                .lock()
+                .unwrap()
+                .push(notification.clone());
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+    }
+
+    /// 后台发布的结果邮件与管理员通知都交给 `recorder`
+    fn recorded_by(recorder: &Arc<RecordingNotifiers>) -> PublishNotifiers {
+        PublishNotifiers {
+            mailer: recorder.clone(),
+            admins: vec![recorder.clone()],
+        }
+    }
+
+    fn mock_notifiers() -> PublishNotifiers {
+        recorded_by(&Arc::default())
+    }
+
+    fn limits(max: usize) -> UploadConfig {
+        UploadConfig {
+            content_max_bytes: max,
+            cover_max_bytes: max,
+            image_max_bytes: max,
+            submit_max_mb: 250,
+            json_max_mb: 1,
+        }
+    }
+
+    fn multipart_request(body: Vec<u8>) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn json_submission(content: &str, image_len: usize) -> String {
+        use base64::Engine as _;
+        let image = base64::engine::general_purpose::STANDARD.encode(vec![7u8; image_len]);
+        format!(
+            r#"{{"author":"a","content":"{content}","email":"e@example.com","email_code":"x",
+            "tags":[],"title":"t","cover":{{"name":"c.png","base64":"{image}"}},
+            "images":[{{"name":"1.png","base64":"{image}"}},{{"name":"2.png","base64":"{image}"}}]}}"#
+        )
+    }
+
+    fn text_part(name: &str, value: &str) -> Vec<u8> {
+        format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+        )
+        .into_bytes()
+    }
+
+    fn file_part(name: &str, file_name: &str, bytes: &[u8]) -> Vec<u8> {
+        let mut part = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\n\
+            Content-Type: application/octet-stream\r\n\r\n"
+        )
+        .into_bytes();
+        part.extend_from_slice(bytes);
+        part.extend_from_slice(b"\r\n");
+        part
+    }
+
+    #[tokio::test]
+    async fn test_multipart_submission_files() {
+        config::test_global();
+
+        let cover_bytes = b"\x89PNG cover bytes".to_vec();
+        let image_bytes = vec![0u8, 1, 2, 3, 255, 254];
+
+        let mut body = Vec::new();
+        body.extend(text_part("author", "作者"));
+        body.extend(text_part("content", "正文内容"));
+        body.extend(text_part("email", "author@example.com"));
+        body.extend(text_part("email_code", "123456"));
+        body.extend(text_part("title", "标题"));
+        body.extend(text_part("tags", "科幻"));
+        body.extend(text_part("tags", "短篇"));
+        body.extend(text_part("lang", "en"));
+        body.extend(file_part("cover", "cover.png", &cover_bytes));
+        body.extend(file_part("images", "first.png", &image_bytes));
+        body.extend(format!("--{BOUNDARY}--\r\n").into_bytes());
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/submit/multipart")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::from(body))
+            .unwrap();
+        let multipart = Multipart::from_request(req, &()).await.unwrap();
+
+        let payload = SubmissionRequest::from_multipart(multipart, &config::test_global().upload)
+            .await
+            .unwrap();
+        assert_eq!(payload.author, "作者");
+        assert_eq!(payload.content, "正文内容");
+        assert_eq!(payload.email, "author@example.com");
+        assert_eq!(payload.email_code.as_deref(), Some("123456"));
+        assert_eq!(payload.verification_token, None);
+        assert_eq!(payload.title, "标题");
+        assert_eq!(payload.tags, vec!["科幻".to_string(), "短篇".to_string()]);
+        assert_eq!(payload.lang.as_deref(), Some("en"));
+        assert_eq!(payload.slug, None);
+        assert_eq!(payload.cover.name, "cover.png");
+        assert_eq!(payload.images.len(), 1);
+        assert_eq!(payload.images[0].name, "first.png");
+
+        // 转换为与 JSON 接口相同的 Submission，文件按原始字节提交到仓库
+        let submission = Submission::from_request(payload);
+        let files = submission.files();
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].path, "source/_posts/标题.md");
+        let markdown = String::from_utf8(files[0].content.to_vec()).unwrap();
+        assert!(markdown.contains("正文内容"));
+        assert_eq!(files[1].path, "source/_posts/标题/cover.webp");
+        assert_eq!(files[1].content.as_ref(), cover_bytes.as_slice());
+        assert_eq!(files[2].path, "source/photos/标题/001.webp");
+        assert_eq!(files[2].content.as_ref(), image_bytes.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_submit_with_verification_token() {
+        use crate::handler::auth::{EmailVerifyKey, VerifyRequest, verify};
+        use crate::middleware::mem_map::MemMap;
+
+        config::test_global();
+        let email = "token-submit@example.com".to_string();
+        MemMap::global().insert(
+            EmailVerifyKey::new(email.clone()),
+            "654321".to_string(),
+            chrono::Duration::minutes(5),
+        );
+
+        let resp = verify(
+            Extension(RequestId::new()),
+            axum::Json(VerifyRequest {
+                email: email.clone(),
+                code: "654321".to_string(),
+            }),
+        )
+        .await;
+        let token = resp.data.expect("应返回令牌").verification_token;
+
+        // 标题不合法，在校验令牌前被拦下，不会访问网络
+        let json = format!(
+            r#"{{"author":"a","content":"c","email":"{email}","verification_token":"{token}",
+            "tags":[],"title":"C# 入门","cover":{{"name":"c.png","base64":"AAAA"}},"images":[]}}"#
+        );
+        let payload = || SubmissionRequest::parse_json(json.as_bytes(), &limits(1024)).unwrap();
+
+        let resp = process_submission(
+            Uuid::new_v4(),
+            &HeaderMap::new(),
+            payload(),
+            mock_notifiers(),
+        )
+        .await;
+        assert_eq!(resp.code, 422, "{}", resp.message);
+        let errors = resp.errors.expect("应按字段列出校验错误");
+        assert!(errors.iter().any(|e| e.field == "title"), "{:?}", errors);
+
+        // 校验未通过时令牌不被消费，修改后仍可使用，成功受理后才失效
+        let fixed = json.replace("C# 入门", "Rust 入门");
+        let payload = || SubmissionRequest::parse_json(fixed.as_bytes(), &limits(1024)).unwrap();
+        let resp = process_submission(
+            Uuid::new_v4(),
+            &HeaderMap::new(),
+            payload(),
+            mock_notifiers(),
+        )
+        .await;
+        assert_eq!(resp.code, 202, "{}", resp.message);
+        let other = json.replace("C# 入门", "Go 入门");
+        let payload = SubmissionRequest::parse_json(other.as_bytes(), &limits(1024)).unwrap();
+        let resp =
+            process_submission(Uuid::new_v4(), &HeaderMap::new(), payload, mock_notifiers()).await;
+        assert_eq!(resp.code, 401);
+    }
+
+    #[tokio::test]
+    async fn test_failed_validation_keeps_code() {
+        use crate::handler::auth::EmailVerifyKey;
+        use crate::middleware::mem_map::MemMap;
+
+        config::test_global();
+        let email = "validation-keeps-code@example.com";
+        MemMap::global().insert(
+            EmailVerifyKey::new(email.to_string()),
+            "123456".to_string(),
+            chrono::Duration::minutes(5),
+        );
+        let headers = HeaderMap::new();
+        let submit = |title: &str| {
+            let json = format!(
+                r#"{{"author":"a","content":"c","email":"{email}","email_code":"123456",
+                "tags":[],"title":"{title}","cover":{{"name":"c.png","base64":"AAAA"}},"images":[]}}"#
+            );
+            let payload = SubmissionRequest::parse_json(json.as_bytes(), &limits(1024)).unwrap();
+            process_submission(Uuid::new_v4(), &headers, payload, mock_notifiers())
+        };
+
+        // 标题为空，校验失败；验证码仍在缓存中
+        let resp = submit("").await;
+        assert_eq!(resp.code, 422, "{}", resp.message);
+        assert!(
+            MemMap::global()
+                .get::<EmailVerifyKey, String>(&EmailVerifyKey::new(email.to_string()))
+                .is_some()
+        );
+
+        // 改正后用同一个验证码提交即可受理
+        let resp = submit("改正后的标题").await;
+        assert_eq!(resp.code, 202, "{}", resp.message);
+    }
+
+    #[tokio::test]
+    async fn test_validate_submission_reports_errors() {
+        config::test_global();
+
+        let json = r#"{"author":"","content":"c","email":"not-an-email",
+            "tags":[],"title":"标题","cover":{"name":"c.png","base64":"AAAA"},"images":[]}"#;
+        let payload = SubmissionRequest::parse_json(json.as_bytes(), &limits(1024)).unwrap();
+
+        let report = validate_submission(Extension(RequestId::new()), StreamingJson(payload))
+            .await
+            .data
+            .unwrap();
+        assert!(!report.valid);
+        let mut fields: Vec<&str> = report.errors.iter().map(|e| e.field).collect();
+        fields.sort();
+        assert_eq!(fields, ["author", "cover", "email"]);
+
+        // 错误列表以结构化形式返回
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["errors"][0]["field"], report.errors[0].field);
+    }
+
+    #[tokio::test]
+    async fn test_branch_pr_status_rejects_other_branches() {
+        config::test_global();
+        for branch in ["main", "contrib-x", "../contrib"] {
+            let resp = branch_pr_status(
+                Extension(RequestId::new()),
+                Query(BranchQuery {
+                    branch: branch.to_string(),
+                }),
+            )
+            .await;
+            assert_eq!(resp.code, 400, "{}", branch);
+            assert_eq!(resp.error_code, Some(ErrorCode::InvalidBranch));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preview_submission() {
+        config::test_global();
+
+        // 1x1 PNG
+        let png = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAAC0lEQVQIW2NgAAIAAAUAAR4f7BQAAAAASUVORK5CYII=";
+        let json = format!(
+            r#"{{"author":"作者","content":"预览正文","email":"preview@example.com",
+            "tags":["Rust"],"title":"预览投稿","cover":{{"name":"c.png","base64":"{png}"}},
+            "images":[{{"name":"i.png","base64":"{png}"}}]}}"#
+        );
+        let payload = SubmissionRequest::parse_json(json.as_bytes(), &limits(1024)).unwrap();
+
+        let id = RequestId::new();
+        let resp = preview_submission(Extension(id), StreamingJson(payload)).await;
+        assert_eq!(resp.code, 200, "{}", resp.message);
+        let preview = resp.data.unwrap();
+        assert!(
+            preview.markdown.starts_with("---\n"),
+            "{}",
+            preview.markdown
+        );
+        assert!(preview.markdown.contains("title: 预览投稿"));
+        assert!(preview.markdown.contains("- rust"));
+        assert!(preview.markdown.contains("预览正文"));
+        assert_eq!(
+            preview.files,
+            [
+                "source/_posts/预览投稿.md",
+                "source/_posts/预览投稿/cover.webp",
+                "source/photos/预览投稿/001.webp",
+            ]
+        );
+
+        // 预览不进入后台投稿流程
+        assert!(submission_queue::get_status(id.0).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_submit_returns_before_publishing() {
+        use crate::handler::auth::EmailVerifyKey;
+        use crate::middleware::mem_map::MemMap;
+
+        config::test_global();
+        let email = "background-submit@example.com";
+        MemMap::global().insert(
+            EmailVerifyKey::new(email.to_string()),
+            "123456".to_string(),
+            chrono::Duration::minutes(5),
+        );
+
+        // 封面不是有效图片，后台转换时失败，不会访问 GitHub
+        let json = format!(
+            r#"{{"author":"a","content":"c","email":"{email}","email_code":"123456",
+            "tags":[],"title":"后台投稿","cover":{{"name":"c.png","base64":"AAAA"}},"images":[]}}"#
+        );
+        let payload = SubmissionRequest::parse_json(json.as_bytes(), &limits(1024)).unwrap();
+
+        let id = Uuid::new_v4();
+        let recorder = Arc::new(RecordingNotifiers::default());
+        let resp = process_submission(id, &HeaderMap::new(), payload, recorded_by(&recorder)).await;
+        assert_eq!(resp.code, 202, "{}", resp.message);
+        assert_eq!(resp.data.unwrap().submission_id, id);
+
+        // 后台任务随后执行并记录失败原因
+        let mut failed = false;
+        for _ in 0..500 {
+            if let Some(SubmissionStatus::Failed { reason }) = submission_queue::get_status(id) {
+                assert!(reason.contains("转换为 WebP 失败"), "{}", reason);
+                failed = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(failed, "status: {:?}", submission_queue::get_status(id));
+
+        // 失败邮件与管理员通知交给注入的渠道，由后台队列发送
+        for _ in 0..500 {
+            let notified = !recorder.notifications.lock().unwrap().is_empty();
+            if notified && !recorder.mails.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(*recorder.mails.lock().unwrap(), [email]);
+        let notifications = recorder.notifications.lock().unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].event, NotificationEvent::SubmissionFailed);
+        assert!(
+            notifications[0].body.contains("转换为 WebP 失败"),
+            "{}",
+            notifications[0].body
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_is_not_a_duplicate() {
+        config::test_global();
+        let email = "resubmit-duplicate@example.com";
+        let headers = HeaderMap::new();
+        let payload = |branch: Option<&str>, code: &str| {
+            let branch = branch
+                .map(|b| format!(r#""branch":"{b}","#))
+                .unwrap_or_default();
+            let json = format!(
+                r#"{{"author":"a","content":"c","email":"{email}","email_code":"{code}",{branch}
+                "tags":[],"title":"重新投稿","cover":{{"name":"c.png","base64":"AAAA"}},"images":[]}}"#
+            );
+            SubmissionRequest::parse_json(json.as_bytes(), &limits(1024)).unwrap()
+        };
+        let submit = |branch: Option<&str>, code: &str| {
+            process_submission(
+                Uuid::new_v4(),
+                &headers,
+                payload(branch, code),
+                mock_notifiers(),
+            )
+        };
+
+        // 原投稿刚受理、仍在处理中，已登记指纹
+        let original = Uuid::new_v4();
+        let fingerprint = Submission::from_request(payload(None, "123456")).fingerprint();
+        submission_queue::claim_fingerprint(&fingerprint, original, chrono::Duration::minutes(10))
+            .await
+            .unwrap();
+        submission_queue::set_status(original, SubmissionStatus::Processing);
+        let branch = format!("contrib-{}", Uuid::new_v4());
+
+        // 相同内容的新投稿被拒绝
+        let resp = submit(None, "000000").await;
+        assert_eq!(resp.error_code, Some(ErrorCode::DuplicateSubmission));
+
+        // 重新投稿到原分支不受重复检查限制，继续校验验证码；
+        // 这里用错误的验证码在查询分支归属之前结束，不会访问 GitHub
+        let resp = submit(Some(&branch), "000000").await;
+        assert_eq!(
+            resp.error_code,
+            Some(ErrorCode::InvalidCode),
+            "{}",
+            resp.message
+        );
+    }
+
+    fn idempotent_headers(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, key.parse().unwrap());
+        headers
+    }
+
+    /// 封面无效、后台必然失败的投稿，不会访问 GitHub
+    fn idempotent_payload(email: &str, title: &str) -> SubmissionRequest {
+        use crate::handler::auth::EmailVerifyKey;
+        use crate::middleware::mem_map::MemMap;
+
+        MemMap::global().insert(
+            EmailVerifyKey::new(email.to_string()),
+            "123456".to_string(),
+            chrono::Duration::minutes(5),
+        );
+        let json = format!(
+            r#"{{"author":"a","content":"c","email":"{email}","email_code":"123456",
+            "tags":[],"title":"{title}","cover":{{"name":"c.png","base64":"AAAA"}},"images":[]}}"#
+        );
+        SubmissionRequest::parse_json(json.as_bytes(), &limits(1024)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_repeated_idempotency_key_returns_prior_result() {
+        config::test_global();
+        let email = "idempotent-repeat@example.com";
+        let headers = idempotent_headers(&Uuid::new_v4().to_string());
+
+        let first = process_submission(
+            Uuid::new_v4(),
+            &headers,
+            idempotent_payload(email, "幂等投稿"),
+            mock_notifiers(),
+        )
+        .await;
+        assert_eq!(first.code, 202, "{}", first.message);
+        let first = first.data.unwrap();
+
+        // 重试时验证码已被消费，仍返回首次受理的投稿
+        let retry = process_submission(
+            Uuid::new_v4(),
+            &headers,
+            idempotent_payload("other@example.com", "另一篇"),
+            mock_notifiers(),
+        )
+        .await;
+        assert_eq!(retry.code, 202, "{}", retry.message);
+        let retry = retry.data.unwrap();
+        assert_eq!(retry.submission_id, first.submission_id);
+        assert_eq!(retry.branch, first.branch);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_idempotency_keys_are_independent() {
+        config::test_global();
+        let email = "idempotent-distinct@example.com";
+
+        let first = process_submission(
+            Uuid::new_v4(),
+            &idempotent_headers("key-a"),
+            idempotent_payload(email, "第一篇"),
+            mock_notifiers(),
+        )
+        .await;
+        let second = process_submission(
+            Uuid::new_v4(),
+            &idempotent_headers("key-b"),
+            idempotent_payload(email, "第二篇"),
+            mock_notifiers(),
+        )
+        .await;
+        assert_eq!(first.code, 202, "{}", first.message);
+        assert_eq!(second.code, 202, "{}", second.message);
+        assert_ne!(
+            first.data.unwrap().submission_id,
+            second.data.unwrap().submission_id
+        );
+
+        // 过长的幂等键被拒绝
+        let resp = process_submission(
+            Uuid::new_v4(),
+            &idempotent_headers(&"k".repeat(256)),
+            idempotent_payload(email, "第三篇"),
+            mock_notifiers(),
+        )
+        .await;
+        assert_eq!(resp.code, 400);
+        assert_eq!(resp.error_code, Some(ErrorCode::InvalidIdempotencyKey));
+    }
+
+    #[test]
+    fn test_submit_accepted_json() {
+        let id = Uuid::new_v4();
+        let json = serde_json::to_value(SubmitAccepted {
+            submission_id: id,
+            branch: Some("contrib-1".to_string()),
+            pr_url: None,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"submission_id": id, "branch": "contrib-1"})
+        );
+
+        // 测试投稿不创建分支
+        let json = serde_json::to_value(SubmitAccepted {
+            submission_id: id,
+            branch: None,
+            pr_url: None,
+        })
+        .unwrap();
+        assert_eq!(json, serde_json::json!({"submission_id": id}));
+    }
+
+    #[tokio::test]
+    async fn test_submission_status_endpoint() {
+        let id = Uuid::new_v4();
+        let resp = submission_status(Extension(RequestId::new()), Path(id)).await;
+        assert_eq!(resp.code, 404);
+
+        submission_queue::set_status(id, SubmissionStatus::Processing);
+        let resp = submission_status(Extension(RequestId::new()), Path(id)).await;
+        assert_eq!(resp.data, Some(SubmissionStatus::Processing));
+    }
+
+    #[test]
+    fn test_json_within_limits() {
+        let json = json_submission("hello", 8);
+        let req = SubmissionRequest::parse_json(json.as_bytes(), &limits(8)).unwrap();
+        assert_eq!(req.images.len(), 2);
+        assert_eq!(req.cover.bytes, vec![7u8; 8]);
+    }
+
+    #[test]
+    fn test_json_oversized_content() {
+        let json = json_submission(&"x".repeat(9), 8);
+        let err = SubmissionRequest::parse_json(json.as_bytes(), &limits(8))
+            .err()
+            .expect("正文超限时应解析失败");
+        let field = err
+            .downcast_ref::<FieldTooLarge>()
+            .expect("应为字段超限错误");
+        assert_eq!(field.field, "content");
+    }
+
+    #[test]
+    fn test_json_oversized_single_image() {
+        let json = json_submission("hello", 9);
+        let mut limits = limits(8);
+        limits.cover_max_bytes = 16;
+        let err = SubmissionRequest::parse_json(json.as_bytes(), &limits)
+            .err()
+            .expect("图片超限时应解析失败");
+        let field = err
+            .downcast_ref::<FieldTooLarge>()
+            .expect("应为字段超限错误");
+        assert_eq!(field.field, "images[0]");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_oversized_content() {
+        let mut body = Vec::new();
+        body.extend(text_part("author", "作者"));
+        body.extend(text_part("content", &"x".repeat(9)));
+        body.extend(format!("--{BOUNDARY}--\r\n").into_bytes());
+
+        let multipart = Multipart::from_request(multipart_request(body), &())
+            .await
+            .unwrap();
+        let err = SubmissionRequest::from_multipart(multipart, &limits(8))
+            .await
+            .err()
+            .expect("正文超限时应解析失败");
+        let field = err
+            .downcast_ref::<FieldTooLarge>()
+            .expect("应为字段超限错误");
+        assert_eq!(field.field, "content");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_oversized_single_image() {
+        let mut body = Vec::new();
+        body.extend(file_part("images", "ok.png", &[0u8; 8]));
+        body.extend(file_part("images", "big.png", &[0u8; 9]));
+        body.extend(format!("--{BOUNDARY}--\r\n").into_bytes());
+
+        let multipart = Multipart::from_request(multipart_request(body), &())
+            .await
+            .unwrap();
+        let err = SubmissionRequest::from_multipart(multipart, &limits(8))
+            .await
+            .err()
+            .expect("图片超限时应解析失败");
+        let field = err
+            .downcast_ref::<FieldTooLarge>()
+            .expect("应为字段超限错误");
+        assert_eq!(field.field, "images[1]");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_rejects_malformed_parts() {
+        // 封面超出单文件上限
+        let mut body = Vec::new();
+        body.extend(file_part("cover", "cover.png", &[0u8; 9]));
+        body.extend(format!("--{BOUNDARY}--\r\n").into_bytes());
+        let multipart = Multipart::from_request(multipart_request(body), &())
+            .await
+            .unwrap();
+        let err = SubmissionRequest::from_multipart(multipart, &limits(8))
+            .await
+            .err()
+            .expect("封面超限时应解析失败");
+        let field = err
+            .downcast_ref::<FieldTooLarge>()
+            .expect("应为字段超限错误");
+        assert_eq!(field.field, "cover");
+
+        // 正文不是有效的 UTF-8
+        let mut body = Vec::new();
+        body.extend(file_part("content", "content.md", &[0xff, 0xfe, 0xfd]));
+        body.extend(format!("--{BOUNDARY}--\r\n").into_bytes());
+        let multipart = Multipart::from_request(multipart_request(body), &())
+            .await
+            .unwrap();
+        let err = SubmissionRequest::from_multipart(multipart, &limits(1024))
+            .await
+            .err()
+            .expect("正文不是 UTF-8 时应解析失败");
+        assert!(err.to_string().contains("UTF-8"), "{:#}", err);
+
+        // 缺少结束边界的截断表单
+        let mut body = Vec::new();
+        body.extend(text_part("author", "作者"));
+        body.extend(
+            format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\n标")
+                .into_bytes(),
+        );
+        let multipart = Multipart::from_request(multipart_request(body), &())
+            .await
+            .unwrap();
+        let err = SubmissionRequest::from_multipart(multipart, &limits(1024))
+            .await
+            .err()
+            .expect("表单被截断时应解析失败");
+        assert!(err.downcast_ref::<FieldTooLarge>().is_none());
+        assert!(!err.to_string().contains("缺少字段"), "{:#}", err);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_missing_field() {
+        let mut body = Vec::new();
+        body.extend(text_part("author", "作者"));
+        body.extend(format!("--{BOUNDARY}--\r\n").into_bytes());
+
+        let multipart = Multipart::from_request(multipart_request(body), &())
+            .await
+            .unwrap();
+
+        let err = SubmissionRequest::from_multipart(multipart, &limits(1024))
+            .await
+            .err()
+            .expect("缺少字段时应解析失败");
+        assert!(err.to_string().contains("缺少字段"));
+    }
 }
@@ -1,6 +1,8 @@
 use crate::config::AppConfig;
 use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
+use tracing::{info, warn};
 
 mod config;
 mod handler;
@@ -11,13 +13,62 @@ mod utils;
 
 #[tokio::main]
 async fn main() {
-    let config = AppConfig::global();
-    utils::log::init_tracing();
+    // 日志尚未初始化，配置错误直接输出到 stderr 后退出
+    let config = match AppConfig::try_global() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to load config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // 持有到 main() 结束，保证退出前的日志全部写入文件
+    let _log_guard = utils::log::init_tracing();
+    config.admin.warn_if_empty();
+    middleware::mem_map::set_capacity(config.cache.max_entries);
+    if let Some(path) = &config.cache.snapshot_path {
+        middleware::mem_map::enable_snapshot(
+            path.clone(),
+            Duration::from_secs(config.cache.snapshot_interval_secs),
+        );
+    }
+    #[cfg(unix)]
+    tokio::spawn(utils::reload::reload_on_sighup());
     let app = routes::routers();
 
     let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
     let listener = TcpListener::bind(addr).await.unwrap();
-    println!("Server running at https://{}", addr);
+    info!("Server running at https://{}", addr);
 
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(utils::shutdown::shutdown_signal())
+    .await
+    .unwrap();
+    info!("SHUTDOWN: server stopped, draining background jobs");
+
+    // 先等进行中的投稿发布完成（它们还会提交邮件任务），再等排队中的邮件、通知发送完成，
+    // 两者共用同一个超时
+    let deadline = Instant::now() + Duration::from_secs(config.shutdown_timeout_secs);
+    let published =
+        middleware::submission_queue::shutdown(deadline.saturating_duration_since(Instant::now()))
+            .await;
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let drained =
+        tokio::task::spawn_blocking(move || middleware::background::shutdown_background(remaining))
+            .await
+            .unwrap();
+
+    // 定期快照可能落后于最后的写入，排空后台任务后再写一次
+    if let Some(path) = &config.cache.snapshot_path {
+        match middleware::mem_map::MemMap::global().dump_to(path) {
+            Ok(n) => info!("SHUTDOWN: wrote {} cache entries to {}", n, path.display()),
+            Err(e) => warn!("SHUTDOWN: write cache snapshot failed: {:#}", e),
+        }
+    }
+    info!(
+        "SHUTDOWN: complete (submissions published = {}, background drained = {})",
+        published, drained
+    );
 }
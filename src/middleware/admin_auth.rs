@@ -0,0 +1,82 @@
+use crate::config::AppConfig;
+use crate::middleware::request_id::RequestId;
+use crate::response::{ApiResponse, ErrorCode};
+use axum::body::Body;
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use secrecy::ExposeSecret;
+use tracing::warn;
+
+/// 管理接口携带 API Key 的请求头
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// 常量时间比较，避免通过响应时间猜测 key
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 校验请求头中的 API Key 是否与配置一致
+pub fn check_api_key(expected: Option<&str>, provided: Option<&str>) -> bool {
+    match (expected, provided) {
+        (Some(expected), Some(provided)) => {
+            constant_time_eq(expected.as_bytes(), provided.as_bytes())
+        }
+        _ => false,
+    }
+}
+
+/// 请求头中是否携带与配置一致的 API Key
+pub fn has_api_key(headers: &HeaderMap) -> bool {
+    let config = AppConfig::global();
+    let expected = config
+        .admin
+        .api_key
+        .as_ref()
+        .map(|k| k.expose_secret().as_str());
+    let provided = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok());
+    check_api_key(expected, provided)
+}
+
+/// 管理接口鉴权中间件，配合 `axum::middleware::from_fn` 使用
+pub async fn require_api_key(req: Request<Body>, next: Next) -> Response {
+    if has_api_key(req.headers()) {
+        return next.run(req).await;
+    }
+
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .copied()
+        .unwrap_or_else(RequestId::new);
+    warn!(
+        uri = %req.uri(),
+        "ADMIN_AUTH: rejected request with missing or invalid api key"
+    );
+    ApiResponse::<()>::error_with_code(
+        StatusCode::UNAUTHORIZED,
+        ErrorCode::InvalidApiKey,
+        "API Key 无效",
+        request_id,
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_api_key() {
+        assert!(check_api_key(Some("secret"), Some("secret")));
+        assert!(!check_api_key(Some("secret"), Some("secreT")));
+        assert!(!check_api_key(Some("secret"), Some("secret-longer")));
+        assert!(!check_api_key(Some("secret"), None));
+        // 未配置 key 时一律拒绝
+        assert!(!check_api_key(None, Some("secret")));
+        assert!(!check_api_key(None, None));
+    }
+}
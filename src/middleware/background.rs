@@ -1,70 +1,572 @@
 use once_cell::sync::Lazy;
-use std::sync::{mpsc, Arc};
+use std::fmt;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use tracing::{error, info, warn};
-use crate::utils::email::{Mailer, SmtpMailer};
+use std::time::{Duration, Instant};
+use tracing::{Span, debug, error, info, warn};
+use crate::config::AppConfig;
+use crate::utils::email::{Mailer, SendParams};
+use crate::utils::notify::{Notification, Notifier, configured_notifiers};
 
 /// 一条后台任务
 type Job = Box<dyn FnOnce() + Send + 'static>;
+/// 任务名、提交时所在的 span（携带 request_id 等字段）与任务本身
+type Task = (&'static str, Span, Job);
 
-/// 全局 Sender，用 std::sync::mpsc 即可
-static JOB_TX: Lazy<mpsc::Sender<( &'static str, Job )>> = Lazy::new(|| {
-    let (tx, rx) = mpsc::channel::<(&'static str, Job)>();
+/// 任务未能进入队列的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnqueueError {
+    /// 队列已满
+    Full,
+    /// 队列已关闭或 worker 已退出
+    Closed,
+}
+
+impl fmt::Display for EnqueueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnqueueError::Full => write!(f, "background queue is full"),
+            EnqueueError::Closed => write!(f, "background queue is closed"),
+        }
+    }
+}
 
-    // 启一个常驻 worker 线程，专门执行这些任务
-    thread::spawn(move || {
-        info!("TASK_POOL: worker thread started");
+impl std::error::Error for EnqueueError {}
 
-        for (name, job) in rx {
-            info!("TASK_POOL[{name}]: started");
+/// 有界的后台任务队列，队列满时立即拒绝而不是阻塞调用方
+pub struct TaskQueue {
+    /// 关闭后为 None，不再接受新任务
+    tx: Mutex<Option<mpsc::SyncSender<Task>>>,
+    workers: Mutex<Vec<thread::JoinHandle<()>>>,
+}
 
-            // 防止某个任务 panic 把整个线程干崩
-            if let Err(e) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job)) {
-                error!("TASK_POOL[{name}]: panicked: {:?}", e);
-            } else {
-                info!("TASK_POOL[{name}]: finished");
+impl TaskQueue {
+    /// 创建最多排队 `capacity` 个任务的队列，返回的 Receiver 交给 worker 消费
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<Task>) {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        let queue = Self {
+            tx: Mutex::new(Some(tx)),
+            workers: Mutex::default(),
+        };
+        (queue, rx)
+    }
+
+    /// 创建队列并启动 `workers` 个 worker 消费，关闭时会等待它们退出
+    pub fn start(capacity: usize, workers: usize) -> Self {
+        let (queue, rx) = Self::new(capacity);
+        *queue.workers.lock().unwrap() = spawn_workers(rx, workers);
+        queue
+    }
+
+    /// 提交任务，不阻塞；任务执行时会进入提交时所在的 span，日志因此带上请求 ID
+    pub fn submit<F>(&self, name: &'static str, f: F) -> Result<(), EnqueueError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let tx = self.tx.lock().unwrap();
+        let Some(tx) = tx.as_ref() else {
+            return Err(EnqueueError::Closed);
+        };
+        tx.try_send((name, Span::current(), Box::new(f))).map_err(|e| match e {
+            mpsc::TrySendError::Full(_) => EnqueueError::Full,
+            mpsc::TrySendError::Disconnected(_) => EnqueueError::Closed,
+        })
+    }
+
+    /// 停止接受新任务，等待 worker 执行完已排队的任务后退出
+    ///
+    /// 最多等待 `timeout`，返回是否在超时前全部完成；超时后剩余的任务随进程退出被放弃
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        // 丢弃 sender 后，worker 取完剩余任务便会退出
+        self.tx.lock().unwrap().take();
+        let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+
+        let deadline = Instant::now() + timeout;
+        while workers.iter().any(|w| !w.is_finished()) {
+            if Instant::now() >= deadline {
+                warn!("TASK_POOL: shutdown timed out, unfinished jobs are abandoned");
+                return false;
             }
+            thread::sleep(Duration::from_millis(10));
         }
+        for worker in workers {
+            // worker 内部已捕获任务的 panic，这里不会出错
+            let _ = worker.join();
+        }
+        info!("TASK_POOL: background queue drained");
+        true
+    }
+}
+
+/// 启动 `workers` 个线程共同消费同一个队列，至少启动一个
+pub fn spawn_workers(rx: mpsc::Receiver<Task>, workers: usize) -> Vec<thread::JoinHandle<()>> {
+    let rx = Arc::new(Mutex::new(rx));
+    (0..workers.max(1))
+        .map(|id| {
+            let rx = rx.clone();
+            thread::spawn(move || run_worker(id, &rx))
+        })
+        .collect()
+}
+
+/// 从共享的队列中逐个取出任务执行，直到所有 sender 被丢弃
+fn run_worker(id: usize, rx: &Mutex<mpsc::Receiver<Task>>) {
+    info!("TASK_POOL: worker thread {id} started");
+
+    loop {
+        // 只在取任务时持有锁，任务执行期间其他 worker 可以继续取
+        let next = rx.lock().unwrap().recv();
+        let Ok(task) = next else {
+            break;
+        };
+        run_task(id, task);
+    }
+
+    info!("TASK_POOL: worker thread {id} exiting (sender dropped)");
+}
 
-        info!("TASK_POOL: worker thread exiting (sender dropped)");
-    });
+/// 在提交任务时的 span 中执行任务
+fn run_task(worker: usize, (name, span, job): Task) {
+    let _entered = span.enter();
+    info!("TASK_POOL[{name}]: started on worker {worker}");
 
-    tx
+    // 防止某个任务 panic 把整个线程干崩
+    if let Err(e) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job)) {
+        error!("TASK_POOL[{name}]: panicked: {:?}", e);
+    } else {
+        info!("TASK_POOL[{name}]: finished");
+    }
+}
+
+/// 全局任务队列，由常驻的 worker 线程池消费
+static JOB_QUEUE: Lazy<TaskQueue> = Lazy::new(|| {
+    let config = AppConfig::global();
+    TaskQueue::start(config.background_queue_capacity, config.background_workers)
 });
 
-/// 对外暴露：获取全局任务 sender
-pub fn task_sender() -> &'static mpsc::Sender<(&'static str, Job)> {
-    &JOB_TX
+/// 对外暴露：获取全局任务队列
+pub fn task_queue() -> &'static TaskQueue {
+    &JOB_QUEUE
+}
+
+/// 关闭全局任务队列，等待已排队的任务完成；队列从未启用时直接返回
+pub fn shutdown_background(timeout: Duration) -> bool {
+    match Lazy::get(&JOB_QUEUE) {
+        Some(queue) => queue.shutdown(timeout),
+        None => true,
+    }
 }
 
 /// 提交一个后台任务到全局任务池
-pub fn submit_background<F>(name: &'static str, f: F)
+///
+/// 队列已满或 worker 已退出时返回错误并记录日志，由调用方决定如何处理被丢弃的任务
+pub fn submit_background<F>(name: &'static str, f: F) -> Result<(), EnqueueError>
 where
     F: FnOnce() + Send + 'static,
 {
-    // 如果队列满/发送失败，就打个日志，不影响主流程
-    if let Err(e) = task_sender().send((name, Box::new(f))) {
-        error!("TASK_POOL[{name}]: failed to enqueue job: {}", e);
-    }
+    task_queue().submit(name, f).inspect_err(|e| {
+        error!("TASK_POOL[{name}]: job dropped: {}", e);
+    })
 }
 
 static MAIL: &str= "mail";
 
-pub fn send_mail_background(
-    mailer: Arc<SmtpMailer>,
+/// 后台发送 HTML 邮件，`text` 为纯文本备选内容；队列满时邮件不会发送
+///
+/// 重试由 `mailer` 负责，后台任务应使用 [`SmtpMailer::background`](crate::utils::email::SmtpMailer::background)
+pub fn send_html_mail_background(
+    mailer: Arc<dyn Mailer>,
     to: String,
     subject: String,
-    body: String,
-) {
-    submit_background(MAIL,move || {
-        if let Err(e) = mailer.send(&to, &subject, &body) {
-            warn!("MAIL_BG[{MAIL}]: send mail to {} failed: {:#}", to, e);
+    html: String,
+    text: String,
+) -> Result<(), EnqueueError> {
+    submit_background(MAIL, move || {
+        if let Err(e) = mailer.send_html(&to, &subject, &html, &text) {
+            warn!("MAIL_BG[{MAIL}]: send html mail to {} failed: {:#}", to, e);
         } else {
-            info!(
-                    "MAIL_BG[{MAIL}]: mail sent to {} (subject = {})",
+            info!("MAIL_BG[{MAIL}]: html mail sent to {} (subject = {})", to, subject);
+        }
+    })
+}
+
+static MAIL_BATCH: &str = "mail_batch";
+
+/// 构造一次性发给多个收件人的后台任务，单个收件人失败不影响其他人
+///
+/// `concurrency` 为同时发送的最大数量，不大于 1 时逐个发送；
+/// 重试由 `mailer` 自身负责，`reply_to` 为每封邮件的回复地址
+pub fn mail_batch_job(
+    mailer: Arc<dyn Mailer>,
+    recipients: Vec<String>,
+    subject: String,
+    body: String,
+    reply_to: Option<String>,
+    concurrency: usize,
+) -> impl FnOnce() + Send + 'static {
+    move || {
+        let send_one = |to: &String| {
+            let params = SendParams {
+                to: Some(to),
+                reply_to: reply_to.as_deref(),
+                subject: &subject,
+                body: &body,
+                ..Default::default()
+            };
+            if let Err(e) = mailer.send_full(params) {
+                warn!("MAIL_BG[{MAIL_BATCH}]: send mail to {} failed: {:#}", to, e);
+            } else {
+                info!(
+                    "MAIL_BG[{MAIL_BATCH}]: mail sent to {} (subject = {})",
                     to, subject
                 );
+            }
+        };
+
+        if concurrency <= 1 {
+            recipients.iter().for_each(send_one);
+            return;
+        }
+        for chunk in recipients.chunks(concurrency) {
+            thread::scope(|s| {
+                for to in chunk {
+                    s.spawn(|| send_one(to));
+                }
+            });
+        }
+    }
+}
+
+static NOTIFY: &str = "notify";
+
+/// 通过全部已配置的渠道通知管理员：无论管理员有多少，只提交一个后台任务
+pub fn notify_admins_background(notification: Notification) -> Result<(), EnqueueError> {
+    notify_background(configured_notifiers(), notification)
+}
+
+/// 通过 `notifiers` 中的每个渠道发送通知，全部渠道共用一个后台任务
+pub fn notify_background(
+    notifiers: Vec<Arc<dyn Notifier>>,
+    notification: Notification,
+) -> Result<(), EnqueueError> {
+    if notifiers.is_empty() {
+        debug!("NOTIFY_BG[{NOTIFY}]: no notifier configured, notification skipped");
+        return Ok(());
+    }
+
+    submit_background(NOTIFY, move || {
+        for notifier in &notifiers {
+            // 某个渠道失败不影响其他渠道
+            if let Err(e) = notifier.notify(&notification) {
+                warn!(
+                    "NOTIFY_BG[{NOTIFY}]: {} notification failed: {:#}",
+                    notifier.name(),
+                    e
+                );
+            } else {
+                info!(
+                    "NOTIFY_BG[{NOTIFY}]: {} notification sent (subject = {})",
+                    notifier.name(),
+                    notification.subject
+                );
+            }
         }
-    });
+    })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::email::{RetryPolicy, RetryableError, SmtpMailer};
+    use lettre::Transport;
+    use std::sync::Mutex;
+
+    /// 记录收件人，对指定地址模拟发送失败
+    #[derive(Default)]
+    struct RecordingMailer {
+        sent: Mutex<Vec<String>>,
+        fail_for: Option<&'static str>,
+    }
+
+    impl Mailer for RecordingMailer {
+        fn send_full(&self, params: SendParams<'_>) -> anyhow::Result<()> {
+            let to = params.to.unwrap_or_default();
+            if self.fail_for == Some(to) {
+                anyhow::bail!("mock failure");
+            }
+            self.sent.lock().unwrap().push(to.to_string());
+            Ok(())
+        }
+    }
+
+    /// 前 `failures` 次发送失败，之后成功
+    #[derive(Default)]
+    struct FlakyMailer {
+        failures: u32,
+        attempts: Mutex<u32>,
+        sent: Mutex<Vec<String>>,
+    }
+
+    impl Mailer for FlakyMailer {
+        fn send_full(&self, params: SendParams<'_>) -> anyhow::Result<()> {
+            let to = params.to.unwrap_or_default();
+            let mut attempts = self.attempts.lock().unwrap();
+            *attempts += 1;
+            if *attempts <= self.failures {
+                anyhow::bail!("mock failure #{}", *attempts);
+            }
+            self.sent.lock().unwrap().push(to.to_string());
+            Ok(())
+        }
+    }
+
+    fn admins() -> Vec<String> {
+        (1..=5).map(|i| format!("admin{}@example.com", i)).collect()
+    }
+
+    #[test]
+    fn test_full_queue_rejects_without_blocking() {
+        // 没有 worker 消费，队列填满后的提交应立即失败
+        let (queue, rx) = TaskQueue::new(2);
+        assert_eq!(queue.submit("job", || {}), Ok(()));
+        assert_eq!(queue.submit("job", || {}), Ok(()));
+        assert_eq!(queue.submit("job", || {}), Err(EnqueueError::Full));
+
+        // 消费一个后又能继续提交
+        let (_, _, job) = rx.recv().unwrap();
+        job();
+        assert_eq!(queue.submit("job", || {}), Ok(()));
+
+        drop(rx);
+        assert_eq!(queue.submit("job", || {}), Err(EnqueueError::Closed));
+    }
+
+    #[test]
+    fn test_workers_run_jobs_concurrently() {
+        use std::time::{Duration, Instant};
+
+        let (queue, rx) = TaskQueue::new(8);
+        spawn_workers(rx, 3);
+
+        let (done_tx, done_rx) = mpsc::channel();
+        for _ in 0..3 {
+            let done_tx = done_tx.clone();
+            queue
+                .submit("slow", move || {
+                    let start = Instant::now();
+                    thread::sleep(Duration::from_millis(200));
+                    done_tx.send((start, Instant::now())).unwrap();
+                })
+                .unwrap();
+        }
+
+        let spans: Vec<(Instant, Instant)> = (0..3)
+            .map(|_| done_rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        // 所有任务都在第一个任务结束前开始，说明它们是并发执行的
+        let last_start = spans.iter().map(|s| s.0).max().unwrap();
+        let first_end = spans.iter().map(|s| s.1).min().unwrap();
+        assert!(last_start < first_end);
+    }
+
+    #[test]
+    fn test_panicking_job_does_not_kill_worker() {
+        let (queue, rx) = TaskQueue::new(4);
+        spawn_workers(rx, 1);
+
+        let (done_tx, done_rx) = mpsc::channel();
+        queue.submit("panic", || panic!("boom")).unwrap();
+        queue.submit("after", move || done_tx.send(()).unwrap()).unwrap();
+        done_rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_shutdown_drains_queued_jobs() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let queue = TaskQueue::start(16, 2);
+        let ran = Arc::new(AtomicUsize::new(0));
+        for _ in 0..10 {
+            let ran = ran.clone();
+            queue
+                .submit("job", move || {
+                    thread::sleep(Duration::from_millis(20));
+                    ran.fetch_add(1, Ordering::SeqCst);
+                })
+                .unwrap();
+        }
+
+        // 返回时已排队的任务全部执行完毕，之后的提交被拒绝
+        assert!(queue.shutdown(Duration::from_secs(5)));
+        assert_eq!(ran.load(Ordering::SeqCst), 10);
+        assert_eq!(queue.submit("late", || {}), Err(EnqueueError::Closed));
+    }
+
+    #[test]
+    fn test_shutdown_gives_up_after_timeout() {
+        let queue = TaskQueue::start(4, 1);
+        queue
+            .submit("slow", || thread::sleep(Duration::from_millis(500)))
+            .unwrap();
+
+        assert!(!queue.shutdown(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_job_runs_in_submitting_span() {
+        let subscriber = tracing_subscriber::registry();
+        tracing::subscriber::with_default(subscriber, || {
+            let (queue, rx) = TaskQueue::new(1);
+            let (seen_tx, seen_rx) = mpsc::channel();
+            tracing::info_span!("request", request_id = "rid").in_scope(|| {
+                queue
+                    .submit("job", move || {
+                        let name = Span::current().metadata().map(|m| m.name());
+                        seen_tx.send(name).unwrap();
+                    })
+                    .unwrap();
+            });
+
+            // 任务在 span 之外被取出执行，仍处于提交时的 span 中
+            run_task(0, rx.recv().unwrap());
+            assert_eq!(seen_rx.recv().unwrap(), Some("request"));
+        });
+    }
+
+    #[test]
+    fn test_single_job_notifies_all_admins() {
+        for concurrency in [1, 2, 8] {
+            let mailer = Arc::new(RecordingMailer {
+                fail_for: Some("admin2@example.com"),
+                ..Default::default()
+            });
+
+            let job = mail_batch_job(
+                mailer.clone(),
+                admins(),
+                "subject".to_string(),
+                "body".to_string(),
+                None,
+                concurrency,
+            );
+            job();
+
+            // 一个任务覆盖全部管理员，失败的那位不影响其他人
+            let mut sent = mailer.sent.lock().unwrap().clone();
+            sent.sort();
+            let expected: Vec<String> = admins()
+                .into_iter()
+                .filter(|a| a != "admin2@example.com")
+                .collect();
+            assert_eq!(sent, expected, "concurrency={}", concurrency);
+        }
+    }
+
+    #[test]
+    fn test_mail_job_does_not_add_its_own_retries() {
+        // 重试只在 mailer 内部进行一层，任务本身不再重发，避免次数相乘与重复投递
+        let mailer = Arc::new(FlakyMailer {
+            failures: 1,
+            ..Default::default()
+        });
+
+        let job = mail_batch_job(
+            mailer.clone(),
+            vec!["admin@example.com".to_string()],
+            "subject".to_string(),
+            "body".to_string(),
+            None,
+            1,
+        );
+        job();
+
+        assert_eq!(*mailer.attempts.lock().unwrap(), 1);
+        assert!(mailer.sent.lock().unwrap().is_empty());
+    }
+
+    /// 前 `failures` 次返回临时性故障的 SMTP transport
+    #[derive(Clone, Default)]
+    struct FlakyTransport {
+        failures: u32,
+        calls: Arc<Mutex<u32>>,
+        delivered: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[derive(Debug)]
+    struct TransientError;
+
+    impl fmt::Display for TransientError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "mock transient failure")
+        }
+    }
+
+    impl std::error::Error for TransientError {}
+
+    impl RetryableError for TransientError {
+        fn is_retryable(&self) -> bool {
+            true
+        }
+    }
+
+    impl Transport for FlakyTransport {
+        type Ok = ();
+        type Error = TransientError;
+
+        fn send_raw(
+            &self,
+            envelope: &lettre::address::Envelope,
+            _email: &[u8],
+        ) -> Result<(), TransientError> {
+            let mut calls = self.calls.lock().unwrap();
+            *calls += 1;
+            if *calls <= self.failures {
+                return Err(TransientError);
+            }
+            let to = envelope.to().iter().map(|a| a.to_string());
+            self.delivered.lock().unwrap().extend(to);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_background_mail_retries_transient_failures() {
+        // 次数取 smtp.background_retry_*，等待缩短以免拖慢测试
+        let cfg = &crate::config::test_global().smtp;
+        assert!(cfg.background_retry_attempts >= 3);
+        let transport = FlakyTransport {
+            failures: 2,
+            ..Default::default()
+        };
+        let mailer = SmtpMailer::with_transport(transport.clone(), "from@example.com".to_string())
+            .with_retry(RetryPolicy {
+                attempts: cfg.background_retry_attempts,
+                base_delay: Duration::from_millis(10),
+            });
+
+        let queue = TaskQueue::start(1, 1);
+        queue
+            .submit(
+                MAIL_BATCH,
+                mail_batch_job(
+                    Arc::new(mailer),
+                    vec!["admin@example.com".to_string()],
+                    "subject".to_string(),
+                    "body".to_string(),
+                    None,
+                    1,
+                ),
+            )
+            .unwrap();
+        assert!(queue.shutdown(Duration::from_secs(5)));
+
+        // 两次临时性故障后第三次送达，且只投递一次
+        assert_eq!(*transport.calls.lock().unwrap(), 3);
+        assert_eq!(
+            *transport.delivered.lock().unwrap(),
+            vec!["admin@example.com".to_string()]
+        );
+    }
+}
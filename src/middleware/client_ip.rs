@@ -0,0 +1,101 @@
+use crate::config::AppConfig;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::net::{IpAddr, SocketAddr};
+
+/// 转发链路头
+pub const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// 解析后的客户端真实地址
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// 直连对端是否为可信代理
+pub fn is_trusted_peer(peer: IpAddr, trusted: &[IpAddr]) -> bool {
+    trusted.contains(&peer)
+}
+
+/// 计算客户端地址
+///
+/// 只有直连对端是可信代理时才采信 X-Forwarded-For：从右往左跳过可信代理，
+/// 取第一个不可信的地址；否则直接使用 socket 对端地址
+pub fn resolve_client_ip(peer: IpAddr, headers: &HeaderMap, trusted: &[IpAddr]) -> IpAddr {
+    if !is_trusted_peer(peer, trusted) {
+        return peer;
+    }
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all(FORWARDED_FOR_HEADER)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+        .collect();
+
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !is_trusted_peer(**ip, trusted))
+        .or(forwarded.first())
+        .copied()
+        .unwrap_or(peer)
+}
+
+/// 把 `ClientIp` 写入请求扩展，需要以 `into_make_service_with_connect_info` 启动服务
+pub async fn client_ip(mut req: Request<Body>, next: Next) -> Response {
+    if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>().copied() {
+        let ip = resolve_client_ip(
+            addr.ip(),
+            req.headers(),
+            &AppConfig::global().trusted_proxies,
+        );
+        req.extensions_mut().insert(ClientIp(ip));
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn trusted() -> Vec<IpAddr> {
+        vec!["127.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()]
+    }
+
+    fn headers(xff: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(FORWARDED_FOR_HEADER, HeaderValue::from_str(xff).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_trusted_peer_uses_forwarded_header() {
+        let peer: IpAddr = "127.0.0.1".parse().unwrap();
+        let ip = resolve_client_ip(peer, &headers("203.0.113.7, 10.0.0.2"), &trusted());
+        assert_eq!(ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+
+        // 没有转发头时退回对端地址
+        let ip = resolve_client_ip(peer, &HeaderMap::new(), &trusted());
+        assert_eq!(ip, peer);
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_forwarded_header() {
+        let peer: IpAddr = "198.51.100.9".parse().unwrap();
+        let ip = resolve_client_ip(peer, &headers("203.0.113.7"), &trusted());
+        assert_eq!(ip, peer);
+    }
+
+    #[test]
+    fn test_spoofed_prefix_is_skipped() {
+        // 客户端自行伪造的最左侧地址不应被采信
+        let peer: IpAddr = "127.0.0.1".parse().unwrap();
+        let ip = resolve_client_ip(peer, &headers("1.1.1.1, 203.0.113.7"), &trusted());
+        assert_eq!(ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+    }
+}
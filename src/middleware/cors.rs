@@ -1,20 +1,55 @@
+use crate::config::{AppConfig, CorsConfig};
 use axum::http::{HeaderValue, Method};
-use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::cors::{AllowHeaders, AllowOrigin, Any, CorsLayer};
+use tracing::warn;
+
+/// 未配置 `cors.allowed_origins` 时允许的来源
+pub const DEFAULT_ALLOWED_ORIGINS: [&str; 3] = [
+    "https://qidian.space",
+    "https://mini.qidian.space",
+    "https://contribute.qidian.space",
+];
+
+/// 解析配置中的来源，无法作为响应头的条目记录日志后跳过
+fn parse_origins(origins: &[String]) -> Vec<HeaderValue> {
+    let configured: Vec<&str> = if origins.is_empty() {
+        DEFAULT_ALLOWED_ORIGINS.to_vec()
+    } else {
+        origins.iter().map(String::as_str).collect()
+    };
+
+    configured
+        .into_iter()
+        .filter_map(|origin| match origin.parse::<HeaderValue>() {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!(origin, error = %e, "CORS: invalid origin skipped");
+                None
+            }
+        })
+        .collect()
+}
+
+/// 按配置构造 CORS 层
+pub fn cors_layer_from(config: &CorsConfig) -> CorsLayer {
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::list(parse_origins(&config.allowed_origins)))
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS]);
+
+    // 允许凭证时不能使用通配请求头，改为回显预检请求中的头
+    if config.allow_credentials {
+        cors.allow_headers(AllowHeaders::mirror_request())
+            .allow_credentials(true)
+    } else {
+        cors.allow_headers(Any)
+    }
+}
 
 pub fn cors_layer() -> CorsLayer {
     // 检查启动参数是否包含 "--test"
     let is_test = std::env::args().any(|arg| arg == "--test");
 
-    let mut cors = CorsLayer::new()
-        .allow_origin(AllowOrigin::list(vec![
-            "https://qidian.space".parse::<HeaderValue>().unwrap(),
-            "https://mini.qidian.space".parse::<HeaderValue>().unwrap(),
-            "https://contribute.qidian.space"
-                .parse::<HeaderValue>()
-                .unwrap(),
-        ]))
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers(Any);
+    let mut cors = cors_layer_from(&AppConfig::global().cors);
 
     if is_test {
         cors = CorsLayer::new()
@@ -26,3 +61,70 @@ pub fn cors_layer() -> CorsLayer {
 
     cors
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::{Request, header};
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    fn app(config: &CorsConfig) -> Router {
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(cors_layer_from(config))
+    }
+
+    async fn preflight(app: Router, origin: &str) -> Option<HeaderValue> {
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        resp.headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn test_custom_origins() {
+        let config = CorsConfig {
+            allowed_origins: vec![
+                "https://staging.example.com".to_string(),
+                "bad\norigin".to_string(),
+                "http://localhost:5173".to_string(),
+            ],
+            allow_credentials: true,
+        };
+
+        for origin in ["https://staging.example.com", "http://localhost:5173"] {
+            assert_eq!(
+                preflight(app(&config), origin).await.unwrap(),
+                HeaderValue::from_str(origin).unwrap()
+            );
+        }
+        // 自定义列表替换内置域名
+        assert_eq!(preflight(app(&config), "https://qidian.space").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_empty_origins_fall_back_to_defaults() {
+        let config = CorsConfig {
+            allowed_origins: Vec::new(),
+            allow_credentials: false,
+        };
+
+        assert_eq!(
+            preflight(app(&config), "https://mini.qidian.space")
+                .await
+                .unwrap(),
+            "https://mini.qidian.space"
+        );
+        assert_eq!(preflight(app(&config), "https://evil.example").await, None);
+    }
+}
@@ -1,15 +1,60 @@
+use crate::config::AppConfig;
+use crate::middleware::client_ip::ClientIp;
 use crate::middleware::request_id::RequestId;
 use axum::body::Body;
-use axum::http::Request;
-use tower_http::trace::{HttpMakeClassifier, TraceLayer};
+use axum::http::{Request, Response};
+use std::time::Duration;
+use tower_http::classify::ServerErrorsFailureClass;
+use tower_http::trace::{
+    DefaultOnBodyChunk, DefaultOnEos, DefaultOnRequest, HttpMakeClassifier, OnResponse, TraceLayer,
+};
 use tracing::{Level, Span};
 use uuid::Uuid;
 
 pub type HttpMakeSpanFn = fn(&Request<Body>) -> Span;
 
-pub type HttpTraceLayer = TraceLayer<HttpMakeClassifier, HttpMakeSpanFn>;
+pub type HttpOnFailureFn = fn(ServerErrorsFailureClass, Duration, &Span);
+
+pub type HttpTraceLayer = TraceLayer<
+    HttpMakeClassifier,
+    HttpMakeSpanFn,
+    DefaultOnRequest,
+    HttpOnResponse,
+    DefaultOnBodyChunk,
+    DefaultOnEos,
+    HttpOnFailureFn,
+>;
+
+/// 记录响应状态码与耗时：5xx 与慢请求记为警告，其余为 debug
+#[derive(Debug, Clone, Copy)]
+pub struct HttpOnResponse {
+    slow_request: Duration,
+}
+
+impl OnResponse<Body> for HttpOnResponse {
+    fn on_response(self, res: &Response<Body>, latency: Duration, span: &Span) {
+        let status = res.status().as_u16();
+        let latency_ms = latency.as_millis() as u64;
+        span.record("status", status);
+        span.record("latency_ms", latency_ms);
+
+        if res.status().is_server_error() {
+            tracing::warn!(status, latency_ms, "HTTP: response");
+        } else if latency >= self.slow_request {
+            tracing::warn!(status, latency_ms, "HTTP: slow request");
+        } else {
+            tracing::debug!(status, latency_ms, "HTTP: response");
+        }
+    }
+}
 
 pub fn trace_layer() -> HttpTraceLayer {
+    let slow_request = Duration::from_millis(AppConfig::global().log.slow_request_ms);
+    trace_layer_with(slow_request)
+}
+
+/// 耗时达到 `slow_request` 的请求记录为慢请求
+pub fn trace_layer_with(slow_request: Duration) -> HttpTraceLayer {
     fn make_span(req: &Request<Body>) -> Span {
         let rid = req
             .extensions()
@@ -17,15 +62,130 @@ pub fn trace_layer() -> HttpTraceLayer {
             .copied()
             .unwrap_or_else(|| RequestId(Uuid::new_v4()));
 
-        tracing::span!(
-            Level::DEBUG,
+        // 与默认日志级别一致，否则 span 被过滤后其中的日志就没有 request_id
+        let span = tracing::span!(
+            Level::INFO,
             "request",
             request_id = display(rid),
+            client_ip = tracing::field::Empty,
             method = display(req.method()),
             uri = display(req.uri()),
             version = debug(req.version()),
-        )
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        );
+        // 由 client_ip 中间件按可信代理解析，转发头不可信时为对端地址
+        if let Some(ClientIp(ip)) = req.extensions().get::<ClientIp>() {
+            span.record("client_ip", tracing::field::display(ip));
+        }
+        span
+    }
+
+    /// 5xx 已在 on_response 中记录，这里只记录没有产生响应的错误
+    fn on_failure(failure: ServerErrorsFailureClass, latency: Duration, _span: &Span) {
+        if let ServerErrorsFailureClass::Error(e) = failure {
+            let latency_ms = latency.as_millis() as u64;
+            tracing::warn!(latency_ms, "HTTP: request failed: {}", e);
+        }
+    }
+
+    TraceLayer::new_for_http()
+        .make_span_with(make_span as HttpMakeSpanFn)
+        .on_response(HttpOnResponse { slow_request })
+        .on_failure(on_failure as HttpOnFailureFn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+
+    /// 把日志收集到内存中
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
     }
 
-    TraceLayer::new_for_http().make_span_with(make_span as HttpMakeSpanFn)
+    /// 请求 `uri` 并返回 trace layer 记录的响应事件
+    async fn response_event(uri: &str) -> serde_json::Value {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(Level::TRACE)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route("/boom", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(60)).await;
+                    "slow"
+                }),
+            )
+            .layer(trace_layer_with(Duration::from_millis(50)));
+        let req = Request::builder()
+            .uri(uri)
+            .extension(ClientIp("203.0.113.7".parse().unwrap()))
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(req).await.unwrap();
+
+        let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        logs.lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|event| {
+                event["fields"]["message"]
+                    .as_str()
+                    .is_some_and(|message| message.starts_with("HTTP: "))
+            })
+            .expect("response event should be logged")
+    }
+
+    #[tokio::test]
+    async fn test_response_event_has_status_and_latency() {
+        let event = response_event("/ok").await;
+        assert_eq!(event["level"], "DEBUG");
+        assert_eq!(event["fields"]["message"], "HTTP: response");
+        assert_eq!(event["fields"]["status"], 200);
+        assert!(event["fields"]["latency_ms"].is_u64(), "{}", event);
+        assert_eq!(event["span"]["uri"], "/ok");
+        assert_eq!(event["span"]["client_ip"], "203.0.113.7");
+
+        let event = response_event("/boom").await;
+        assert_eq!(event["level"], "WARN");
+        assert_eq!(event["fields"]["status"], 500);
+        assert!(event["fields"]["latency_ms"].is_u64(), "{}", event);
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_is_warned() {
+        let event = response_event("/slow").await;
+        assert_eq!(event["level"], "WARN");
+        assert_eq!(event["fields"]["message"], "HTTP: slow request");
+        assert_eq!(event["span"]["uri"], "/slow");
+        assert!(
+            event["fields"]["latency_ms"].as_u64().unwrap() >= 50,
+            "{}",
+            event
+        );
+    }
 }
@@ -0,0 +1,103 @@
+use crate::config::AppConfig;
+use crate::middleware::request_id::RequestId;
+use crate::response::{ApiResponse, ErrorCode};
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{info, warn};
+
+/// 维护模式开关，运行时可通过管理接口切换
+///
+/// 作为请求扩展注入路由，测试可以各自持有独立的开关，互不影响
+#[derive(Debug, Clone, Default)]
+pub struct Maintenance(Arc<AtomicBool>);
+
+impl Maintenance {
+    /// 服务实际使用的开关
+    pub fn global() -> Self {
+        static INSTANCE: Lazy<Maintenance> = Lazy::new(Maintenance::default);
+        INSTANCE.clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::SeqCst);
+        info!(enabled, "MAINTENANCE: mode changed");
+    }
+}
+
+/// 维护模式下直接返回 503，在读取请求体之前拦截，不会消耗验证码
+///
+/// 开关从请求扩展中读取，未注入时使用全局开关
+pub async fn reject_in_maintenance(req: Request<Body>, next: Next) -> Response {
+    let enabled = req
+        .extensions()
+        .get::<Maintenance>()
+        .cloned()
+        .unwrap_or_else(Maintenance::global)
+        .is_enabled();
+    if !enabled {
+        return next.run(req).await;
+    }
+
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .copied()
+        .unwrap_or_else(RequestId::new);
+    warn!(uri = %req.uri(), "MAINTENANCE: request rejected");
+    ApiResponse::<()>::error_with_code(
+        StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCode::Maintenance,
+        &AppConfig::global().submission.maintenance_message,
+        request_id,
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use axum::middleware::from_fn;
+    use axum::routing::post;
+    use axum::{Extension, Router};
+    use tower::ServiceExt;
+
+    async fn status_of(maintenance: &Maintenance) -> StatusCode {
+        let app = Router::new()
+            .route("/submit", post(|| async { "accepted" }))
+            .route_layer(from_fn(reject_in_maintenance))
+            .layer(Extension(maintenance.clone()));
+        let req = Request::builder()
+            .method("POST")
+            .uri("/submit")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_toggle() {
+        config::test_global();
+        // 独立的开关，不影响并行运行的其他测试
+        let maintenance = Maintenance::default();
+
+        maintenance.set_enabled(true);
+        assert_eq!(
+            status_of(&maintenance).await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert!(!Maintenance::global().is_enabled());
+
+        maintenance.set_enabled(false);
+        assert_eq!(status_of(&maintenance).await, StatusCode::OK);
+    }
+}
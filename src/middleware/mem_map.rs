@@ -1,11 +1,24 @@
+use crate::utils::file::ShareFile;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
-use once_cell::sync::OnceCell;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::{
-    any::Any,
-    collections::HashMap,
-    sync::{Arc, RwLock},
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    fs,
+    future::Future,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 use tokio::time::interval;
+use tracing::{info, warn};
 
 pub trait ToKey {
     fn to_key(&self) -> String;
@@ -37,29 +50,218 @@ macro_rules! to_key {
 type BoxedValue = Box<dyn Any + Send + Sync>;
 type CacheEntry = (BoxedValue, DateTime<Utc>);
 type CacheMap = HashMap<String, CacheEntry>;
-type Cache = Arc<RwLock<CacheMap>>;
+type Cache = Arc<RwLock<Store>>;
+/// 每个 key 正在进行的初始化对应一把异步锁
+type InflightMap = Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>;
+
+/// 缓存数据及其访问顺序
+#[derive(Default)]
+struct Store {
+    entries: CacheMap,
+    /// 访问序号 -> key，序号越小越久未被访问；受保护的条目不在其中，不会被淘汰
+    order: BTreeMap<u64, String>,
+    /// key -> 最近一次写入或读取的序号
+    seqs: HashMap<String, u64>,
+    next_seq: u64,
+}
+
+impl Store {
+    /// 写入条目，`evictable` 为 false 时超出容量也不淘汰，只随过期清理
+    fn put(&mut self, key: String, entry: CacheEntry, evictable: bool) {
+        self.unlink(&key);
+        if evictable {
+            self.link(key.clone());
+        }
+        self.entries.insert(key, entry);
+    }
+
+    /// 把可淘汰的条目标记为最近访问
+    fn touch(&mut self, key: &str) {
+        if self.unlink(key) {
+            self.link(key.to_string());
+        }
+    }
+
+    fn link(&mut self, key: String) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.order.insert(seq, key.clone());
+        self.seqs.insert(key, seq);
+    }
+
+    /// 从访问顺序中移除，返回该条目是否可淘汰
+    fn unlink(&mut self, key: &str) -> bool {
+        match self.seqs.remove(key) {
+            Some(seq) => {
+                self.order.remove(&seq);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_protected(&self, key: &str) -> bool {
+        !self.seqs.contains_key(key)
+    }
+
+    fn take(&mut self, key: &str) -> Option<CacheEntry> {
+        self.unlink(key);
+        self.entries.remove(key)
+    }
+
+    /// 清理过期条目，返回清理数
+    fn retain_unexpired(&mut self, now: DateTime<Utc>) -> usize {
+        let expired: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, (_, exp))| *exp <= now)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in &expired {
+            self.take(key);
+        }
+        expired.len()
+    }
+
+    /// 可淘汰的条目超出容量时先清理过期条目，仍超出再淘汰最久未被访问的，返回淘汰数
+    ///
+    /// 受保护的条目不计入容量
+    fn enforce_capacity(&mut self, capacity: usize) -> usize {
+        if capacity == 0 || self.order.len() <= capacity {
+            return 0;
+        }
+        let mut evicted = self.retain_unexpired(Utc::now());
+        while self.order.len() > capacity {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            self.seqs.remove(&key);
+            self.entries.remove(&key);
+            evicted += 1;
+        }
+        evicted
+    }
+}
+
+/// 可写入快照的值类型：按 TypeId 序列化，按 tag 反序列化
+struct Codec {
+    tag: &'static str,
+    type_id: TypeId,
+    encode: fn(&BoxedValue) -> Option<serde_json::Value>,
+    decode: fn(serde_json::Value) -> serde_json::Result<BoxedValue>,
+}
+
+fn codec<T>(tag: &'static str) -> Codec
+where
+    T: Any + Send + Sync + Serialize + DeserializeOwned,
+{
+    Codec {
+        tag,
+        type_id: TypeId::of::<T>(),
+        encode: |v| {
+            v.downcast_ref::<T>()
+                .and_then(|v| serde_json::to_value(v).ok())
+        },
+        decode: |v| Ok(Box::new(serde_json::from_value::<T>(v)?)),
+    }
+}
+
+/// 参与持久化的值类型，未列出的类型（如投稿状态）只保存在内存中
+static CODECS: Lazy<Vec<Codec>> = Lazy::new(|| {
+    vec![
+        codec::<()>("unit"),
+        codec::<u32>("u32"),
+        codec::<String>("string"),
+        codec::<Vec<String>>("string_list"),
+        codec::<ShareFile>("share_file"),
+    ]
+});
+
+/// 快照文件中的一条记录
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    key: String,
+    tag: String,
+    expires_at: DateTime<Utc>,
+    value: serde_json::Value,
+    /// 由 [`MemMap::insert_protected`] 写入，恢复后同样不会被淘汰
+    #[serde(default)]
+    protected: bool,
+}
+
+/// 由 `enable_snapshot` 设置，`global()` 初始化时据此恢复并定期写入快照
+static SNAPSHOT: OnceCell<(PathBuf, std::time::Duration)> = OnceCell::new();
+
+/// 开启缓存快照，需在首次调用 [`MemMap::global`] 之前设置
+pub fn enable_snapshot(path: PathBuf, every: std::time::Duration) {
+    if SNAPSHOT.set((path, every)).is_err() {
+        warn!("MEM_MAP: snapshot already enabled, ignored");
+    }
+}
+
+/// 缓存运行计数
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// 缓存统计快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// 当前未过期的条目数
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub inserts: u64,
+    /// 因过期或超出容量被清理的条目数
+    pub evictions: u64,
+}
+
+/// 由 `set_capacity` 设置，`global()` 初始化时使用
+static CAPACITY: OnceCell<usize> = OnceCell::new();
+
+/// 设置全局缓存的最大条目数，需在首次调用 [`MemMap::global`] 之前设置
+pub fn set_capacity(capacity: usize) {
+    if CAPACITY.set(capacity).is_err() {
+        warn!("MEM_MAP: capacity already set, ignored");
+    }
+}
 
 #[derive(Clone)]
 pub struct MemMap {
     store: Cache,
+    inflight: InflightMap,
+    counters: Arc<Counters>,
+    /// 最大条目数，0 表示不限制
+    capacity: usize,
 }
 
 impl MemMap {
+    #[allow(dead_code)]
     fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// 创建最多保存 `capacity` 个条目的缓存，超出时淘汰最久未被访问的条目；0 表示不限制
+    fn with_capacity(capacity: usize) -> Self {
         let map = MemMap {
-            store: Arc::new(RwLock::new(HashMap::new())),
+            store: Arc::default(),
+            inflight: Arc::new(Mutex::new(HashMap::new())),
+            counters: Arc::default(),
+            capacity,
         };
 
         // 定期清理过期数据
         {
-            let store_clone = map.store.clone();
+            let map = map.clone();
             tokio::spawn(async move {
                 let mut ticker = interval(std::time::Duration::from_secs(60));
                 loop {
                     ticker.tick().await;
-                    let mut map = store_clone.write().unwrap();
-                    let now = Utc::now();
-                    map.retain(|_, (_, exp)| *exp > now);
+                    map.clean_expired();
                 }
             });
         }
@@ -69,41 +271,303 @@ impl MemMap {
 
     /// 写入数据，使用 chrono::Duration 作为 TTL
     pub fn insert<K: ToKey, T: Any + Send + Sync>(&self, key: K, value: T, ttl: Duration) {
+        self.put(key, value, ttl, true);
+    }
+
+    /// 写入不会因超出容量被淘汰的数据，只在过期后清理
+    ///
+    /// 用于验证码错误次数、发送冷却等安全计数：被其他条目挤出缓存就等于重置了限制
+    pub fn insert_protected<K: ToKey, T: Any + Send + Sync>(
+        &self,
+        key: K,
+        value: T,
+        ttl: Duration,
+    ) {
+        self.put(key, value, ttl, false);
+    }
+
+    /// 不存在或已过期时写入不会被淘汰的数据，检查与写入在同一把写锁内完成
+    ///
+    /// 并发调用只有一个会写入成功；已存在时返回其剩余存活时间
+    pub fn insert_protected_if_absent<K: ToKey, T: Any + Send + Sync>(
+        &self,
+        key: K,
+        value: T,
+        ttl: Duration,
+    ) -> Result<(), Duration> {
+        let key = key.to_key();
+        let now = Utc::now();
+        let mut store = self.store.write().unwrap();
+        if let Some((_, exp)) = store.entries.get(&key)
+            && *exp > now
+        {
+            return Err(*exp - now);
+        }
+        self.put_locked(&mut store, key, (Box::new(value), now + ttl), false);
+        Ok(())
+    }
+
+    fn put<K: ToKey, T: Any + Send + Sync>(
+        &self,
+        key: K,
+        value: T,
+        ttl: Duration,
+        evictable: bool,
+    ) {
         let expire_time = Utc::now() + ttl;
-        let mut map = self.store.write().unwrap();
-        map.insert(key.to_key(), (Box::new(value), expire_time));
+        let mut store = self.store.write().unwrap();
+        self.put_locked(
+            &mut store,
+            key.to_key(),
+            (Box::new(value), expire_time),
+            evictable,
+        );
+    }
+
+    /// 在已持有的写锁内写入条目并按容量淘汰
+    fn put_locked(&self, store: &mut Store, key: String, entry: CacheEntry, evictable: bool) {
+        store.put(key, entry, evictable);
+        let evicted = store.enforce_capacity(self.capacity);
+        self.counters.inserts.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .evictions
+            .fetch_add(evicted as u64, Ordering::Relaxed);
     }
 
-    /// 读取数据
+    /// 读取数据，命中时标记为最近访问
     pub fn get<K: ToKey, T: Any + Clone>(&self, key: &K) -> Option<T> {
-        let map = self.store.read().unwrap();
-        map.get(&key.to_key()).and_then(|(v, exp)| {
+        let key = key.to_key();
+        let mut store = self.store.write().unwrap();
+        let value = store.entries.get(&key).and_then(|(v, exp)| {
             if *exp > Utc::now() {
                 v.downcast_ref::<T>().cloned()
             } else {
                 None
             }
+        });
+        if value.is_some() {
+            store.touch(&key);
+        }
+
+        let counter = if value.is_some() {
+            &self.counters.hits
+        } else {
+            &self.counters.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// 当前条目数与累计的命中、未命中、写入、过期清理次数
+    pub fn stats(&self) -> CacheStats {
+        let now = Utc::now();
+        let entries = self
+            .store
+            .read()
+            .unwrap()
+            .entries
+            .values()
+            .filter(|(_, exp)| *exp > now)
+            .count();
+        CacheStats {
+            entries,
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            inserts: self.counters.inserts.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 读取数据，未命中时执行 `f` 计算并写入
+    ///
+    /// 同一 key 的并发调用只有一个会执行 `f`，其余等待其完成后直接读取结果
+    pub async fn get_or_insert_with<K, T, F, Fut>(&self, key: K, ttl: Duration, f: F) -> T
+    where
+        K: ToKey,
+        T: Any + Clone + Send + Sync,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let result = self
+            .try_get_or_insert_with(key, ttl, || async { Ok::<_, Infallible>(f().await) })
+            .await;
+        match result {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// 与 [`MemMap::get_or_insert_with`] 相同，但 `f` 可能失败；失败时不写缓存，
+    /// 等待中的调用会重新尝试计算
+    pub async fn try_get_or_insert_with<K, T, E, F, Fut>(
+        &self,
+        key: K,
+        ttl: Duration,
+        f: F,
+    ) -> Result<T, E>
+    where
+        K: ToKey,
+        T: Any + Clone + Send + Sync,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(v) = self.get::<K, T>(&key) {
+            return Ok(v);
+        }
+
+        let key_str = key.to_key();
+        let lock = self
+            .inflight
+            .lock()
+            .unwrap()
+            .entry(key_str.clone())
+            .or_default()
+            .clone();
+
+        let result = {
+            let _guard = lock.lock().await;
+            // 拿到锁后再查一次，前一个持有者可能已写入
+            match self.get::<K, T>(&key) {
+                Some(v) => Ok(v),
+                None => f().await.inspect(|v| self.insert(key, v.clone(), ttl)),
+            }
+        };
+
+        // 没有其他调用在等待时清理这把锁
+        let mut inflight = self.inflight.lock().unwrap();
+        if Arc::strong_count(&lock) == 2 {
+            inflight.remove(&key_str);
+        }
+
+        result
+    }
+
+    /// 查询剩余存活时间，不存在或已过期时返回 None
+    pub fn ttl_remaining<K: ToKey>(&self, key: &K) -> Option<Duration> {
+        let store = self.store.read().unwrap();
+        store.entries.get(&key.to_key()).and_then(|(_, exp)| {
+            let remaining = *exp - Utc::now();
+            (remaining > Duration::zero()).then_some(remaining)
         })
     }
 
-    /// 手动清理过期数据
-    #[allow(dead_code)]
+    /// 清理过期数据
     pub fn clean_expired(&self) {
-        let mut map = self.store.write().unwrap();
-        let now = Utc::now();
-        map.retain(|_, (_, exp)| *exp > now);
+        let evicted = self.store.write().unwrap().retain_unexpired(Utc::now());
+        self.counters
+            .evictions
+            .fetch_add(evicted as u64, Ordering::Relaxed);
     }
 
     /// 删除指定 key
     pub fn remove<K: ToKey>(&self, key: &K) -> bool {
-        let mut map = self.store.write().unwrap();
-        map.remove(&key.to_key()).is_some()
+        let mut store = self.store.write().unwrap();
+        store.take(&key.to_key()).is_some()
+    }
+
+    /// 把未过期且类型可持久化的数据写入快照文件（先写临时文件再替换）
+    pub fn dump_to(&self, path: &Path) -> Result<usize> {
+        let entries: Vec<SnapshotEntry> = {
+            let store = self.store.read().unwrap();
+            let now = Utc::now();
+            store
+                .entries
+                .iter()
+                .filter(|(_, (_, exp))| *exp > now)
+                .filter_map(|(key, (value, exp))| {
+                    let type_id = (**value).type_id();
+                    let codec = CODECS.iter().find(|c| c.type_id == type_id)?;
+                    Some(SnapshotEntry {
+                        key: key.clone(),
+                        tag: codec.tag.to_string(),
+                        expires_at: *exp,
+                        value: (codec.encode)(value)?,
+                        protected: store.is_protected(key),
+                    })
+                })
+                .collect()
+        };
+
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("创建快照目录失败: {}", dir.display()))?;
+        }
+        let tmp = path.with_extension("tmp");
+        {
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            // 快照中含有验证码，只允许本用户读写
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options
+                .open(&tmp)
+                .with_context(|| format!("创建快照文件失败: {}", tmp.display()))?;
+            serde_json::to_writer(&mut file, &entries).context("序列化快照失败")?;
+            file.flush().context("写入快照失败")?;
+        }
+        fs::rename(&tmp, path).with_context(|| format!("替换快照文件失败: {}", path.display()))?;
+
+        Ok(entries.len())
+    }
+
+    /// 从快照文件恢复数据，跳过已过期或类型未知的记录，返回恢复的条数
+    pub fn load_from(&self, path: &Path) -> Result<usize> {
+        let content =
+            fs::read(path).with_context(|| format!("读取快照失败: {}", path.display()))?;
+        let entries: Vec<SnapshotEntry> =
+            serde_json::from_slice(&content).context("解析快照失败")?;
+
+        let now = Utc::now();
+        let mut store = self.store.write().unwrap();
+        let mut restored = 0;
+        for entry in entries.into_iter().filter(|e| e.expires_at > now) {
+            let Some(codec) = CODECS.iter().find(|c| c.tag == entry.tag) else {
+                warn!("MEM_MAP: unknown snapshot type {}, skipped", entry.tag);
+                continue;
+            };
+            match (codec.decode)(entry.value) {
+                Ok(value) => {
+                    store.put(entry.key, (value, entry.expires_at), !entry.protected);
+                    restored += 1;
+                }
+                Err(e) => warn!("MEM_MAP: decode snapshot entry {} failed: {}", entry.key, e),
+            }
+        }
+        store.enforce_capacity(self.capacity);
+        Ok(restored)
+    }
+
+    /// 启动时恢复快照，并定期写入
+    fn start_snapshot(&self, path: PathBuf, every: std::time::Duration) {
+        if path.exists() {
+            match self.load_from(&path) {
+                Ok(n) => info!("MEM_MAP: restored {} entries from {}", n, path.display()),
+                Err(e) => warn!("MEM_MAP: restore snapshot failed: {:#}", e),
+            }
+        }
+
+        let map = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = interval(every);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = map.dump_to(&path) {
+                    warn!("MEM_MAP: write snapshot failed: {:#}", e);
+                }
+            }
+        });
     }
 
     /// 获取全局单例缓存
     pub fn global() -> &'static MemMap {
         static INSTANCE: OnceCell<MemMap> = OnceCell::new();
-        INSTANCE.get_or_init(MemMap::new)
+        INSTANCE.get_or_init(|| {
+            let map = MemMap::with_capacity(CAPACITY.get().copied().unwrap_or(0));
+            if let Some((path, every)) = SNAPSHOT.get() {
+                map.start_snapshot(path.clone(), *every);
+            }
+            map
+        })
     }
 }
 
@@ -175,6 +639,267 @@ mod tests {
         assert!(cache.get::<String, String>(&"key".to_string()).is_none());
     }
 
+    #[tokio::test]
+    async fn test_get_or_insert_with_runs_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = MemMap::global();
+        let key = "get_or_insert_once".to_string();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..16)
+            .map(|_| {
+                let key = key.clone();
+                let calls = calls.clone();
+                tokio::spawn(async move {
+                    cache
+                        .get_or_insert_with(key, Duration::seconds(60), || async move {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            // 让其他任务有机会在计算期间到达
+                            sleep(std::time::Duration::from_millis(50)).await;
+                            "computed".to_string()
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), "computed");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(cache.inflight.lock().unwrap().get(&key).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_try_get_or_insert_with_error_not_cached() {
+        let cache = MemMap::global();
+        let key = "try_get_or_insert".to_string();
+
+        let result = cache
+            .try_get_or_insert_with(key.clone(), Duration::seconds(60), || async {
+                Err::<u32, _>("failed")
+            })
+            .await;
+        assert_eq!(result, Err("failed"));
+        assert!(cache.get::<String, u32>(&key).is_none());
+
+        let result = cache
+            .try_get_or_insert_with(key.clone(), Duration::seconds(60), || async {
+                Ok::<_, &str>(7u32)
+            })
+            .await;
+        assert_eq!(result, Ok(7));
+        assert_eq!(cache.get::<String, u32>(&key), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_stats_counters() {
+        let cache = MemMap::new();
+        let key = "stats".to_string();
+
+        assert!(cache.get::<String, u32>(&key).is_none());
+        cache.insert(key.clone(), 1u32, Duration::seconds(60));
+        cache.insert("short".to_string(), 2u32, Duration::milliseconds(100));
+        assert_eq!(cache.get::<String, u32>(&key), Some(1));
+        assert_eq!(cache.get::<String, u32>(&key), Some(1));
+        // 类型不符同样计为未命中
+        assert!(cache.get::<String, String>(&key).is_none());
+
+        sleep(std::time::Duration::from_millis(150)).await;
+        assert!(cache.get::<String, u32>(&"short".to_string()).is_none());
+        cache.clean_expired();
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                entries: 1,
+                hits: 2,
+                misses: 3,
+                inserts: 2,
+                evictions: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capacity_evicts_least_recently_used() {
+        let cache = MemMap::with_capacity(3);
+        for i in 0..5u32 {
+            cache.insert(format!("cap-{}", i), i, Duration::seconds(60));
+        }
+        // 重新写入或读取已有 key 都会刷新其访问顺序
+        cache.insert("cap-2".to_string(), 20u32, Duration::seconds(60));
+        assert_eq!(cache.get::<String, u32>(&"cap-3".to_string()), Some(3));
+        cache.insert("cap-5".to_string(), 5u32, Duration::seconds(60));
+
+        for gone in ["cap-0", "cap-1", "cap-4"] {
+            assert!(
+                cache.get::<String, u32>(&gone.to_string()).is_none(),
+                "{}",
+                gone
+            );
+        }
+        assert_eq!(cache.get::<String, u32>(&"cap-3".to_string()), Some(3));
+        assert_eq!(cache.get::<String, u32>(&"cap-2".to_string()), Some(20));
+        assert_eq!(cache.get::<String, u32>(&"cap-5".to_string()), Some(5));
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 3);
+        assert_eq!(stats.evictions, 3);
+    }
+
+    #[tokio::test]
+    async fn test_protected_entries_are_not_evicted() {
+        let cache = MemMap::with_capacity(2);
+        cache.insert_protected("attempts".to_string(), 4u32, Duration::seconds(60));
+        for i in 0..5u32 {
+            cache.insert(format!("filler-{}", i), i, Duration::seconds(60));
+        }
+
+        // 大量写入其他条目不会挤掉安全计数，受保护的条目也不占用容量
+        assert_eq!(cache.get::<String, u32>(&"attempts".to_string()), Some(4));
+        assert_eq!(cache.get::<String, u32>(&"filler-3".to_string()), Some(3));
+        assert_eq!(cache.get::<String, u32>(&"filler-4".to_string()), Some(4));
+        assert_eq!(cache.stats().entries, 3);
+
+        // 保护状态随快照保留
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        cache.dump_to(&path).unwrap();
+        let restored = MemMap::with_capacity(1);
+        restored.load_from(&path).unwrap();
+        assert_eq!(
+            restored.get::<String, u32>(&"attempts".to_string()),
+            Some(4)
+        );
+        assert_eq!(restored.stats().entries, 2);
+
+        // 仍会随过期被清理
+        cache.insert_protected("short".to_string(), 1u32, Duration::milliseconds(50));
+        sleep(std::time::Duration::from_millis(80)).await;
+        assert!(cache.get::<String, u32>(&"short".to_string()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_insert_protected_if_absent_is_atomic() {
+        let cache = MemMap::with_capacity(0);
+        let key = "cooldown".to_string();
+
+        // 并发写入同一个 key，只有一个成功
+        let inserted: usize = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..16)
+                .map(|_| {
+                    s.spawn(|| {
+                        cache
+                            .insert_protected_if_absent(key.clone(), (), Duration::seconds(60))
+                            .is_ok()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap() as usize)
+                .sum()
+        });
+        assert_eq!(inserted, 1);
+
+        let remaining = cache
+            .insert_protected_if_absent(key.clone(), (), Duration::seconds(60))
+            .unwrap_err();
+        assert!(remaining > Duration::seconds(50), "{}", remaining);
+
+        // 过期后可以再次写入
+        cache.insert_protected("expiring".to_string(), (), Duration::milliseconds(50));
+        sleep(std::time::Duration::from_millis(80)).await;
+        assert!(
+            cache
+                .insert_protected_if_absent("expiring".to_string(), (), Duration::seconds(60))
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache").join("snapshot.json");
+
+        let cache = MemMap::new();
+        cache.insert(
+            "code".to_string(),
+            "abc123".to_string(),
+            Duration::seconds(60),
+        );
+        cache.insert("attempts".to_string(), 3u32, Duration::seconds(60));
+        cache.insert("cooldown".to_string(), (), Duration::seconds(60));
+        cache.insert(
+            "list".to_string(),
+            vec!["a.pdf".to_string()],
+            Duration::seconds(60),
+        );
+        cache.insert(
+            "short".to_string(),
+            "bye".to_string(),
+            Duration::milliseconds(500),
+        );
+        // 未登记的类型不写入快照
+        cache.insert("unknown".to_string(), 1.5f64, Duration::seconds(60));
+
+        assert_eq!(cache.dump_to(&path).unwrap(), 5);
+        sleep(std::time::Duration::from_millis(600)).await;
+
+        let restored = MemMap::new();
+        assert_eq!(restored.load_from(&path).unwrap(), 4);
+        assert_eq!(
+            restored.get::<String, String>(&"code".to_string()),
+            Some("abc123".to_string())
+        );
+        assert_eq!(
+            restored.get::<String, u32>(&"attempts".to_string()),
+            Some(3)
+        );
+        assert!(
+            restored
+                .get::<String, ()>(&"cooldown".to_string())
+                .is_some()
+        );
+        assert_eq!(
+            restored.get::<String, Vec<String>>(&"list".to_string()),
+            Some(vec!["a.pdf".to_string()])
+        );
+        // 过期时间随快照保留
+        let remaining = restored.ttl_remaining(&"code".to_string()).unwrap();
+        assert!(remaining <= Duration::seconds(60));
+        assert!(remaining > Duration::seconds(55));
+        // 已过期与未登记类型不恢复
+        assert!(
+            restored
+                .get::<String, String>(&"short".to_string())
+                .is_none()
+        );
+        assert!(
+            restored
+                .get::<String, f64>(&"unknown".to_string())
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mem_map_ttl_remaining() {
+        let cache = MemMap::global();
+        let key = "ttl_remaining".to_string();
+
+        assert!(cache.ttl_remaining(&key).is_none());
+
+        cache.insert(key.clone(), 1u8, Duration::seconds(60));
+        let remaining = cache.ttl_remaining(&key).expect("应有剩余时间");
+        assert!(remaining <= Duration::seconds(60));
+        assert!(remaining > Duration::seconds(58));
+
+        cache.remove(&key);
+        assert!(cache.ttl_remaining(&key).is_none());
+    }
+
     #[tokio::test]
     async fn test_mem_map_struct_with_datetime_and_string() {
         use chrono::{DateTime, Utc};
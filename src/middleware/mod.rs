@@ -1,6 +1,12 @@
+pub mod admin_auth;
+pub mod client_ip;
 pub mod cors;
 pub mod http_tracing;
+pub mod maintenance;
 pub mod mem_map;
 pub mod request_id;
+pub mod security_headers;
+pub mod streaming_json;
+pub mod submission_queue;
 pub mod upload_limit;
 pub mod background;
@@ -1,11 +1,21 @@
-use axum::{body::Body, http::Request};
+use crate::config::AppConfig;
+use crate::middleware::client_ip::is_trusted_peer;
+use axum::extract::ConnectInfo;
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request},
+};
 use std::convert::Infallible;
 use std::fmt;
+use std::net::{IpAddr, SocketAddr};
 use std::task::{Context, Poll};
 use tower_layer::Layer;
 use tower_service::Service;
 use uuid::Uuid;
 
+/// 上游网关透传的关联 ID 头
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
 #[derive(Clone, Debug, Copy)]
 pub struct RequestId(pub Uuid);
 
@@ -27,6 +37,25 @@ impl From<Uuid> for RequestId {
     }
 }
 
+/// 解析上游传入的请求 ID
+///
+/// 投稿编号沿用请求 ID，所以只采信可信代理转发的头，
+/// 直连客户端或无法确定对端时一律忽略；头不是合法 UUID 时同样忽略
+pub fn inbound_request_id(
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+    trusted: &[IpAddr],
+) -> Option<Uuid> {
+    if !peer.is_some_and(|peer| is_trusted_peer(peer, trusted)) {
+        return None;
+    }
+    headers
+        .get(REQUEST_ID_HEADER)?
+        .to_str()
+        .ok()
+        .and_then(|value| Uuid::parse_str(value.trim()).ok())
+}
+
 #[derive(Clone, Debug)]
 pub struct RequestIdLayer;
 
@@ -58,8 +87,14 @@ where
     }
 
     fn call(&mut self, mut req: Request<Body>) -> Self::Future {
-        // 这里生成一个 RequestId 并塞进 extensions
-        let rid = RequestId::new();
+        // 优先沿用可信上游的 X-Request-Id，否则生成新的，再塞进 extensions
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let rid = inbound_request_id(req.headers(), peer, &AppConfig::global().trusted_proxies)
+            .map(RequestId)
+            .unwrap_or_else(RequestId::new);
         req.extensions_mut().insert(rid);
 
         self.inner.call(req)
@@ -69,3 +104,70 @@ where
 pub fn request_id_layer() -> RequestIdLayer {
     RequestIdLayer
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use tower::{ServiceExt, service_fn};
+
+    const VALID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    /// 经过中间件后返回 handler 看到的 RequestId
+    async fn seen_request_id(header: Option<&str>, peer: &str) -> Uuid {
+        crate::config::test_global();
+        let service = RequestIdLayer.layer(service_fn(|req: Request<Body>| async move {
+            Ok::<_, Infallible>(req.extensions().get::<RequestId>().copied().unwrap().0)
+        }));
+
+        let mut req = Request::builder().uri("/").body(Body::empty()).unwrap();
+        if let Some(value) = header {
+            req.headers_mut()
+                .insert(REQUEST_ID_HEADER, HeaderValue::from_str(value).unwrap());
+        }
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 40000)));
+
+        service.oneshot(req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_header_is_reused() {
+        // config.toml 把 127.0.0.1 列为可信代理
+        let id = seen_request_id(Some(VALID), "127.0.0.1").await;
+        assert_eq!(id, Uuid::parse_str(VALID).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_header_is_regenerated() {
+        let id = seen_request_id(Some("not-a-uuid"), "127.0.0.1").await;
+        assert_ne!(id, Uuid::nil());
+        assert_ne!(id.to_string(), "not-a-uuid");
+    }
+
+    #[tokio::test]
+    async fn test_missing_header_is_generated() {
+        let first = seen_request_id(None, "127.0.0.1").await;
+        let second = seen_request_id(None, "127.0.0.1").await;
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_peer_header_is_ignored() {
+        let id = seen_request_id(Some(VALID), "198.51.100.9").await;
+        assert_ne!(id, Uuid::parse_str(VALID).unwrap());
+    }
+
+    #[test]
+    fn test_inbound_request_id_requires_known_peer() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static(VALID));
+        let trusted: Vec<IpAddr> = vec!["10.0.0.2".parse().unwrap()];
+
+        assert_eq!(
+            inbound_request_id(&headers, Some("10.0.0.2".parse().unwrap()), &trusted),
+            Some(Uuid::parse_str(VALID).unwrap())
+        );
+        assert_eq!(inbound_request_id(&headers, None, &trusted), None);
+    }
+}
@@ -0,0 +1,33 @@
+use crate::config::AppConfig;
+use axum::body::Body;
+use axum::http::{HeaderValue, Request, header};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::warn;
+
+/// 为所有响应补充基础安全头，处理函数已设置的同名头保持不变
+///
+/// 只写入下面几个头，不会改动 CORS 相关的 `Access-Control-*` 头
+pub async fn security_headers(req: Request<Body>, next: Next) -> Response {
+    let mut resp = next.run(req).await;
+    let headers = resp.headers_mut();
+
+    headers
+        .entry(header::X_CONTENT_TYPE_OPTIONS)
+        .or_insert(HeaderValue::from_static("nosniff"));
+    headers
+        .entry(header::X_FRAME_OPTIONS)
+        .or_insert(HeaderValue::from_static("DENY"));
+
+    let policy = &AppConfig::global().content_security_policy;
+    if !policy.is_empty() && !headers.contains_key(header::CONTENT_SECURITY_POLICY) {
+        match HeaderValue::from_str(policy) {
+            Ok(value) => {
+                headers.insert(header::CONTENT_SECURITY_POLICY, value);
+            }
+            Err(e) => warn!("SECURITY_HEADERS: invalid content security policy: {}", e),
+        }
+    }
+
+    resp
+}
@@ -0,0 +1,319 @@
+use crate::middleware::request_id::RequestId;
+use crate::response::{ApiResponse, ErrorCode};
+use axum::RequestExt;
+use axum::extract::{FromRequest, Request};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use futures_util::TryStreamExt;
+use http_body_util::LengthLimitError;
+use std::fmt;
+use std::io::{self, Read};
+use tokio_util::io::{StreamReader, SyncIoBridge};
+use tracing::{debug, warn};
+
+/// 流式 JSON 提取器
+///
+/// 与 `Json<T>` 的请求格式完全一致，但边读取请求体边反序列化，
+/// 不会先把整个请求体缓存在内存中
+pub struct StreamingJson<T>(pub T);
+
+/// 可以从同步 reader 中边读边解析的请求体
+pub trait FromJsonReader: Sized + Send + 'static {
+    fn from_json_reader(reader: Box<dyn Read + Send>) -> anyhow::Result<Self>;
+}
+
+/// 单个字段超出解析阶段的大小限制
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldTooLarge {
+    pub field: String,
+    pub limit: usize,
+}
+
+impl FieldTooLarge {
+    /// 字段大小超过上限时返回错误
+    pub fn check(field: impl Into<String>, size: usize, limit: usize) -> Result<(), Self> {
+        if size > limit {
+            Err(Self {
+                field: field.into(),
+                limit,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for FieldTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "字段 {} 过大，上限 {} 字节", self.field, self.limit)
+    }
+}
+
+impl std::error::Error for FieldTooLarge {}
+
+fn is_json_content_type(req: &Request) -> bool {
+    let Some(content_type) = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+
+    let mime = content_type.split(';').next().unwrap_or("").trim();
+    mime.eq_ignore_ascii_case("application/json") || mime.ends_with("+json")
+}
+
+/// 判断反序列化失败是否由请求体超限引起
+///
+/// 超限错误可能被 `axum::Error` 包装，需要沿错误链查找
+fn exceeds_limit(e: &serde_json::Error) -> bool {
+    let mut source = std::error::Error::source(e)
+        .and_then(|s| s.downcast_ref::<io::Error>())
+        .and_then(|io| io.get_ref())
+        .map(|inner| inner as &(dyn std::error::Error + 'static));
+    while let Some(err) = source {
+        if err.is::<LengthLimitError>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// 把解析错误转换为对应的响应：超限为 413，其余为 422
+fn rejection(e: &anyhow::Error, request_id: RequestId) -> Response {
+    if let Some(field) = e.downcast_ref::<FieldTooLarge>() {
+        warn!("STREAMING_JSON: {}", field);
+        return ApiResponse::<()>::error_with_code(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::PayloadTooLarge,
+            &field.to_string(),
+            request_id,
+        )
+        .into_response();
+    }
+    if e.downcast_ref::<serde_json::Error>()
+        .is_some_and(exceeds_limit)
+    {
+        warn!("STREAMING_JSON: body exceeds route limit");
+        return ApiResponse::<()>::error_with_code(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::PayloadTooLarge,
+            "请求体过大",
+            request_id,
+        )
+        .into_response();
+    }
+    warn!("STREAMING_JSON: parse failed: {:#}", e);
+    ApiResponse::<()>::error_with_code(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorCode::InvalidBody,
+        &format!("请求体解析失败: {:#}", e),
+        request_id,
+    )
+    .into_response()
+}
+
+impl<S, T> FromRequest<S> for StreamingJson<T>
+where
+    S: Send + Sync,
+    T: FromJsonReader,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .copied()
+            .unwrap_or_else(RequestId::new);
+
+        if !is_json_content_type(&req) {
+            return Err(ApiResponse::<()>::error_with_code(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                ErrorCode::UnsupportedMediaType,
+                "请求需使用 Content-Type: application/json",
+                request_id,
+            )
+            .into_response());
+        }
+
+        // 大小上限沿用路由上的 DefaultBodyLimit
+        let stream = req
+            .with_limited_body()
+            .into_body()
+            .into_data_stream()
+            .map_err(io::Error::other);
+        let reader = SyncIoBridge::new(StreamReader::new(stream));
+
+        debug!("STREAMING_JSON: start parsing request body");
+        let parsed =
+            tokio::task::spawn_blocking(move || T::from_json_reader(Box::new(reader))).await;
+
+        match parsed {
+            Ok(Ok(value)) => Ok(StreamingJson(value)),
+            Ok(Err(e)) => Err(rejection(&e, request_id)),
+            Err(e) => {
+                warn!("STREAMING_JSON: parse task failed: {}", e);
+                Err(ApiResponse::<()>::error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "请求体解析失败",
+                    request_id,
+                )
+                .into_response())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::UploadConfig;
+    use crate::handler::submit::SubmissionRequest;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicIsize, Ordering};
+
+    /// 只统计开启了跟踪的线程上的内存分配
+    struct CountingAlloc;
+
+    thread_local! {
+        static TRACKING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    static CURRENT: AtomicIsize = AtomicIsize::new(0);
+    static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+    fn tracking() -> bool {
+        TRACKING.try_with(|t| t.get()).unwrap_or(false)
+    }
+
+    fn record(delta: isize) {
+        let now = CURRENT.fetch_add(delta, Ordering::SeqCst) + delta;
+        PEAK.fetch_max(now, Ordering::SeqCst);
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            if tracking() {
+                record(layout.size() as isize);
+            }
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            if tracking() {
+                record(-(layout.size() as isize));
+            }
+            unsafe { System.dealloc(ptr, layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            if tracking() {
+                record(new_size as isize - layout.size() as isize);
+            }
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    /// 按需生成超大 JSON 请求体的 reader，自身不持有整个请求体
+    struct LargeSubmission {
+        parts: Vec<(&'static [u8], usize)>,
+        index: usize,
+        offset: usize,
+    }
+
+    impl LargeSubmission {
+        fn new(image_count: usize, image_len: usize) -> Self {
+            let mut parts: Vec<(&'static [u8], usize)> = vec![(
+                br#"{"author":"a","content":"c","email":"e@example.com","email_code":"x","tags":[],"title":"t","cover":{"name":"cover.png","base64":""#,
+                1,
+            )];
+            parts.push((b"A", image_len));
+            parts.push((br#""},"images":["#, 1));
+            for i in 0..image_count {
+                if i > 0 {
+                    parts.push((b",", 1));
+                }
+                parts.push((br#"{"name":"image.png","base64":""#, 1));
+                parts.push((b"A", image_len));
+                parts.push((br#""}"#, 1));
+            }
+            parts.push((b"]}", 1));
+            Self {
+                parts,
+                index: 0,
+                offset: 0,
+            }
+        }
+
+        fn total_len(&self) -> usize {
+            self.parts.iter().map(|(s, n)| s.len() * n).sum()
+        }
+    }
+
+    impl Read for LargeSubmission {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut written = 0;
+            while written < buf.len() && self.index < self.parts.len() {
+                let (seg, repeat) = self.parts[self.index];
+                let total = seg.len() * repeat;
+                let n = (total - self.offset).min(buf.len() - written);
+                for (i, b) in buf[written..written + n].iter_mut().enumerate() {
+                    *b = seg[(self.offset + i) % seg.len()];
+                }
+                written += n;
+                self.offset += n;
+                if self.offset == total {
+                    self.index += 1;
+                    self.offset = 0;
+                }
+            }
+            Ok(written)
+        }
+    }
+
+    #[test]
+    fn test_streaming_parse_peak_memory_is_bounded() {
+        const IMAGE_LEN: usize = 1024 * 1024;
+        const IMAGE_COUNT: usize = 32;
+
+        let reader = LargeSubmission::new(IMAGE_COUNT, IMAGE_LEN);
+        let payload_len = reader.total_len();
+
+        TRACKING.with(|t| t.set(true));
+        CURRENT.store(0, Ordering::SeqCst);
+        PEAK.store(0, Ordering::SeqCst);
+
+        let limits = UploadConfig {
+            content_max_bytes: usize::MAX,
+            cover_max_bytes: usize::MAX,
+            image_max_bytes: usize::MAX,
+            submit_max_mb: 250,
+            json_max_mb: 1,
+        };
+        let parsed = SubmissionRequest::parse_json(reader, &limits);
+
+        let retained = CURRENT.load(Ordering::SeqCst);
+        let peak = PEAK.load(Ordering::SeqCst);
+        TRACKING.with(|t| t.set(false));
+
+        let request = parsed.expect("large submission should parse");
+        assert_eq!(request.images.len(), IMAGE_COUNT);
+        assert_eq!(request.cover.bytes.len(), IMAGE_LEN / 4 * 3);
+
+        // 保留下来的只有解码后的图片（约为 Base64 长度的 3/4）
+        assert!(retained < payload_len as isize);
+        // 解析过程中的额外开销只与单张图片大小相关，而非整个请求体
+        let transient = peak - retained;
+        assert!(
+            transient < (5 * IMAGE_LEN) as isize,
+            "transient={transient}, payload={payload_len}"
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use crate::middleware::mem_map::{MemMap, ToKey};
+use crate::to_key;
+use chrono::Duration;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::future::Future;
+use tokio_util::task::TaskTracker;
+use tracing::{Instrument, Span, error, info, warn};
+use uuid::Uuid;
+
+/// 处理状态的保留时间
+const STATUS_TTL: Duration = Duration::days(1);
+
+/// 投稿在后台队列中的处理状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum SubmissionStatus {
+    Queued,
+    Processing,
+    Done {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pr_url: Option<String>,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+/// 投稿状态缓存 Key
+pub struct SubmissionStatusKey {
+    pub module: &'static str,
+    pub id: Uuid,
+}
+
+impl SubmissionStatusKey {
+    pub fn new(id: Uuid) -> Self {
+        Self {
+            module: "submission-status",
+            id,
+        }
+    }
+}
+
+to_key!(SubmissionStatusKey; module=module; id);
+
+/// 投稿指纹缓存 Key，值为首次投稿的 submission_id
+pub struct SubmissionFingerprintKey {
+    pub module: &'static str,
+    pub fingerprint: String,
+}
+
+impl SubmissionFingerprintKey {
+    pub fn new(fingerprint: &str) -> Self {
+        Self {
+            module: "submission-fingerprint",
+            fingerprint: fingerprint.to_string(),
+        }
+    }
+}
+
+to_key!(SubmissionFingerprintKey; module=module; fingerprint);
+
+/// 幂等键缓存 Key，值为首次携带该键受理的投稿
+pub struct IdempotencyKey {
+    pub module: &'static str,
+    pub key: String,
+}
+
+impl IdempotencyKey {
+    pub fn new(key: &str) -> Self {
+        Self {
+            module: "submission-idempotency",
+            key: key.to_string(),
+        }
+    }
+}
+
+to_key!(IdempotencyKey; module=module; key);
+
+/// 携带幂等键的投稿首次被受理时的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotentSubmission {
+    pub submission_id: Uuid,
+    pub branch: String,
+}
+
+/// 更新投稿状态
+pub fn set_status(id: Uuid, status: SubmissionStatus) {
+    MemMap::global().insert(SubmissionStatusKey::new(id), status, STATUS_TTL);
+}
+
+/// 查询投稿状态，不存在或已过期时返回 None
+pub fn get_status(id: Uuid) -> Option<SubmissionStatus> {
+    MemMap::global().get::<SubmissionStatusKey, SubmissionStatus>(&SubmissionStatusKey::new(id))
+}
+
+/// 在 `window` 内为投稿 `id` 登记指纹
+///
+/// 同一指纹已登记过其他投稿时返回该投稿的 id；先前的投稿处理失败时允许重新投稿
+pub async fn claim_fingerprint(fingerprint: &str, id: Uuid, window: Duration) -> Result<(), Uuid> {
+    let cache = MemMap::global();
+    let existing = cache
+        .get_or_insert_with(
+            SubmissionFingerprintKey::new(fingerprint),
+            window,
+            || async { id },
+        )
+        .await;
+    if existing == id {
+        return Ok(());
+    }
+    if let Some(SubmissionStatus::Failed { .. }) = get_status(existing) {
+        cache.insert(SubmissionFingerprintKey::new(fingerprint), id, window);
+        return Ok(());
+    }
+    Err(existing)
+}
+
+/// 查询 `window` 内登记过同一指纹、且未处理失败的投稿，只读取不登记
+pub fn find_fingerprint(fingerprint: &str) -> Option<Uuid> {
+    let existing = MemMap::global()
+        .get::<SubmissionFingerprintKey, Uuid>(&SubmissionFingerprintKey::new(fingerprint))?;
+    match get_status(existing) {
+        Some(SubmissionStatus::Failed { .. }) => None,
+        _ => Some(existing),
+    }
+}
+
+/// 查询幂等键已受理的投稿，不存在或已过期时返回 None
+pub fn find_idempotent(key: &str) -> Option<IdempotentSubmission> {
+    MemMap::global().get::<IdempotencyKey, IdempotentSubmission>(&IdempotencyKey::new(key))
+}
+
+/// 在 `window` 内把幂等键登记给投稿 `submission`
+///
+/// 该键已登记给其他投稿（如并发的重试请求）时返回先登记的投稿
+pub async fn claim_idempotency_key(
+    key: &str,
+    submission: IdempotentSubmission,
+    window: Duration,
+) -> Result<(), IdempotentSubmission> {
+    let id = submission.submission_id;
+    let existing = MemMap::global()
+        .get_or_insert_with(IdempotencyKey::new(key), window, || async { submission })
+        .await;
+    if existing.submission_id == id {
+        Ok(())
+    } else {
+        Err(existing)
+    }
+}
+
+/// 进行中的投稿发布任务，关闭服务时等待它们结束
+static PUBLISH_TASKS: Lazy<TaskTracker> = Lazy::new(TaskTracker::new);
+
+/// 把投稿放入后台处理，立即返回
+///
+/// `work` 成功时返回 PR 地址，失败时的错误信息会作为 `failed` 的原因
+pub fn enqueue<F, Fut>(id: Uuid, work: F)
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<String>> + Send + 'static,
+{
+    enqueue_on(&PUBLISH_TASKS, id, work);
+}
+
+fn enqueue_on<F, Fut>(tracker: &TaskTracker, id: Uuid, work: F)
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<String>> + Send + 'static,
+{
+    set_status(id, SubmissionStatus::Queued);
+    info!("SUBMISSION_QUEUE[{id}]: queued");
+    // 沿用提交请求的 span，后台发布过程的日志同样带上 request_id
+    tracker.spawn(run(id, work).instrument(Span::current()));
+}
+
+/// 等待进行中的投稿发布完成，最多等待 `timeout`，返回是否在超时前全部完成
+///
+/// 发布中途被丢弃会留下已消费的验证码和没有 PR 的分支，投稿人也收不到任何通知
+pub async fn shutdown(timeout: std::time::Duration) -> bool {
+    drain(&PUBLISH_TASKS, timeout).await
+}
+
+async fn drain(tracker: &TaskTracker, timeout: std::time::Duration) -> bool {
+    tracker.close();
+    if tokio::time::timeout(timeout, tracker.wait()).await.is_err() {
+        warn!(
+            "SUBMISSION_QUEUE: shutdown timed out, {} publish jobs abandoned",
+            tracker.len()
+        );
+        return false;
+    }
+    info!("SUBMISSION_QUEUE: publish jobs finished");
+    true
+}
+
+async fn run<F, Fut>(id: Uuid, work: F)
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = anyhow::Result<String>>,
+{
+    set_status(id, SubmissionStatus::Processing);
+    info!("SUBMISSION_QUEUE[{id}]: processing");
+
+    match work().await {
+        Ok(pr_url) => {
+            info!("SUBMISSION_QUEUE[{id}]: done, pr_url={}", pr_url);
+            set_status(
+                id,
+                SubmissionStatus::Done {
+                    pr_url: Some(pr_url),
+                },
+            );
+        }
+        Err(e) => {
+            error!("SUBMISSION_QUEUE[{id}]: failed: {:#}", e);
+            set_status(
+                id,
+                SubmissionStatus::Failed {
+                    reason: format!("{:#}", e),
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    /// 让出执行权直到后台任务推进到期望的状态
+    async fn wait_for(id: Uuid, expected: &SubmissionStatus) {
+        for _ in 0..100 {
+            if get_status(id).as_ref() == Some(expected) {
+                return;
+            }
+            tokio::task::yield_now().await;
+        }
+        panic!(
+            "status stuck at {:?}, expected {:?}",
+            get_status(id),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_done() {
+        let id = Uuid::new_v4();
+        let (tx, rx) = oneshot::channel::<()>();
+
+        enqueue(id, move || async move {
+            rx.await.unwrap();
+            Ok("https://github.com/o/r/pull/1".to_string())
+        });
+        // 后台任务尚未开始执行
+        assert_eq!(get_status(id), Some(SubmissionStatus::Queued));
+
+        wait_for(id, &SubmissionStatus::Processing).await;
+
+        tx.send(()).unwrap();
+        wait_for(
+            id,
+            &SubmissionStatus::Done {
+                pr_url: Some("https://github.com/o/r/pull/1".to_string()),
+            },
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_failed() {
+        let id = Uuid::new_v4();
+
+        enqueue(id, || async { Err(anyhow::anyhow!("推送分支失败")) });
+        assert_eq!(get_status(id), Some(SubmissionStatus::Queued));
+
+        wait_for(
+            id,
+            &SubmissionStatus::Failed {
+                reason: "推送分支失败".to_string(),
+            },
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_in_flight_jobs() {
+        let tracker = TaskTracker::new();
+        let id = Uuid::new_v4();
+        enqueue_on(&tracker, id, || async {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            Ok("https://github.com/o/r/pull/1".to_string())
+        });
+
+        assert!(drain(&tracker, std::time::Duration::from_secs(5)).await);
+        assert_eq!(
+            get_status(id),
+            Some(SubmissionStatus::Done {
+                pr_url: Some("https://github.com/o/r/pull/1".to_string()),
+            })
+        );
+
+        // 超时后不再等待仍未完成的任务
+        let tracker = TaskTracker::new();
+        enqueue_on(&tracker, Uuid::new_v4(), std::future::pending);
+        assert!(!drain(&tracker, std::time::Duration::from_millis(20)).await);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_within_window_is_rejected() {
+        let fingerprint = Uuid::new_v4().to_string();
+        let window = Duration::minutes(10);
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(claim_fingerprint(&fingerprint, first, window).await, Ok(()));
+        set_status(first, SubmissionStatus::Processing);
+        assert_eq!(
+            claim_fingerprint(&fingerprint, second, window).await,
+            Err(first)
+        );
+
+        // 先前的投稿失败后允许重新投稿
+        set_status(
+            first,
+            SubmissionStatus::Failed {
+                reason: "推送分支失败".to_string(),
+            },
+        );
+        assert_eq!(
+            claim_fingerprint(&fingerprint, second, window).await,
+            Ok(())
+        );
+        assert_eq!(
+            claim_fingerprint(&fingerprint, Uuid::new_v4(), window).await,
+            Err(second)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_after_window_is_accepted() {
+        let fingerprint = Uuid::new_v4().to_string();
+        let window = Duration::milliseconds(50);
+        let first = Uuid::new_v4();
+
+        assert_eq!(claim_fingerprint(&fingerprint, first, window).await, Ok(()));
+        set_status(first, SubmissionStatus::Processing);
+
+        tokio::time::sleep(std::time::Duration::from_millis(80)).await;
+        assert_eq!(
+            claim_fingerprint(&fingerprint, Uuid::new_v4(), window).await,
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_status_json() {
+        let json = serde_json::to_value(SubmissionStatus::Done {
+            pr_url: Some("u".to_string()),
+        })
+        .unwrap();
+        assert_eq!(json, serde_json::json!({"status": "done", "pr_url": "u"}));
+
+        let json = serde_json::to_value(SubmissionStatus::Queued).unwrap();
+        assert_eq!(json, serde_json::json!({"status": "queued"}));
+        assert!(get_status(Uuid::new_v4()).is_none());
+    }
+}
@@ -1,8 +1,15 @@
+use crate::config::AppConfig;
 use axum::extract::DefaultBodyLimit;
 
 /// 上传大小的单位换算常量：1 MB
 pub const MB: usize = 1024 * 1024;
-/// 请求体大小限制层
-pub fn body_limit_layer() -> DefaultBodyLimit {
-    DefaultBodyLimit::max(250 * MB)
+
+/// 投稿接口的请求体大小限制层，图片以 Base64 或文件形式随请求上传
+pub fn submit_limit_layer() -> DefaultBodyLimit {
+    DefaultBodyLimit::max(AppConfig::global().upload.submit_max_mb * MB)
+}
+
+/// 验证码、文件分享、管理等小型 JSON 接口的请求体大小限制层
+pub fn small_json_limit_layer() -> DefaultBodyLimit {
+    DefaultBodyLimit::max(AppConfig::global().upload.json_max_mb * MB)
 }
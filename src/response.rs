@@ -1,10 +1,74 @@
 use crate::middleware::request_id::RequestId;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode, header};
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
+use utoipa::ToSchema;
+
+/// 客户端在 Accept 中携带该媒体类型时，支持的接口直接返回 `data`，不再包装
+pub const RAW_MEDIA_TYPE: &str = "application/vnd.qidian.raw+json";
+
+/// 客户端是否要求返回未包装的原始数据
+pub fn prefers_raw(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|v| v.split(';').next())
+        .any(|mime| mime.trim().eq_ignore_ascii_case(RAW_MEDIA_TYPE))
+}
+
+/// 机器可读的错误类型，客户端据此区分错误而不必匹配提示文案
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// 邮箱格式不正确
+    InvalidEmail,
+    /// 验证码错误或已过期
+    InvalidCode,
+    /// 请求过于频繁
+    RateLimited,
+    /// 邮件发送失败
+    MailFailed,
+    /// 请求体或单个字段超出大小限制
+    PayloadTooLarge,
+    /// 请求体无法解析
+    InvalidBody,
+    /// 请求未使用 JSON
+    UnsupportedMediaType,
+    /// 投稿内容校验未通过
+    ValidationFailed,
+    /// 重复投稿
+    DuplicateSubmission,
+    /// 资源不存在
+    NotFound,
+    /// 分享文件存储出错
+    StorageError,
+    /// API Key 无效
+    InvalidApiKey,
+    /// 服务维护中
+    Maintenance,
+    /// 分支名不是投稿创建的分支
+    InvalidBranch,
+    /// 分支不属于本次投稿的邮箱
+    BranchNotOwned,
+    /// 请求 GitHub 失败
+    GithubError,
+    /// `Idempotency-Key` 请求头无效
+    InvalidIdempotencyKey,
+    /// 重新加载的配置无效
+    InvalidConfig,
+}
+
+/// 单个字段的校验错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
 
 /// 通用响应结构
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ApiResponse<T>
 where
     T: Serialize,
@@ -14,6 +78,11 @@ where
     pub data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ErrorCode>,
+    /// 按字段列出的校验错误
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
 }
 
 impl<T> ApiResponse<T>
@@ -26,6 +95,28 @@ where
             message: "success".to_string(),
             data: Some(data),
             request_id: None,
+            error_code: None,
+            errors: None,
+        }
+    }
+
+    /// 成功响应，附带请求 ID 以便与日志关联
+    pub fn success_with_id(data: T, request_id: RequestId) -> Self {
+        Self {
+            request_id: Some(request_id.to_string()),
+            ..Self::success(data)
+        }
+    }
+
+    /// 请求已接受、将在后台处理（202）
+    pub fn accepted(data: T, request_id: RequestId) -> Self {
+        Self {
+            code: StatusCode::ACCEPTED.as_u16(),
+            message: "accepted".to_string(),
+            data: Some(data),
+            request_id: Some(request_id.to_string()),
+            error_code: None,
+            errors: None,
         }
     }
 
@@ -35,6 +126,53 @@ where
             message: message.to_string(),
             data: None,
             request_id: Some(request_id.to_string()),
+            error_code: None,
+            errors: None,
+        }
+    }
+
+    /// 带机器可读错误类型的错误响应
+    pub fn error_with_code(
+        status: StatusCode,
+        code: ErrorCode,
+        message: &str,
+        request_id: RequestId,
+    ) -> Self {
+        Self {
+            error_code: Some(code),
+            ..Self::error(status, message, request_id)
+        }
+    }
+
+    /// 按字段列出校验错误（422），客户端可据此定位到具体的输入项
+    pub fn validation_error(errors: Vec<FieldError>, request_id: RequestId) -> Self {
+        Self {
+            errors: Some(errors),
+            ..Self::error_with_code(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorCode::ValidationFailed,
+                "校验失败",
+                request_id,
+            )
+        }
+    }
+}
+
+impl<T> ApiResponse<T>
+where
+    T: Serialize,
+{
+    /// 按客户端偏好生成响应：要求原始数据且成功时只返回 `data`，失败时仍返回完整结构
+    pub fn negotiate(self, raw: bool) -> Response {
+        match self {
+            ApiResponse {
+                code,
+                data: Some(data),
+                ..
+            } if raw && StatusCode::from_u16(code).is_ok_and(|s| s.is_success()) => {
+                axum::Json(data).into_response()
+            }
+            resp => resp.into_response(),
         }
     }
 }
@@ -49,3 +187,77 @@ where
         (status, body).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code_serialized() {
+        let resp = ApiResponse::<()>::error_with_code(
+            StatusCode::UNAUTHORIZED,
+            ErrorCode::InvalidCode,
+            "验证码错误或已过期",
+            RequestId::new(),
+        );
+        let json = serde_json::to_value(&resp).unwrap();
+        assert_eq!(json["code"], 401);
+        assert_eq!(json["error_code"], "INVALID_CODE");
+
+        let json = serde_json::to_value(ErrorCode::RateLimited).unwrap();
+        assert_eq!(json, "RATE_LIMITED");
+    }
+
+    #[test]
+    fn test_validation_error_lists_fields() {
+        let errors = vec![
+            FieldError {
+                field: "title".to_string(),
+                message: "不能为空".to_string(),
+            },
+            FieldError {
+                field: "images".to_string(),
+                message: "最多 20 张".to_string(),
+            },
+        ];
+        let resp = ApiResponse::<()>::validation_error(errors.clone(), RequestId::new());
+        let json = serde_json::to_value(&resp).unwrap();
+
+        assert_eq!(json["code"], 422);
+        assert_eq!(json["error_code"], "VALIDATION_FAILED");
+        let fields: Vec<(&str, &str)> = json["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| (e["field"].as_str().unwrap(), e["message"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![("title", "不能为空"), ("images", "最多 20 张")]
+        );
+    }
+
+    #[test]
+    fn test_success_with_id_carries_request_id() {
+        let request_id = RequestId::new();
+        let json = serde_json::to_value(ApiResponse::success_with_id(1, request_id)).unwrap();
+        assert_eq!(json["code"], 200);
+        assert_eq!(json["data"], 1);
+        assert_eq!(json["request_id"], request_id.to_string());
+
+        // 原始数据模式下只返回 data
+        let resp = ApiResponse::success_with_id(1, request_id).negotiate(true);
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_error_code_omitted_when_absent() {
+        let resp = ApiResponse::<()>::error(StatusCode::BAD_REQUEST, "bad", RequestId::new());
+        let json = serde_json::to_value(&resp).unwrap();
+        assert!(json.get("error_code").is_none());
+
+        let json = serde_json::to_value(ApiResponse::success(1)).unwrap();
+        assert!(json.get("error_code").is_none());
+        assert!(json.get("errors").is_none());
+    }
+}
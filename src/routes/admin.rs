@@ -0,0 +1,21 @@
+use crate::handler::{admin, auth};
+use crate::middleware::admin_auth::require_api_key;
+use crate::middleware::upload_limit;
+use axum::Router;
+use axum::middleware::from_fn;
+use axum::routing::{get, post};
+
+pub fn routes() -> Router {
+    Router::new()
+        // 管理员补发验证码 -> POST /admin/auth/reissue
+        .route("/admin/auth/reissue", post(auth::reissue_code))
+        // 维护模式 -> GET/POST /admin/maintenance
+        .route(
+            "/admin/maintenance",
+            get(admin::get_maintenance).post(admin::set_maintenance),
+        )
+        // 重新加载配置 -> POST /admin/reload
+        .route("/admin/reload", post(admin::reload_config))
+        .route_layer(from_fn(require_api_key))
+        .layer(upload_limit::small_json_limit_layer())
+}
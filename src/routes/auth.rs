@@ -1,4 +1,5 @@
 use crate::handler::auth;
+use crate::middleware::upload_limit;
 use axum::Router;
 use axum::routing::post;
 
@@ -6,4 +7,7 @@ pub fn routes() -> Router {
     Router::new()
         // 发送验证码 -> POST /auth/send
         .route("/auth/send", post(auth::send_code))
+        // 预先验证验证码并换取一次性令牌 -> POST /auth/verify
+        .route("/auth/verify", post(auth::verify))
+        .layer(upload_limit::small_json_limit_layer())
 }
@@ -1,44 +1,424 @@
-use crate::config::AppConfig;
-use crate::response::ApiResponse;
-use axum::{Router, routing::get};
+use crate::config::{AppConfig, HealthConfig};
+use crate::middleware::admin_auth::has_api_key;
+use crate::middleware::mem_map::{CacheStats, MemMap, ToKey};
+use crate::middleware::request_id::RequestId;
+use crate::response::{ApiResponse, ErrorCode};
+use crate::to_key;
+use crate::utils::email::SmtpMailer;
+use crate::utils::http;
+use axum::extract::Query;
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Router, routing::get};
+use chrono::Duration;
+use once_cell::sync::Lazy;
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use std::{fs, io};
+use tracing::warn;
+
+const GITHUB_RATE_LIMIT_URL: &str = "https://api.github.com/rate_limit";
 
 #[derive(Deserialize, Serialize)]
 pub struct Health {
     config: String,
+    /// 有效的管理员邮箱数
+    admin_emails: usize,
     github: String,
+    smtp: String,
+    disk: DiskHealth,
+}
+
+impl Health {
+    /// 关键依赖是否都可用；GitHub 处于 degraded 时仍视为可用
+    fn is_ready(&self) -> bool {
+        !self.github.starts_with("error") && self.smtp == "ok" && self.disk.status == "ok"
+    }
+}
+
+/// 分享文件目录的状态
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DiskHealth {
+    /// `ok` 或 `error: ...`
+    status: String,
+    /// 非特权用户可用的剩余空间（字节），检测失败时为空
+    available_bytes: Option<u64>,
+}
+
+/// `/health` 的查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct HealthQuery {
+    /// 为 true 时忽略缓存的 GitHub 与 SMTP 检测结果，重新检测；需要携带管理 API Key
+    #[serde(default)]
+    fresh: bool,
+}
+
+impl HealthQuery {
+    /// `fresh=true` 会当场访问 GitHub 与 SMTP，只接受携带管理 API Key 的请求，
+    /// 避免匿名请求借此反复连接外部服务
+    fn reject_unauthorized<T: Serialize>(
+        &self,
+        headers: &HeaderMap,
+        request_id: RequestId,
+    ) -> Option<ApiResponse<T>> {
+        if !self.fresh || has_api_key(headers) {
+            return None;
+        }
+        warn!("HEALTH: fresh=true rejected, missing or invalid api key");
+        Some(ApiResponse::error_with_code(
+            StatusCode::UNAUTHORIZED,
+            ErrorCode::InvalidApiKey,
+            "fresh=true 需要管理 API Key",
+            request_id,
+        ))
+    }
+}
+
+/// 缓存某个地址最近一次的检测结果
+struct HealthKey {
+    module: &'static str,
+    url: String,
+}
+
+impl HealthKey {
+    fn new(url: impl Into<String>) -> Self {
+        Self {
+            module: "health-github-status",
+            url: url.into(),
+        }
+    }
+}
+
+to_key!(HealthKey; module=module; url);
+
+/// 记录某个地址最近一次检测成功
+struct GithubHealthKey {
+    module: &'static str,
+    url: String,
+}
+
+impl GithubHealthKey {
+    fn new(url: impl Into<String>) -> Self {
+        Self {
+            module: "health-github",
+            url: url.into(),
+        }
+    }
+}
+
+to_key!(GithubHealthKey; module=module; url);
+
+/// 缓存的 SMTP 检测结果，探针请求不再每次都连接 SMTP 服务器
+#[derive(Default)]
+struct SmtpProbe {
+    /// 最近一次检测的结果与检测时间
+    last: Mutex<Option<(String, Instant)>>,
+    /// 是否已有后台检测在进行，避免过期后每个探针请求都发起一次
+    refreshing: AtomicBool,
+}
+
+static SMTP_PROBE: Lazy<Arc<SmtpProbe>> = Lazy::new(Arc::default);
+
+impl SmtpProbe {
+    /// 返回缓存的检测结果，过期时先返回旧结果并在后台重新检测
+    ///
+    /// 尚无结果、`fresh` 为 true 或 `smtp_cache_secs` 为 0 时当场检测
+    async fn status(
+        self: &Arc<Self>,
+        mailer: Arc<SmtpMailer>,
+        cfg: &HealthConfig,
+        fresh: bool,
+    ) -> String {
+        let ttl = std::time::Duration::from_secs(cfg.smtp_cache_secs);
+        let last = self.last.lock().unwrap().clone();
+        match last {
+            Some((status, checked_at)) if !fresh && !ttl.is_zero() => {
+                if checked_at.elapsed() >= ttl {
+                    self.refresh_in_background(mailer, cfg.smtp_timeout_ms);
+                }
+                status
+            }
+            _ => self.refresh(mailer, cfg.smtp_timeout_ms).await,
+        }
+    }
+
+    async fn refresh(&self, mailer: Arc<SmtpMailer>, timeout_ms: u64) -> String {
+        let status = smtp_status(mailer, timeout_ms).await;
+        *self.last.lock().unwrap() = Some((status.clone(), Instant::now()));
+        status
+    }
+
+    fn refresh_in_background(self: &Arc<Self>, mailer: Arc<SmtpMailer>, timeout_ms: u64) {
+        if self.refreshing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let probe = self.clone();
+        tokio::spawn(async move {
+            probe.refresh(mailer, timeout_ms).await;
+            probe.refreshing.store(false, Ordering::SeqCst);
+        });
+    }
 }
 
 pub fn routes() -> Router {
-    Router::new().route("/health", get(health))
+    Router::new()
+        .route("/health", get(health))
+        .route("/health/live", get(live))
+        .route("/health/ready", get(ready))
+        .route("/health/cache", get(cache_stats))
+        .route("/health/smtp", get(smtp_health))
+        .route("/health/disk", get(disk_health))
+}
+
+/// 单独检测分享文件目录
+async fn disk_health(Extension(request_id): Extension<RequestId>) -> ApiResponse<DiskHealth> {
+    ApiResponse::success_with_id(
+        disk_status(&AppConfig::global().file_share.path),
+        request_id,
+    )
 }
 
-async fn health() -> ApiResponse<Health> {
+/// 单独检测 SMTP 连通性，结果缓存同 `/health`
+async fn smtp_health(
+    Extension(request_id): Extension<RequestId>,
+    headers: HeaderMap,
+    Query(query): Query<HealthQuery>,
+) -> ApiResponse<String> {
+    if let Some(rejected) = query.reject_unauthorized(&headers, request_id) {
+        return rejected;
+    }
     let config = AppConfig::global();
-    let (config_ok, config_total) = config.stats();
+    let status = SMTP_PROBE
+        .status(SmtpMailer::global(), &config.health, query.fresh)
+        .await;
+    ApiResponse::success_with_id(status, request_id)
+}
 
-    // GitHub 连通性检测
-    let github_status = match check_github().await {
-        Ok(_) => "ok".to_string(),
-        Err(e) => format!("error: {}", e),
-    };
+/// 内存缓存的条目数与命中情况
+async fn cache_stats(Extension(request_id): Extension<RequestId>) -> ApiResponse<CacheStats> {
+    ApiResponse::success_with_id(MemMap::global().stats(), request_id)
+}
 
-    ApiResponse::success(Health {
-        config: format!("{}/{}", config_ok, config_total),
-        github: github_status,
-    })
+/// 存活检测：进程能响应即返回 200，不访问任何外部依赖
+async fn live(Extension(request_id): Extension<RequestId>) -> ApiResponse<&'static str> {
+    ApiResponse::success_with_id("ok", request_id)
 }
 
-async fn check_github() -> Result<(), Box<dyn std::error::Error>> {
+/// 就绪检测：与 `/health` 做同样的检测，关键依赖不可用时返回 503
+async fn ready(
+    Extension(request_id): Extension<RequestId>,
+    headers: HeaderMap,
+    Query(query): Query<HealthQuery>,
+) -> ApiResponse<Health> {
+    if let Some(rejected) = query.reject_unauthorized(&headers, request_id) {
+        return rejected;
+    }
+    readiness(check_health(query.fresh).await, request_id)
+}
+
+fn readiness(health: Health, request_id: RequestId) -> ApiResponse<Health> {
+    if health.is_ready() {
+        return ApiResponse::success_with_id(health, request_id);
+    }
+    warn!(
+        "HEALTH: not ready, github={}, smtp={}, disk={}",
+        health.github, health.smtp, health.disk.status
+    );
+    ApiResponse {
+        code: StatusCode::SERVICE_UNAVAILABLE.as_u16(),
+        message: "dependency unavailable".to_string(),
+        ..ApiResponse::success_with_id(health, request_id)
+    }
+}
+
+/// 完整的健康信息，不论结果如何都返回 200
+async fn health(
+    Extension(request_id): Extension<RequestId>,
+    headers: HeaderMap,
+    Query(query): Query<HealthQuery>,
+) -> ApiResponse<Health> {
+    if let Some(rejected) = query.reject_unauthorized(&headers, request_id) {
+        return rejected;
+    }
+    ApiResponse::success_with_id(check_health(query.fresh).await, request_id)
+}
+
+async fn check_health(fresh: bool) -> Health {
     let config = AppConfig::global();
-    let token = config.github.personal_access_token.expose_secret();
+    let stats = config.stats();
+
+    // GitHub 与 SMTP 连通性检测同时进行
+    let (github_status, smtp_status) = tokio::join!(
+        github_status(
+            GITHUB_RATE_LIMIT_URL,
+            config.github.personal_access_token.expose_secret(),
+            &config.health,
+            fresh,
+        ),
+        SMTP_PROBE.status(SmtpMailer::global(), &config.health, fresh),
+    );
+
+    Health {
+        config: format!("{}/{}", stats.ok, stats.total),
+        admin_emails: stats.admin_emails,
+        github: github_status,
+        smtp: smtp_status,
+        disk: disk_status(&config.file_share.path),
+    }
+}
+
+/// 检测目录存在、可读，并给出剩余空间
+fn disk_status(path: &Path) -> DiskHealth {
+    match check_disk(path) {
+        Ok(available) => DiskHealth {
+            status: "ok".to_string(),
+            available_bytes: Some(available),
+        },
+        Err(e) => {
+            warn!("HEALTH: disk check failed: {}", e);
+            DiskHealth {
+                status: format!("error: {}", e),
+                available_bytes: None,
+            }
+        }
+    }
+}
+
+fn check_disk(path: &Path) -> Result<u64, String> {
+    let meta = fs::metadata(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => format!("{} does not exist", path.display()),
+        _ => format!("cannot stat {}: {}", path.display(), e),
+    })?;
+    if !meta.is_dir() {
+        return Err(format!("{} is not a directory", path.display()));
+    }
+    fs::read_dir(path).map_err(|e| format!("{} is not readable: {}", path.display(), e))?;
+    available_space(path).map_err(|e| format!("cannot get free space of {}: {}", path.display(), e))
+}
 
-    let client = reqwest::Client::new();
+/// 通过 statvfs 获取非特权用户可用的剩余空间
+#[cfg(unix)]
+fn available_space(path: &Path) -> io::Result<u64> {
+    use std::ffi::CString;
+    use std::mem::MaybeUninit;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat = MaybeUninit::<libc::statvfs>::zeroed();
+    // SAFETY: c_path 是以 NUL 结尾的路径，stat 指向一个 statvfs 大小的缓冲区
+    if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: statvfs 成功返回时已填充整个结构体
+    let stat = unsafe { stat.assume_init() };
+    // 字段类型随平台不同，32 位平台上不是 u64
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "free space check is only supported on unix",
+    ))
+}
+
+/// 检测 SMTP 连通性并给出状态：`ok` 或 `error: ...`
+///
+/// 超过 `timeout_ms` 即视为失败；卡住的检测线程会在 SMTP 连接超时后自行退出
+async fn smtp_status(mailer: Arc<SmtpMailer>, timeout_ms: u64) -> String {
+    let check = tokio::task::spawn_blocking(move || mailer.test_connection());
+    let timeout = std::time::Duration::from_millis(timeout_ms);
+
+    let result = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(anyhow::anyhow!("check task failed: {}", e)),
+        Err(_) => Err(anyhow::anyhow!("timed out after {} ms", timeout_ms)),
+    };
+    match result {
+        Ok(()) => "ok".to_string(),
+        Err(e) => {
+            warn!("HEALTH: smtp check failed: {:#}", e);
+            format!("error: {:#}", e)
+        }
+    }
+}
+
+/// 检测 GitHub 连通性并给出状态：`ok`、`degraded: ...` 或 `error: ...`
+///
+/// 检测失败但最近有过成功记录时只视为 degraded，避免短暂抖动让实例被负载均衡摘除；
+/// 结果缓存 `github_cache_secs` 秒，期间的调用不再请求 GitHub，除非 `fresh` 为 true
+async fn github_status(url: &str, token: &str, cfg: &HealthConfig, fresh: bool) -> String {
+    let cache = MemMap::global();
+    let status_key = HealthKey::new(url);
+    if !fresh && let Some(status) = cache.get::<HealthKey, String>(&status_key) {
+        return status;
+    }
+
+    let status = check_github_status(url, token, cfg).await;
+    if cfg.github_cache_secs > 0 {
+        cache.insert(
+            status_key,
+            status.clone(),
+            Duration::seconds(cfg.github_cache_secs),
+        );
+    }
+    status
+}
+
+async fn check_github_status(url: &str, token: &str, cfg: &HealthConfig) -> String {
+    let cache = MemMap::global();
+    let key = GithubHealthKey::new(url);
+
+    match check_github(url, token, cfg).await {
+        Ok(_) => {
+            cache.insert(key, (), Duration::seconds(cfg.github_grace_secs));
+            "ok".to_string()
+        }
+        Err(e) if cache.get::<GithubHealthKey, ()>(&key).is_some() => {
+            warn!("HEALTH: github check failed, recent success cached: {}", e);
+            format!("degraded: {}", e)
+        }
+        Err(e) => {
+            warn!("HEALTH: github check failed: {}", e);
+            format!("error: {}", e)
+        }
+    }
+}
+
+/// 带超时的检测，失败后按配置重试
+async fn check_github(
+    url: &str,
+    token: &str,
+    cfg: &HealthConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut attempt = 0;
+    loop {
+        match check_github_once(url, token, cfg.github_timeout_ms).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < cfg.github_retries => {
+                warn!("HEALTH: github check attempt {} failed: {}", attempt + 1, e);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn check_github_once(
+    url: &str,
+    token: &str,
+    timeout_ms: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let client = http::client();
     let res = client
-        .get("https://api.github.com/rate_limit")
-        .header("User-Agent", "qidian-healthcheck")
+        .get(url)
         .bearer_auth(token) // 用 PAT
+        .timeout(std::time::Duration::from_millis(timeout_ms))
         .send()
         .await?;
 
@@ -48,3 +428,293 @@ async fn check_github() -> Result<(), Box<dyn std::error::Error>> {
         Err(format!("GitHub returned status {}", res.status()).into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::net::TcpListener;
+
+    /// 启动本地 mock 服务，按顺序返回给定状态码，之后一直返回最后一个；同时返回请求计数
+    async fn mock_github(statuses: Vec<StatusCode>) -> (String, Arc<AtomicUsize>) {
+        // http::client() 需要读取全局配置中的 User-Agent
+        crate::config::test_global();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/rate_limit",
+            get(move || {
+                let calls = calls.clone();
+                let statuses = statuses.clone();
+                async move {
+                    let n = calls.fetch_add(1, Ordering::SeqCst);
+                    statuses[n.min(statuses.len() - 1)]
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/rate_limit", addr), counter)
+    }
+
+    fn health_config(retries: u32) -> HealthConfig {
+        HealthConfig {
+            github_timeout_ms: 1000,
+            github_retries: retries,
+            github_grace_secs: 300,
+            github_cache_secs: 0,
+            smtp_timeout_ms: 1000,
+            smtp_cache_secs: 0,
+        }
+    }
+
+    /// 指向本机 `port` 的 SMTP mailer
+    fn local_mailer(port: u16) -> Arc<SmtpMailer> {
+        let transport = lettre::SmtpTransport::builder_dangerous("127.0.0.1")
+            .port(port)
+            .timeout(Some(std::time::Duration::from_secs(1)))
+            .build();
+        Arc::new(SmtpMailer::with_transport(
+            transport,
+            "noreply@example.com".to_string(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_github_status_is_cached() {
+        let (url, calls) = mock_github(vec![StatusCode::OK]).await;
+        let cfg = HealthConfig {
+            github_cache_secs: 30,
+            ..health_config(0)
+        };
+
+        assert_eq!(github_status(&url, "token", &cfg, false).await, "ok");
+        assert_eq!(github_status(&url, "token", &cfg, false).await, "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // fresh=true 跳过缓存
+        assert_eq!(github_status(&url, "token", &cfg, true).await, "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_live_makes_no_outbound_calls() {
+        use crate::middleware::request_id::request_id_layer;
+        use axum::body::Body;
+        use axum::http::Request;
+        use http_body_util::BodyExt;
+        use tower::ServiceExt;
+
+        crate::config::test_global();
+
+        let req = Request::builder()
+            .uri("/health/live")
+            .body(Body::empty())
+            .unwrap();
+        // 不访问外部依赖，远小于任何一项检测的超时
+        let app = routes().layer(request_id_layer());
+        let resp = tokio::time::timeout(std::time::Duration::from_millis(100), app.oneshot(req))
+            .await
+            .expect("live check should not wait on dependencies")
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["request_id"].is_string(), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_requires_api_key() {
+        use crate::middleware::admin_auth::API_KEY_HEADER;
+        use crate::middleware::request_id::request_id_layer;
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        crate::config::test_global();
+
+        for uri in [
+            "/health?fresh=true",
+            "/health/ready?fresh=true",
+            "/health/smtp?fresh=true",
+        ] {
+            for key in [None, Some("wrong-key")] {
+                let mut req = Request::builder().uri(uri);
+                if let Some(key) = key {
+                    req = req.header(API_KEY_HEADER, key);
+                }
+                let resp = routes()
+                    .layer(request_id_layer())
+                    .oneshot(req.body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                // 在访问 GitHub、SMTP 之前即被拒绝
+                assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+            }
+        }
+    }
+
+    fn healthy() -> Health {
+        Health {
+            config: "1/1".to_string(),
+            admin_emails: 1,
+            github: "ok".to_string(),
+            smtp: "ok".to_string(),
+            disk: DiskHealth {
+                status: "ok".to_string(),
+                available_bytes: Some(1),
+            },
+        }
+    }
+
+    #[test]
+    fn test_ready_returns_503_when_dependency_down() {
+        assert_eq!(readiness(healthy(), RequestId::new()).code, 200);
+
+        let degraded = Health {
+            github: "degraded: timeout".to_string(),
+            ..healthy()
+        };
+        assert_eq!(readiness(degraded, RequestId::new()).code, 200);
+
+        let failures = [
+            Health {
+                github: "error: GitHub returned status 503".to_string(),
+                ..healthy()
+            },
+            Health {
+                smtp: "error: timed out after 100 ms".to_string(),
+                ..healthy()
+            },
+            Health {
+                disk: DiskHealth {
+                    status: "error: /srv/share does not exist".to_string(),
+                    available_bytes: None,
+                },
+                ..healthy()
+            },
+        ];
+        for health in failures {
+            let resp = readiness(health, RequestId::new());
+            assert_eq!(resp.code, 503);
+            // 503 时仍返回各项检测结果，便于排查
+            assert!(resp.data.is_some());
+        }
+    }
+
+    #[test]
+    fn test_disk_status_for_existing_dir() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let disk = disk_status(dir.path());
+        assert_eq!(disk.status, "ok");
+        assert!(disk.available_bytes.is_some_and(|bytes| bytes > 0));
+    }
+
+    #[test]
+    fn test_disk_status_for_missing_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+
+        let disk = disk_status(&missing);
+        assert!(disk.status.starts_with("error"), "{}", disk.status);
+        assert!(disk.status.contains("does not exist"), "{}", disk.status);
+        assert_eq!(disk.available_bytes, None);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_smtp_is_error() {
+        // 绑定后立即释放，得到一个没有服务监听的端口
+        let port = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap().port()
+        };
+
+        let status = smtp_status(local_mailer(port), 1000).await;
+        assert!(status.starts_with("error"), "{}", status);
+    }
+
+    #[tokio::test]
+    async fn test_hung_smtp_times_out() {
+        // 接受连接但从不发送问候语
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let status = smtp_status(local_mailer(port), 100).await;
+        assert_eq!(status, "error: timed out after 100 ms");
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn test_smtp_status_is_cached_and_refreshed_in_background() {
+        // 每个连接都计数，之后直接关闭，检测结果为 error
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                drop(stream);
+            }
+        });
+
+        let probe = Arc::new(SmtpProbe::default());
+        let cfg = HealthConfig {
+            smtp_cache_secs: 1,
+            ..health_config(0)
+        };
+        let first = probe.status(local_mailer(port), &cfg, false).await;
+        assert!(first.starts_with("error"), "{}", first);
+        assert_eq!(probe.status(local_mailer(port), &cfg, false).await, first);
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+        // 过期后立即返回旧结果，由后台任务重新检测
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        let started = Instant::now();
+        assert_eq!(probe.status(local_mailer(port), &cfg, false).await, first);
+        assert!(started.elapsed() < std::time::Duration::from_millis(50));
+        for _ in 0..100 {
+            if !probe.refreshing.load(Ordering::SeqCst) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+        // fresh=true 当场检测
+        probe.status(local_mailer(port), &cfg, true).await;
+        assert_eq!(connections.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_single_transient_failure_is_retried() {
+        let (url, _) = mock_github(vec![StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK]).await;
+
+        let status = github_status(&url, "token", &health_config(1), false).await;
+        assert_eq!(status, "ok");
+    }
+
+    #[tokio::test]
+    async fn test_failure_after_recent_success_is_degraded() {
+        let (url, _) = mock_github(vec![StatusCode::OK, StatusCode::SERVICE_UNAVAILABLE]).await;
+
+        assert_eq!(
+            github_status(&url, "token", &health_config(0), false).await,
+            "ok"
+        );
+        let status = github_status(&url, "token", &health_config(0), false).await;
+        assert!(status.starts_with("degraded"), "{}", status);
+    }
+
+    #[tokio::test]
+    async fn test_failure_without_recent_success_is_error() {
+        let (url, _) = mock_github(vec![StatusCode::SERVICE_UNAVAILABLE]).await;
+
+        let status = github_status(&url, "token", &health_config(1), false).await;
+        assert!(status.starts_with("error"), "{}", status);
+    }
+}
@@ -1,19 +1,240 @@
-use crate::middleware::{cors, http_tracing, request_id, upload_limit};
-use axum::Router;
+use crate::middleware::maintenance::Maintenance;
+use crate::middleware::{client_ip, cors, http_tracing, request_id, security_headers};
+use axum::middleware::from_fn;
+use axum::{Extension, Router};
 
+mod admin;
 mod auth;
 mod health;
+mod openapi;
 mod share;
 mod submit;
 
 pub fn routers() -> Router {
+    routers_with(Maintenance::global())
+}
+
+/// 使用给定的维护模式开关构造全部路由
+fn routers_with(maintenance: Maintenance) -> Router {
     Router::new()
         .merge(health::routes())
         .merge(auth::routes())
+        .merge(admin::routes())
         .merge(submit::routes())
         .merge(share::routes())
+        .merge(openapi::routes())
+        // CORS 必须包在所有 route_layer 之外，预检请求在这里直接返回，不会进入鉴权、维护模式等逻辑
         .layer(cors::cors_layer())
-        .layer(upload_limit::body_limit_layer())
+        // 安全头包在 CORS 之外，预检响应同样带上
+        .layer(from_fn(security_headers::security_headers))
+        .layer(Extension(maintenance))
         .layer(http_tracing::trace_layer())
+        .layer(from_fn(client_ip::client_ip))
         .layer(request_id::request_id_layer())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::upload_limit;
+    use axum::body::Body;
+    use axum::http::{Method, Request, StatusCode, header};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    const ORIGIN: &str = "https://contribute.qidian.space";
+
+    async fn preflight(uri: &str, method: Method) {
+        crate::config::test_global();
+
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri(uri)
+            .header(header::ORIGIN, ORIGIN)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, method.as_str())
+            .header(
+                header::ACCESS_CONTROL_REQUEST_HEADERS,
+                "content-type,x-api-key",
+            )
+            .body(Body::empty())
+            .unwrap();
+        let resp = routers().oneshot(req).await.unwrap();
+
+        assert!(
+            matches!(resp.status(), StatusCode::OK | StatusCode::NO_CONTENT),
+            "{} -> {}",
+            uri,
+            resp.status()
+        );
+        let headers = resp.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            ORIGIN,
+            "{}",
+            uri
+        );
+        let methods = headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap();
+        assert!(methods.contains(method.as_str()), "{}: {}", uri, methods);
+        assert!(
+            headers.contains_key(header::ACCESS_CONTROL_ALLOW_HEADERS),
+            "{}",
+            uri
+        );
+
+        // 预检请求不会进入处理函数
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty(), "{}", uri);
+    }
+
+    #[tokio::test]
+    async fn test_security_headers_on_health() {
+        crate::config::test_global();
+
+        let req = Request::builder()
+            .uri("/health")
+            .header(header::ORIGIN, ORIGIN)
+            .body(Body::empty())
+            .unwrap();
+        let resp = routers().oneshot(req).await.unwrap();
+        let headers = resp.headers();
+
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            crate::config::test_global()
+                .content_security_policy
+                .as_str()
+        );
+        // CORS 头不受影响
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], ORIGIN);
+    }
+
+    fn json_post(uri: &str, body: String) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_per_route_body_limits() {
+        use crate::handler::auth::EmailVerifyKey;
+        use crate::middleware::mem_map::MemMap;
+
+        let config = crate::config::test_global();
+        // 介于小型 JSON 上限与投稿上限之间的填充
+        let padding = "A".repeat(config.upload.json_max_mb * upload_limit::MB + 4096);
+        assert!(padding.len() < config.upload.submit_max_mb * upload_limit::MB);
+
+        let body = format!(r#"{{"email":"limit@example.com","padding":"{padding}"}}"#);
+        let resp = routers()
+            .oneshot(json_post("/auth/send", body))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // 同样大小的投稿不受小型 JSON 上限影响，进入投稿校验；
+        // 标题不合法，会在推送前被拦下，不会访问网络
+        let email = "limit-submit@example.com";
+        MemMap::global().insert(
+            EmailVerifyKey::new(email.to_string()),
+            "135790".to_string(),
+            chrono::Duration::minutes(5),
+        );
+        let body = format!(
+            r#"{{"author":"a","content":"c","email":"{email}","email_code":"135790",
+            "tags":[],"title":"C# 入门","cover":{{"name":"c.png","base64":"{padding}"}},"images":[]}}"#
+        );
+        let resp = routers().oneshot(json_post("/submit", body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error_code"], "VALIDATION_FAILED", "{}", body);
+        assert_eq!(body["errors"][0]["field"], "title", "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_rejects_submit_without_consuming_code() {
+        use crate::handler::auth::EmailVerifyKey;
+        use crate::handler::submit::PublishNotifiers;
+        use crate::middleware::mem_map::MemMap;
+        use crate::utils::email::{Mailer, SendParams};
+        use std::sync::{Arc, Mutex};
+
+        /// 只记录收件人，代替后台 SMTP 发信
+        #[derive(Default)]
+        struct RecordingMailer(Mutex<Vec<String>>);
+
+        impl Mailer for RecordingMailer {
+            fn send_full(&self, params: SendParams<'_>) -> anyhow::Result<()> {
+                let to = params.to.unwrap_or_default().to_string();
+                self.0.lock().unwrap().push(to);
+                Ok(())
+            }
+        }
+
+        crate::config::test_global();
+        // 每个测试持有独立的开关，不影响并行运行的其他投稿测试
+        let maintenance = Maintenance::default();
+        maintenance.set_enabled(true);
+
+        let email = "maintenance-submit@example.com";
+        let key = || EmailVerifyKey::new(email.to_string());
+        let cached = || MemMap::global().get::<EmailVerifyKey, String>(&key());
+        MemMap::global().insert(key(), "246810".to_string(), chrono::Duration::minutes(5));
+        // 封面无效，受理后在后台转换时失败，不会访问 GitHub
+        let body = format!(
+            r#"{{"author":"a","content":"c","email":"{email}","email_code":"246810",
+            "tags":[],"title":"维护期间投稿","cover":{{"name":"c.png","base64":"AAAA"}},"images":[]}}"#
+        );
+
+        let resp = routers_with(maintenance.clone())
+            .oneshot(json_post("/submit", body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let json = resp.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["error_code"], "MAINTENANCE", "{}", json);
+        // 验证码未被消费
+        assert!(cached().is_some());
+
+        // 维护结束后同一个验证码可以直接投稿；发布失败的通知只发给注入的 mailer
+        maintenance.set_enabled(false);
+        let mailer = Arc::new(RecordingMailer::default());
+        let notifiers = PublishNotifiers::new(mailer.clone(), vec![]);
+        let resp = routers_with(maintenance)
+            .layer(Extension(notifiers))
+            .oneshot(json_post("/submit", body))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        assert!(cached().is_none());
+
+        // 封面无效导致发布失败，失败邮件发给投稿人
+        for _ in 0..100 {
+            if !mailer.0.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(*mailer.0.lock().unwrap(), [email.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_preflight_all_routes() {
+        preflight("/submit", Method::POST).await;
+        preflight("/submit/multipart", Method::POST).await;
+        preflight("/share/get_file", Method::POST).await;
+        preflight("/share/list_file", Method::GET).await;
+        preflight("/auth/send", Method::POST).await;
+        preflight("/auth/verify", Method::POST).await;
+        // 带 API Key 校验的管理接口同样可以通过预检
+        preflight("/admin/maintenance", Method::POST).await;
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config::AppConfig;
+use crate::handler::{auth, share, submit};
+use crate::response::{ApiResponse, ErrorCode, FieldError};
+use axum::routing::get;
+use axum::{Json, Router};
+use utoipa::OpenApi;
+
+/// 对外接口文档，请求与响应结构由处理函数上的注解生成
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "QidianMini API"),
+    paths(submit::submit_article, share::share_files, auth::send_code),
+    components(schemas(ApiResponse<String>, ErrorCode, FieldError))
+)]
+pub struct ApiDoc;
+
+pub fn routes() -> Router {
+    // 接口文档 -> GET /openapi.json，仅在配置 `app.openapi` 开启时注册
+    if AppConfig::global().openapi {
+        Router::new().route("/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+    } else {
+        Router::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_lists_known_paths() {
+        let json = serde_json::to_string(&ApiDoc::openapi()).unwrap();
+        let spec: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        for path in ["/submit", "/share/get_file", "/auth/send"] {
+            assert!(spec["paths"][path]["post"].is_object(), "{}", path);
+        }
+        let submit = &spec["paths"]["/submit"]["post"];
+        assert_eq!(submit["parameters"][0]["name"], "Idempotency-Key");
+        assert!(submit["responses"]["202"].is_object());
+
+        let schemas = &spec["components"]["schemas"];
+        for schema in [
+            "SubmissionRequest",
+            "ShareRequest",
+            "SendCodeRequest",
+            "Base64Image",
+        ] {
+            assert!(schemas[schema].is_object(), "{}", schema);
+        }
+        let required = schemas["SubmissionRequest"]["required"].as_array().unwrap();
+        assert!(required.contains(&"cover".into()));
+        assert!(!required.contains(&"email_code".into()));
+    }
+}
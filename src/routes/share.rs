@@ -1,4 +1,5 @@
 use crate::handler::share;
+use crate::middleware::upload_limit;
 use axum::Router;
 use axum::routing::{get, post};
 
@@ -8,4 +9,7 @@ pub fn routes() -> Router {
         .route("/share/get_file", post(share::share_files))
         // 发送文件列表 -> GET /share/list_file
         .route("/share/list_file", get(share::list_files))
+        // 文件校验信息 -> GET /share/file_info?name=
+        .route("/share/file_info", get(share::file_info))
+        .layer(upload_limit::small_json_limit_layer())
 }
@@ -1,7 +1,31 @@
+use crate::config::AppConfig;
 use crate::handler::submit;
+use crate::middleware::{maintenance, upload_limit};
 use axum::Router;
-use axum::routing::post;
+use axum::middleware::from_fn;
+use axum::routing::{get, post};
 
 pub fn routes() -> Router {
-    Router::new().route("/submit", post(submit::submit_article))
+    let router = Router::new()
+        .route("/submit", post(submit::submit_article))
+        // multipart/form-data 投稿 -> POST /submit/multipart
+        .route("/submit/multipart", post(submit::submit_article_multipart))
+        // 维护模式下拒绝投稿
+        .route_layer(from_fn(maintenance::reject_in_maintenance))
+        // 查询后台处理状态 -> GET /submit/status/{id}，维护期间仍可查询
+        .route("/submit/status/{id}", get(submit::submission_status))
+        // 按投稿分支查询 PR 状态 -> GET /submit/status?branch=contrib-<uuid>
+        .route("/submit/status", get(submit::branch_pr_status))
+        // 预览渲染结果 -> POST /submit/preview，不创建分支与 PR
+        .route("/submit/preview", post(submit::preview_submission));
+
+    // 试运行校验 -> POST /submit/validate，仅在测试环境开启
+    let router = if AppConfig::global().submission.dry_run {
+        router.route("/submit/validate", post(submit::validate_submission))
+    } else {
+        router
+    };
+
+    // 投稿携带图片，单独放宽请求体上限
+    router.layer(upload_limit::submit_limit_layer())
 }
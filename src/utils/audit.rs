@@ -0,0 +1,180 @@
+use crate::config::AppConfig;
+use crate::middleware::background::{EnqueueError, submit_background};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use secrecy::ExposeSecret;
+use serde::Serialize;
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+static AUDIT: &str = "audit";
+
+/// 审计记录中的一条投稿，不含正文与图片
+#[derive(Debug, Clone, Serialize)]
+pub struct SubmissionAudit {
+    pub timestamp: DateTime<Utc>,
+    pub author: String,
+    /// 邮箱（去除首尾空白并转为小写）以 `audit.email_hash_key` 计算的 HMAC-SHA256，避免明文保存
+    pub email_hash: String,
+    pub title: String,
+    pub pr_url: String,
+    pub request_id: Uuid,
+}
+
+impl SubmissionAudit {
+    /// `key` 为计算邮箱摘要的密钥
+    pub fn new(
+        key: &[u8],
+        author: &str,
+        email: &str,
+        title: &str,
+        pr_url: &str,
+        request_id: Uuid,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            author: author.to_string(),
+            email_hash: hash_email(key, email),
+            title: title.to_string(),
+            pr_url: pr_url.to_string(),
+            request_id,
+        }
+    }
+}
+
+/// 以 `key` 计算邮箱的 HMAC-SHA256 摘要，同一邮箱得到相同的值，没有密钥无法用字典还原
+pub fn hash_email(key: &[u8], email: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(email.trim().to_lowercase().as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// 以 JSONL 格式追加一条记录，文件及其目录不存在时自动创建
+pub fn append_record(path: &Path, record: &SubmissionAudit) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("创建审计目录失败: {}", dir.display()))?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("打开审计文件失败: {}", path.display()))?;
+
+    // 先整行写入缓冲区再一次性落盘，避免并发追加时行被截断
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, record).context("序列化审计记录失败")?;
+    writer.write_all(b"\n").context("写入审计记录失败")?;
+    writer.flush().context("写入审计记录失败")?;
+    Ok(())
+}
+
+/// 在后台任务池中写入一条投稿的审计记录，未配置审计文件时直接跳过
+pub fn record_submission_background(
+    author: &str,
+    email: &str,
+    title: &str,
+    pr_url: &str,
+    request_id: Uuid,
+) -> Result<(), EnqueueError> {
+    let config = AppConfig::global();
+    // 加载配置时已保证设置了审计文件就有摘要密钥
+    let (Some(path), Some(key)) = (&config.audit.path, &config.audit.email_hash_key) else {
+        return Ok(());
+    };
+    let path = path.clone();
+    let record = SubmissionAudit::new(
+        key.expose_secret().as_bytes(),
+        author,
+        email,
+        title,
+        pr_url,
+        request_id,
+    );
+
+    submit_background(AUDIT, move || {
+        if let Err(e) = append_record(&path, &record) {
+            warn!(
+                "AUDIT_BG[{AUDIT}]: append to {} failed: {:#}",
+                path.display(),
+                e
+            );
+        } else {
+            info!(
+                "AUDIT_BG[{AUDIT}]: submission recorded, request_id={}",
+                record.request_id
+            );
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::Digest;
+
+    const KEY: &[u8] = b"audit-test-key";
+
+    #[test]
+    fn test_append_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("audit.jsonl");
+        let request_id = Uuid::new_v4();
+
+        let first = SubmissionAudit::new(
+            KEY,
+            "作者",
+            " Someone@Example.com ",
+            "标题",
+            "https://github.com/o/r/pull/1",
+            request_id,
+        );
+        append_record(&path, &first).unwrap();
+        let second = SubmissionAudit::new(KEY, "b", "b@example.com", "t", "u", Uuid::new_v4());
+        append_record(&path, &second).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let value: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        let obj = value.as_object().unwrap();
+        let mut keys: Vec<&str> = obj.keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "author",
+                "email_hash",
+                "pr_url",
+                "request_id",
+                "timestamp",
+                "title"
+            ]
+        );
+        assert_eq!(obj["author"], "作者");
+        assert_eq!(obj["title"], "标题");
+        assert_eq!(obj["pr_url"], "https://github.com/o/r/pull/1");
+        assert_eq!(obj["request_id"], request_id.to_string());
+        assert_eq!(obj["email_hash"], hash_email(KEY, "someone@example.com"));
+        assert!(!content.contains("Example.com"));
+    }
+
+    #[test]
+    fn test_hash_email_depends_on_key() {
+        let hash = hash_email(KEY, "someone@example.com");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_email(KEY, " Someone@Example.com "));
+        // 不知道密钥时无法通过对常见邮箱求摘要来还原
+        assert_ne!(hash, hash_email(b"other-key", "someone@example.com"));
+        assert_ne!(
+            hash,
+            format!("{:x}", sha2::Sha256::digest("someone@example.com"))
+        );
+    }
+}
@@ -1,28 +1,432 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, AuthConfig, SmtpTls};
+use crate::utils::messages::{self, Lang, Msg};
 use anyhow::{Context, Result};
-use lettre::message::Mailbox;
+use arc_swap::ArcSwap;
+use futures_util::future::BoxFuture;
+use lettre::message::{Mailbox, MessageBuilder, MultiPart};
 use lettre::transport::smtp::authentication::Credentials;
-use lettre::{Message, SmtpTransport, Transport};
+use lettre::transport::smtp::{AsyncSmtpTransportBuilder, SmtpTransportBuilder};
+use lettre::{
+    Address, AsyncSmtpTransport, AsyncTransport, Message, SmtpTransport, Tokio1Executor, Transport,
+};
 use once_cell::sync::Lazy;
 use secrecy::ExposeSecret;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+/// 校验邮箱地址语法（使用 lettre 的地址解析）
+pub fn is_valid_email(email: &str) -> bool {
+    email.parse::<Address>().is_ok()
+}
+
+/// 转义 HTML 特殊字符，用于把用户输入嵌入 HTML 邮件
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 按配置的模板生成验证码邮件的主题与正文
+///
+/// 模板为空时使用 `lang` 对应的默认文案；配置的模板对所有语言生效
+pub fn render_code_mail(
+    auth: &AuthConfig,
+    code: &str,
+    ttl_minutes: i64,
+    lang: Lang,
+) -> (String, String) {
+    let ttl_minutes = ttl_minutes.to_string();
+    let args = [("code", code), ("ttl_minutes", ttl_minutes.as_str())];
+    let render = |template: &str, default: Msg| {
+        if template.is_empty() {
+            messages::render(lang, default, &args)
+        } else {
+            messages::fill(template, &args)
+        }
+    };
+    (
+        render(&auth.code_subject_template, Msg::CodeSubject),
+        render(&auth.code_body_template, Msg::CodeBody),
+    )
+}
+
+/// 一封纯文本邮件的收件人、回复地址与内容
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SendParams<'a> {
+    /// 收件人，为空时只发给抄送与密送地址
+    pub to: Option<&'a str>,
+    pub cc: &'a [String],
+    /// 密送地址不会出现在邮件头中
+    pub bcc: &'a [String],
+    /// 收件人点击回复时使用的地址，为空时回复发件人
+    pub reply_to: Option<&'a str>,
+    pub subject: &'a str,
+    pub body: &'a str,
+}
+
+impl SendParams<'_> {
+    /// 日志中显示的收件人
+    pub fn recipients(&self) -> String {
+        self.to
+            .into_iter()
+            .chain(self.cc.iter().map(String::as_str))
+            .chain(self.bcc.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
 
 pub trait Mailer: Send + Sync {
-    fn send(&self, to: &str, subject: &str, body: &str) -> Result<()>;
+    /// 发送纯文本邮件，可同时抄送、密送并指定回复地址
+    fn send_full(&self, params: SendParams<'_>) -> Result<()>;
+
+    /// 发送给单个收件人的纯文本邮件
+    fn send(&self, to: &str, subject: &str, body: &str) -> Result<()> {
+        self.send_full(SendParams {
+            to: Some(to),
+            subject,
+            body,
+            ..Default::default()
+        })
+    }
+
+    /// 发送 HTML 邮件，`text_fallback` 供不支持 HTML 的客户端显示
+    ///
+    /// 默认只发送纯文本部分
+    fn send_html(&self, to: &str, subject: &str, html: &str, text_fallback: &str) -> Result<()> {
+        let _ = html;
+        self.send(to, subject, text_fallback)
+    }
+
+    /// 发送验证码邮件，主题与正文来自 `auth` 中配置的模板或 `lang` 对应的默认文案
+    fn send_code(&self, to: &str, code: &str, ttl_minutes: i64, lang: Lang) -> Result<()> {
+        let (subject, body) = render_code_mail(&AppConfig::global().auth, code, ttl_minutes, lang);
+        self.send(to, &subject, &body).context("发送验证码邮件失败")
+    }
+}
+
+fn mailbox(address: &str, label: &str) -> Result<Mailbox> {
+    address
+        .parse::<Mailbox>()
+        .with_context(|| format!("{}无效: {}", label, address))
+}
+
+/// 填好发件人、收件人、回复地址与主题的消息构造器
+fn message_builder(from: &str, params: &SendParams<'_>) -> Result<MessageBuilder> {
+    let mut builder = Message::builder()
+        .from(mailbox(from, "发件人邮箱地址")?)
+        .subject(params.subject);
+    if let Some(to) = params.to {
+        builder = builder.to(mailbox(to, "收件人邮箱地址")?);
+    }
+    for cc in params.cc {
+        builder = builder.cc(mailbox(cc, "抄送地址")?);
+    }
+    for bcc in params.bcc {
+        builder = builder.bcc(mailbox(bcc, "密送地址")?);
+    }
+    if let Some(reply_to) = params.reply_to {
+        builder = builder.reply_to(mailbox(reply_to, "回复地址")?);
+    }
+    Ok(builder)
+}
+
+/// 只有收件人与主题的邮件参数
+fn simple_params<'a>(to: &'a str, subject: &'a str) -> SendParams<'a> {
+    SendParams {
+        to: Some(to),
+        subject,
+        ..Default::default()
+    }
+}
+
+/// 发送失败后的重试策略
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// 最大发送次数（含首次），不大于 1 时不重试
+    pub attempts: u32,
+    /// 首次重试前的等待，之后每次翻倍
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// 只发送一次，不重试
+    pub const NONE: RetryPolicy = RetryPolicy {
+        attempts: 1,
+        base_delay: Duration::ZERO,
+    };
+
+    /// 第 `attempt` 次失败后的等待时间
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+/// 区分值得重试的临时性故障与重试也无济于事的永久性错误
+pub trait RetryableError {
+    fn is_retryable(&self) -> bool;
+}
+
+impl RetryableError for lettre::transport::smtp::Error {
+    /// 4xx 响应与连接、网络错误可重试；5xx（如收件人不存在）、
+    /// 响应解析、客户端及 TLS 错误重试也不会成功；超时的发送可能已被服务器接收，重试会导致重复投递
+    fn is_retryable(&self) -> bool {
+        !(self.is_permanent()
+            || timed_out(self)
+            || self.is_client()
+            || self.is_response()
+            || self.is_tls()
+            || self.is_transport_shutdown())
+    }
+}
 
-    fn send_code(&self, to: &str, code: &str) -> Result<()> {
-        let subject = "您的验证码";
-        let body = format!("您的验证码是：{}\n有效期 5 分钟，请勿泄露。", code);
-        self.send(to, subject, &body).context("发送验证码邮件失败")
+/// 发送是否因超时失败，命令读写超时由 lettre 作为网络错误返回
+fn timed_out(e: &lettre::transport::smtp::Error) -> bool {
+    e.is_timeout()
+        || std::error::Error::source(e)
+            .and_then(|source| source.downcast_ref::<std::io::Error>())
+            .is_some_and(|io| {
+                matches!(
+                    io.kind(),
+                    std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+                )
+            })
+}
+
+/// 单次发送的结果
+enum Attempt {
+    Sent,
+    Retry(anyhow::Error),
+    Fail(anyhow::Error),
+}
+
+/// 按加密方式选择 SMTP transport 构造器，端口覆盖各方式的默认端口
+fn smtp_builder(host: &str, port: u16, tls: SmtpTls) -> Result<SmtpTransportBuilder> {
+    let builder = match tls {
+        SmtpTls::Implicit => SmtpTransport::relay(host),
+        SmtpTls::StartTls => SmtpTransport::starttls_relay(host),
+        SmtpTls::None => Ok(SmtpTransport::builder_dangerous(host)),
     }
+    .with_context(|| format!("SMTP 服务器地址无效: {}", host))?;
+    Ok(builder.port(port))
+}
+
+/// [`smtp_builder`] 的异步 transport 版本
+fn async_smtp_builder(host: &str, port: u16, tls: SmtpTls) -> Result<AsyncSmtpTransportBuilder> {
+    let builder = match tls {
+        SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+        SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+        SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            host,
+        )),
+    }
+    .with_context(|| format!("SMTP 服务器地址无效: {}", host))?;
+    Ok(builder.port(port))
+}
+
+static GLOBAL_MAILER: Lazy<ArcSwap<SmtpMailer>> =
+    Lazy::new(|| ArcSwap::from_pointee(SmtpMailer::foreground().expect("初始化 SMTP Mailer 失败")));
+
+static BACKGROUND_MAILER: Lazy<ArcSwap<SmtpMailer>> = Lazy::new(|| {
+    ArcSwap::from_pointee(SmtpMailer::for_background().expect("初始化 SMTP Mailer 失败"))
+});
+
+static ASYNC_MAILER: Lazy<ArcSwap<AsyncSmtpMailer>> = Lazy::new(|| {
+    ArcSwap::from_pointee(AsyncSmtpMailer::new().expect("初始化异步 SMTP Mailer 失败"))
+});
+
+/// 按当前配置重新创建全局 SMTP 单例，重新加载配置后调用
+///
+/// 全部创建成功才替换，任一失败时保留原有实例；已取得旧实例的调用方不受影响
+pub fn reload_mailers() -> Result<()> {
+    let global = SmtpMailer::foreground()?;
+    let background = SmtpMailer::for_background()?;
+    let async_mailer = AsyncSmtpMailer::new()?;
+    GLOBAL_MAILER.store(Arc::new(global));
+    BACKGROUND_MAILER.store(Arc::new(background));
+    ASYNC_MAILER.store(Arc::new(async_mailer));
+    Ok(())
 }
 
-pub struct SmtpMailer {
-    transport: SmtpTransport,
+pub struct SmtpMailer<T = SmtpTransport> {
+    transport: T,
     from: String,
+    retry: RetryPolicy,
 }
 
 impl SmtpMailer {
+    fn new(retry: RetryPolicy) -> Result<Self> {
+        let cfg = AppConfig::global();
+
+        let creds = Credentials::new(
+            cfg.smtp.username.clone(),
+            cfg.smtp.password.expose_secret().to_string(),
+        );
+
+        // 连接超时同时作用于之后每条 SMTP 命令的读写，服务器无响应时发送在此时间内失败
+        let transport = smtp_builder(&cfg.smtp.host, cfg.smtp.port, cfg.smtp.tls)?
+            .credentials(creds)
+            .timeout(Some(Duration::from_secs(cfg.smtp.connect_timeout_secs)))
+            .build();
+
+        Ok(Self::with_transport(transport, cfg.smtp.username.clone()).with_retry(retry))
+    }
+
+    /// 按 `smtp.retry_*` 重试，用于需要立即得知结果的请求
+    fn foreground() -> Result<Self> {
+        let cfg = &AppConfig::global().smtp;
+        Self::new(RetryPolicy {
+            attempts: cfg.retry_attempts,
+            base_delay: Duration::from_millis(cfg.retry_base_delay_ms),
+        })
+    }
+
+    /// 按 `smtp.background_retry_*` 重试，用于后台任务
+    fn for_background() -> Result<Self> {
+        let cfg = &AppConfig::global().smtp;
+        Self::new(RetryPolicy {
+            attempts: cfg.background_retry_attempts,
+            base_delay: Duration::from_millis(cfg.background_retry_delay_ms),
+        })
+    }
+
+    /// 获取全局单例
+    pub fn global() -> Arc<Self> {
+        GLOBAL_MAILER.load_full()
+    }
+
+    /// 后台任务使用的单例，按 `smtp.background_retry_*` 重试
+    ///
+    /// 重试只在这里进行一层：超时与永久性错误不重发，退避等待只占用后台 worker
+    pub fn background() -> Arc<Self> {
+        BACKGROUND_MAILER.load_full()
+    }
+
+    /// 连接 SMTP 服务器完成握手与认证，再用 NOOP 确认连接可用，不发送邮件
+    pub fn test_connection(&self) -> Result<()> {
+        match self.transport.test_connection() {
+            Ok(true) => Ok(()),
+            Ok(false) => anyhow::bail!("SMTP 服务器未响应 NOOP"),
+            Err(e) => Err(anyhow::Error::new(e).context("连接 SMTP 服务器失败")),
+        }
+    }
+}
+
+impl<T> SmtpMailer<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: RetryableError + std::error::Error + Send + Sync + 'static,
+{
+    /// 使用给定的 transport 构造，发送超时由 transport 自身的 `timeout` 决定，默认不重试
+    pub fn with_transport(transport: T, from: String) -> Self {
+        Self {
+            transport,
+            from,
+            retry: RetryPolicy::NONE,
+        }
+    }
+
+    /// 设置临时性故障的重试策略
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 发送一封邮件，临时性故障按重试策略退避后重发
+    fn deliver(&self, to: &str, email: Message) -> Result<()> {
+        let mut attempt = 1;
+        loop {
+            match self.deliver_once(to, email.clone()) {
+                Attempt::Sent => return Ok(()),
+                Attempt::Retry(e) if attempt < self.retry.attempts => {
+                    let delay = self.retry.delay_after(attempt);
+                    warn!(
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        "MAIL: send to {} failed, retrying: {:#}",
+                        to,
+                        e
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                Attempt::Retry(e) | Attempt::Fail(e) => return Err(e),
+            }
+        }
+    }
+
+    fn deliver_once(&self, to: &str, email: Message) -> Attempt {
+        match self.transport.send(&email) {
+            Ok(_) => Attempt::Sent,
+            Err(e) => {
+                let retryable = e.is_retryable();
+                let e = anyhow::Error::new(e).context(format!("发送邮件至 {} 失败", to));
+                if retryable {
+                    Attempt::Retry(e)
+                } else {
+                    Attempt::Fail(e)
+                }
+            }
+        }
+    }
+}
+
+impl<T> Mailer for SmtpMailer<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: RetryableError + std::error::Error + Send + Sync + 'static,
+{
+    fn send_full(&self, params: SendParams<'_>) -> Result<()> {
+        let email = message_builder(&self.from, &params)?
+            .body(params.body.to_string())
+            .context("构建邮件消息失败")?;
+        self.deliver(&params.recipients(), email)
+    }
+
+    fn send_html(&self, to: &str, subject: &str, html: &str, text_fallback: &str) -> Result<()> {
+        let email = message_builder(&self.from, &simple_params(to, subject))?
+            .multipart(MultiPart::alternative_plain_html(
+                text_fallback.to_string(),
+                html.to_string(),
+            ))
+            .context("构建邮件消息失败")?;
+        self.deliver(to, email)
+    }
+}
+
+/// 异步发信接口，供请求处理中直接发送邮件，不占用 tokio 工作线程
+///
+/// 后台任务线程上没有运行时，仍使用同步的 [`Mailer`]
+pub trait AsyncMailer: Send + Sync {
+    fn send<'a>(
+        &'a self,
+        to: &'a str,
+        subject: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<()>>;
+}
+
+/// 基于 lettre 异步 transport 的 SMTP 发信，重试与超时策略与 [`SmtpMailer`] 一致
+pub struct AsyncSmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: String,
+    send_timeout: Duration,
+    retry: RetryPolicy,
+}
+
+impl AsyncSmtpMailer {
     fn new() -> Result<Self> {
         let cfg = AppConfig::global();
 
@@ -31,47 +435,400 @@ impl SmtpMailer {
             cfg.smtp.password.expose_secret().to_string(),
         );
 
-        let transport = SmtpTransport::relay(&cfg.smtp.host)
-            .with_context(|| format!("SMTP 服务器地址无效: {}", cfg.smtp.host))?
+        let transport = async_smtp_builder(&cfg.smtp.host, cfg.smtp.port, cfg.smtp.tls)?
             .credentials(creds)
+            .timeout(Some(Duration::from_secs(cfg.smtp.connect_timeout_secs)))
             .build();
 
         Ok(Self {
             transport,
             from: cfg.smtp.username.clone(),
+            send_timeout: Duration::from_secs(cfg.smtp.send_timeout_secs),
+            retry: RetryPolicy {
+                attempts: cfg.smtp.retry_attempts,
+                base_delay: Duration::from_millis(cfg.smtp.retry_base_delay_ms),
+            },
         })
     }
 
     /// 获取全局单例
     pub fn global() -> Arc<Self> {
-        static INSTANCE: Lazy<Arc<SmtpMailer>> =
-            Lazy::new(|| Arc::new(SmtpMailer::new().expect("初始化 SMTP Mailer 失败")));
-        INSTANCE.clone()
+        ASYNC_MAILER.load_full()
+    }
+
+    async fn deliver(&self, to: &str, email: Message) -> Result<()> {
+        let mut attempt = 1;
+        loop {
+            let result =
+                tokio::time::timeout(self.send_timeout, self.transport.send(email.clone())).await;
+            let e = match result {
+                Ok(Ok(_)) => return Ok(()),
+                Ok(Err(e)) => e,
+                // 超时的发送可能已被服务器接收，不再重试
+                Err(_) => anyhow::bail!(
+                    "发送邮件至 {} 超时（{} 秒）",
+                    to,
+                    self.send_timeout.as_secs()
+                ),
+            };
+
+            let retryable = e.is_retryable();
+            let e = anyhow::Error::new(e).context(format!("发送邮件至 {} 失败", to));
+            if !retryable || attempt >= self.retry.attempts {
+                return Err(e);
+            }
+
+            let delay = self.retry.delay_after(attempt);
+            warn!(
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "MAIL: send to {} failed, retrying: {:#}",
+                to,
+                e
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
     }
 }
 
-impl Mailer for SmtpMailer {
-    fn send(&self, to: &str, subject: &str, body: &str) -> Result<()> {
-        let from_mailbox = self
-            .from
-            .parse::<Mailbox>()
-            .with_context(|| format!("发件人邮箱地址无效: {}", self.from))?;
-
-        let to_mailbox = to
-            .parse::<Mailbox>()
-            .with_context(|| format!("收件人邮箱地址无效: {}", to))?;
-
-        let email = Message::builder()
-            .from(from_mailbox)
-            .to(to_mailbox)
-            .subject(subject)
-            .body(body.to_string())
-            .context("构建邮件消息失败")?;
+impl AsyncMailer for AsyncSmtpMailer {
+    fn send<'a>(
+        &'a self,
+        to: &'a str,
+        subject: &'a str,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let email = message_builder(&self.from, &simple_params(to, subject))?
+                .body(body.to_string())
+                .context("构建邮件消息失败")?;
+            self.deliver(to, email).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CodeKind;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Instant;
+
+    /// 只接受连接、从不应答的 SMTP 服务器
+    fn silent_server() -> (TcpListener, u16) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        (listener, port)
+    }
+
+    fn test_mailer(port: u16, command_timeout: Duration) -> SmtpMailer {
+        let transport = SmtpTransport::builder_dangerous("127.0.0.1")
+            .port(port)
+            .timeout(Some(command_timeout))
+            .build();
+        SmtpMailer::with_transport(transport, "from@example.com".to_string())
+    }
+
+    #[test]
+    fn test_send_fails_fast_on_silent_server() {
+        let (_listener, port) = silent_server();
+
+        // 由 transport 的命令超时结束发送，不在后台遗留发送线程
+        let start = Instant::now();
+        let mailer = test_mailer(port, Duration::from_millis(500)).with_retry(RetryPolicy {
+            attempts: 3,
+            base_delay: Duration::from_millis(10),
+        });
+        let err = mailer.send("to@example.com", "s", "b").unwrap_err();
+        let timed_out = err
+            .downcast_ref::<lettre::transport::smtp::Error>()
+            .is_some_and(timed_out);
+        assert!(timed_out, "{:#}", err);
+        // 超时不重试，只等待一次命令超时
+        assert!(start.elapsed() < Duration::from_millis(1500));
+    }
+
+    /// 前若干次发送失败的模拟 transport
+    #[derive(Clone)]
+    struct FlakyTransport {
+        failures: Arc<AtomicU32>,
+        calls: Arc<AtomicU32>,
+        retryable: bool,
+    }
+
+    impl FlakyTransport {
+        fn new(failures: u32, retryable: bool) -> Self {
+            Self {
+                failures: Arc::new(AtomicU32::new(failures)),
+                calls: Arc::new(AtomicU32::new(0)),
+                retryable,
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct FlakyError(bool);
+
+    impl std::fmt::Display for FlakyError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "mock transport failure (retryable = {})", self.0)
+        }
+    }
 
-        self.transport
-            .send(&email)
-            .with_context(|| format!("发送邮件至 {} 失败", to))?;
+    impl std::error::Error for FlakyError {}
+
+    impl RetryableError for FlakyError {
+        fn is_retryable(&self) -> bool {
+            self.0
+        }
+    }
+
+    impl Transport for FlakyTransport {
+        type Ok = ();
+        type Error = FlakyError;
+
+        fn send_raw(
+            &self,
+            _envelope: &lettre::address::Envelope,
+            _email: &[u8],
+        ) -> Result<(), FlakyError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let remaining = self.failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                return Err(FlakyError(self.retryable));
+            }
+            Ok(())
+        }
+    }
+
+    fn flaky_mailer(transport: FlakyTransport, attempts: u32) -> SmtpMailer<FlakyTransport> {
+        SmtpMailer::with_transport(transport, "from@example.com".to_string()).with_retry(
+            RetryPolicy {
+                attempts,
+                base_delay: Duration::from_millis(10),
+            },
+        )
+    }
+
+    #[test]
+    fn test_retry_transient_failures() {
+        let transport = FlakyTransport::new(2, true);
+        let mailer = flaky_mailer(transport.clone(), 3);
+        mailer.send("to@example.com", "s", "b").unwrap();
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 3);
+
+        // 超过最大次数后放弃
+        let transport = FlakyTransport::new(5, true);
+        let mailer = flaky_mailer(transport.clone(), 3);
+        assert!(mailer.send("to@example.com", "s", "b").is_err());
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_permanent_failure_is_not_retried() {
+        let transport = FlakyTransport::new(1, false);
+        let mailer = flaky_mailer(transport.clone(), 3);
+        assert!(mailer.send("to@example.com", "s", "b").is_err());
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_builder_follows_tls_mode() {
+        let cases = [
+            (SmtpTls::Implicit, 465, "Wrapper"),
+            (SmtpTls::StartTls, 587, "Required"),
+            (SmtpTls::None, 25, "None"),
+        ];
+        for (tls, port, expected) in cases {
+            let sync_debug = format!("{:?}", smtp_builder("smtp.example.com", port, tls).unwrap());
+            let async_debug = format!(
+                "{:?}",
+                async_smtp_builder("smtp.example.com", port, tls).unwrap()
+            );
+            for debug in [sync_debug, async_debug] {
+                assert!(debug.contains(&format!("port: {}", port)), "{}", debug);
+                assert!(
+                    debug.contains(&format!("tls: {}", expected)),
+                    "{:?}: {}",
+                    tls,
+                    debug
+                );
+            }
+        }
+
+        // 端口可以与加密方式的默认端口不同
+        let debug = format!(
+            "{:?}",
+            smtp_builder("smtp.example.com", 2525, SmtpTls::StartTls).unwrap()
+        );
+        assert!(debug.contains("port: 2525"), "{}", debug);
+    }
+
+    fn auth_config(subject: &str, body: &str) -> AuthConfig {
+        AuthConfig {
+            code_len: 6,
+            code_kind: CodeKind::Numeric,
+            max_attempts: 5,
+            send_cooldown_secs: 60,
+            code_subject_template: subject.to_string(),
+            code_body_template: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_code_mail_template() {
+        let auth = auth_config(
+            "Your code: {code}",
+            "Code {code} expires in {ttl_minutes} minutes. ({code})",
+        );
+        let (subject, body) = render_code_mail(&auth, "123456", 10, Lang::Zh);
+        assert_eq!(subject, "Your code: 123456");
+        assert_eq!(body, "Code 123456 expires in 10 minutes. (123456)");
+
+        // 未配置模板时沿用默认文案
+        let (subject, body) = render_code_mail(&auth_config("", ""), "123456", 5, Lang::Zh);
+        assert_eq!(subject, "您的验证码");
+        assert_eq!(body, "您的验证码是：123456\n有效期 5 分钟，请勿泄露。");
+        let (subject, body) = render_code_mail(&auth_config("", ""), "123456", 5, Lang::En);
+        assert_eq!(subject, "Your verification code");
+        assert!(body.starts_with("Your verification code is: 123456\n"));
+    }
+
+    #[test]
+    fn test_message_cc_bcc_reply_to() {
+        let cc = vec!["cc@example.com".to_string()];
+        let bcc = vec!["a@example.com".to_string(), "b@example.com".to_string()];
+        let params = SendParams {
+            to: None,
+            cc: &cc,
+            bcc: &bcc,
+            reply_to: Some("author@example.com"),
+            subject: "s",
+            body: "b",
+        };
+        let email = message_builder("from@example.com", &params)
+            .unwrap()
+            .body("b".to_string())
+            .unwrap();
+
+        // 密送地址只出现在信封中
+        let headers = String::from_utf8(email.formatted()).unwrap();
+        assert!(headers.contains("Cc: cc@example.com"), "{}", headers);
+        assert!(
+            headers.contains("Reply-To: author@example.com"),
+            "{}",
+            headers
+        );
+        assert!(
+            !headers.contains("Bcc") && !headers.contains("To: a@"),
+            "{}",
+            headers
+        );
+        let envelope: Vec<String> = email
+            .envelope()
+            .to()
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert_eq!(
+            envelope,
+            ["cc@example.com", "a@example.com", "b@example.com"]
+        );
+        assert_eq!(
+            params.recipients(),
+            "cc@example.com, a@example.com, b@example.com"
+        );
+
+        let invalid = SendParams {
+            reply_to: Some("not-an-email"),
+            ..params
+        };
+        let err = message_builder("from@example.com", &invalid).unwrap_err();
+        assert!(err.to_string().contains("回复地址无效"), "{}", err);
+    }
+
+    impl RetryableError for lettre::transport::stub::Error {
+        fn is_retryable(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_contributor_html_mail() {
+        use crate::utils::github::Submission;
+        use crate::utils::picture::RawImage;
+        use lettre::transport::stub::StubTransport;
+
+        crate::config::test_global();
+        let submission = Submission::new(
+            "<作者>".to_string(),
+            "author@example.com".to_string(),
+            "标题".to_string(),
+            vec!["科幻".to_string()],
+            "正文".to_string(),
+            RawImage {
+                name: "cover.png".to_string(),
+                bytes: vec![1, 2, 3],
+            },
+            vec![],
+        );
+        let url = "https://github.com/o/r/pull/1";
+        let html = submission.to_contributor_html(url);
+        let text = submission.to_contributor(url);
+        assert!(html.contains(&format!(r#"<a href="{url}">"#)), "{}", html);
+        // 用户输入在 HTML 中被转义
+        assert!(html.contains("&lt;作者&gt;"), "{}", html);
+        assert!(text.contains(url) && text.contains("<作者>"), "{}", text);
+
+        let mailer =
+            SmtpMailer::with_transport(StubTransport::new_ok(), "from@example.com".to_string());
+        mailer
+            .send_html(&submission.email, &submission.to_title(), &html, &text)
+            .unwrap();
+
+        let messages = mailer.transport.messages();
+        assert_eq!(messages.len(), 1);
+        let (envelope, raw) = &messages[0];
+        assert_eq!(
+            envelope.to(),
+            ["author@example.com".parse::<Address>().unwrap()]
+        );
+        // 同时包含纯文本与 HTML 两个部分
+        assert!(raw.contains("multipart/alternative"), "{}", raw);
+        assert!(raw.contains("text/plain"), "{}", raw);
+        assert!(raw.contains("text/html"), "{}", raw);
+    }
+
+    #[test]
+    fn test_send_html_defaults_to_text() {
+        /// 只实现了纯文本发送的 Mailer
+        struct TextOnly(std::sync::Mutex<Vec<String>>);
+
+        impl Mailer for TextOnly {
+            fn send_full(&self, params: SendParams<'_>) -> Result<()> {
+                self.0.lock().unwrap().push(params.body.to_string());
+                Ok(())
+            }
+        }
+
+        let mailer = TextOnly(std::sync::Mutex::new(Vec::new()));
+        mailer
+            .send_html("to@example.com", "s", "<p>html</p>", "text")
+            .unwrap();
+        assert_eq!(*mailer.0.lock().unwrap(), ["text"]);
+    }
 
-        Ok(())
+    #[test]
+    fn test_retry_delay_doubles() {
+        let policy = RetryPolicy {
+            attempts: 4,
+            base_delay: Duration::from_millis(500),
+        };
+        assert_eq!(policy.delay_after(1), Duration::from_millis(500));
+        assert_eq!(policy.delay_after(2), Duration::from_secs(1));
+        assert_eq!(policy.delay_after(3), Duration::from_secs(2));
     }
 }
@@ -2,21 +2,24 @@ use crate::config::AppConfig;
 use crate::middleware::mem_map::{MemMap, ToKey};
 use crate::to_key;
 
-use crate::utils::stream::file_stream_with_md5;
+use crate::utils::http;
+use crate::utils::stream::{StreamDigest, file_stream_with_digests};
+use crate::utils::upload::UploadBackend;
 use anyhow::{Context, Result, anyhow};
-use bytes::Bytes;
-use chrono::{Duration, Utc};
-use futures_util::Stream;
-use reqwest::{Body, Client, multipart};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
-use tokio::{fs, io};
+use std::path::{Path, PathBuf};
+use tokio::fs;
 use tracing::{debug, info, warn, error, instrument};
 
-// 缓存时间常量
-// 列表10分钟更新
-const LIST_TTL: Duration = Duration::minutes(10);
-// 文件3天更新
-const FILE_TTL: Duration = Duration::days(3);
+// 检查缓存下载链接是否可访问的超时时间
+const LINK_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 文件列表的缓存时间
+fn list_ttl() -> Duration {
+    Duration::seconds(AppConfig::global().file_share.list_ttl_secs)
+}
 
 fn validate_filename_only(input: &str) -> Result<String, &'static str> {
     let s = input.trim();
@@ -40,8 +43,21 @@ fn validate_filename_only(input: &str) -> Result<String, &'static str> {
     Ok(s.to_string())
 }
 
+/// 判断文件扩展名是否在允许分享的列表中，列表为空时全部允许
+fn is_allowed_extension(file_name: &str, allowed: &[String]) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    let Some((_, ext)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    allowed
+        .iter()
+        .any(|a| a.trim_start_matches('.').eq_ignore_ascii_case(ext))
+}
+
 /// 缓存中存储的文件信息
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct ShareFile {
     pub file_name: String,
@@ -51,6 +67,10 @@ pub struct ShareFile {
     pub size: u64,
     pub mime_type: String,
     pub md5: String,
+    pub sha256: String,
+    /// 下载链接失效的时间戳；旧快照中缺失时视为已失效
+    #[serde(default)]
+    pub expires_at: i64,
 }
 
 /// 文件缓存 Key
@@ -66,6 +86,14 @@ impl ShareFileKey {
             file_name: file_name.to_string(),
         }
     }
+
+    /// 本地计算的文件信息使用单独的 Key
+    pub fn info(file_name: &str) -> Self {
+        Self {
+            module: "ShareFileInfo",
+            file_name: file_name.to_string(),
+        }
+    }
 }
 to_key!(ShareFileKey; module=module; file_name);
 
@@ -82,36 +110,85 @@ impl ShareFileListKey {
             second_module: "List",
         }
     }
+
+    /// 带文件详情的列表使用单独的 Key
+    pub fn detailed() -> Self {
+        Self {
+            module: "ShareFile",
+            second_module: "DetailedList",
+        }
+    }
 }
 to_key!(ShareFileListKey; module=module; second_module);
 
-/// tmpfile.link 上传返回结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TmpfileResponse {
-    #[serde(rename = "fileName")]
+/// 文件列表中单个文件的详情，直接读取本地元数据，不涉及上传
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareFileEntry {
+    pub file_name: String,
+    pub size: u64,
+    pub mime_type: String,
+    /// 最后修改时间戳（秒），无法读取时为 0
+    pub modified: i64,
+}
+
+/// 文件列表的排序字段
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSort {
+    #[default]
+    Name,
+    Mtime,
+}
+
+/// 文件列表的排序方向
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// 按指定字段与方向排序；修改时间相同的文件始终按文件名升序排列，保证顺序稳定
+pub fn sort_entries(entries: &mut [ShareFileEntry], sort: ListSort, order: SortOrder) {
+    entries.sort_by(|a, b| {
+        let primary = match sort {
+            ListSort::Name => a.file_name.cmp(&b.file_name),
+            ListSort::Mtime => a.modified.cmp(&b.modified),
+        };
+        let primary = match order {
+            SortOrder::Asc => primary,
+            SortOrder::Desc => primary.reverse(),
+        };
+        primary.then_with(|| a.file_name.cmp(&b.file_name))
+    });
+}
+
+/// 供下载者校验文件的信息，不包含下载链接
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareFileInfo {
     pub file_name: String,
-    #[serde(rename = "downloadLink")]
-    pub download_link: String,
-    #[serde(rename = "downloadLinkEncoded")]
-    pub download_link_encoded: String,
     pub size: u64,
-    #[serde(rename = "type")]
     pub mime_type: String,
-    #[serde(rename = "uploadedTo")]
-    pub uploaded_to: String,
+    pub md5: String,
+    pub sha256: String,
+}
+
+impl From<&ShareFile> for ShareFileInfo {
+    fn from(file: &ShareFile) -> Self {
+        Self {
+            file_name: file.file_name.clone(),
+            size: file.size,
+            mime_type: file.mime_type.clone(),
+            md5: file.md5.clone(),
+            sha256: file.sha256.clone(),
+        }
+    }
 }
 
 impl ShareFile {
-    /// 从缓存或本地文件读取元数据
-    /// 从缓存或本地文件读取元数据
-    #[instrument(
-        name = "sharefile_get",
-        fields(
-            module = "sharefile",
-            file   = %file_name,
-        )
-    )]
-    pub async fn get(file_name: &str) -> Result<Self> {
+    /// 校验请求的文件名：必须在分享列表中、不含路径且扩展名允许分享
+    async fn checked_name(file_name: &str) -> Result<String> {
         let allowed = Self::list().await?;
         if !allowed.contains(&file_name.to_string()) {
             warn!("SHAREFILE_GET: illegal file request: {}", file_name);
@@ -124,17 +201,164 @@ impl ShareFile {
         let safe_name =
             validate_filename_only(file_name).map_err(|msg| anyhow::anyhow!(msg))?;
 
-        // 检查缓存
+        let config = AppConfig::global();
+        if !is_allowed_extension(&safe_name, &config.file_share.allowed_extensions) {
+            warn!("SHAREFILE_GET: extension not allowed: {}", safe_name);
+            return Err(anyhow!("该类型的文件不允许分享: {}", safe_name));
+        }
+        Ok(safe_name)
+    }
+
+    /// 获取文件大小与摘要，不会上传文件
+    ///
+    /// 已上传过的文件直接使用缓存中的摘要，否则在本地计算并缓存
+    #[instrument(
+        name = "sharefile_info",
+        fields(
+            module = "sharefile",
+            file   = %file_name,
+        )
+    )]
+    pub async fn info(file_name: &str) -> Result<ShareFileInfo> {
+        let safe_name = Self::checked_name(file_name).await?;
+
         let cache = MemMap::global();
-        let file_key = ShareFileKey::new(&safe_name);
-        if let Some(v) = cache.get::<ShareFileKey, ShareFile>(&file_key) {
-            debug!("SHAREFILE_GET: cache hit for {}", safe_name);
-            return Ok(v);
+        if let Some(file) = cache.get::<_, Self>(&ShareFileKey::new(&safe_name)) {
+            return Ok(ShareFileInfo::from(&file));
         }
-        debug!("SHAREFILE_GET: cache miss for {}, reading from disk", safe_name);
 
         let config = AppConfig::global();
+        let ttl = Duration::seconds(config.file_share.file_ttl_secs);
+        let file_path = config.file_share.path.join(&safe_name);
+        cache
+            .try_get_or_insert_with(ShareFileKey::info(&safe_name), ttl, || {
+                Self::local_info(&file_path, &safe_name)
+            })
+            .await
+    }
+
+    /// 在本地读取文件并计算摘要
+    async fn local_info(file_path: &PathBuf, safe_name: &str) -> Result<ShareFileInfo> {
+        debug!("SHAREFILE_INFO: computing digests for {}", safe_name);
+
+        let size = fs::metadata(file_path)
+            .await
+            .with_context(|| format!("读取文件信息失败: {}", file_path.display()))?
+            .len();
+
+        let (mut stream, digest_handle) =
+            file_stream_with_digests(file_path, &[StreamDigest::Md5, StreamDigest::Sha256])
+                .await?;
+        while let Some(chunk) = stream.next().await {
+            chunk.with_context(|| format!("读取文件失败: {}", file_path.display()))?;
+        }
+
+        let digests = digest_handle.finalize()?;
+        Ok(ShareFileInfo {
+            file_name: safe_name.to_string(),
+            size,
+            mime_type: mime_guess::from_path(safe_name)
+                .first_or_octet_stream()
+                .to_string(),
+            md5: digests.md5.ok_or_else(|| anyhow!("缺少 md5 摘要"))?,
+            sha256: digests.sha256.ok_or_else(|| anyhow!("缺少 sha256 摘要"))?,
+        })
+    }
+
+    /// 从缓存或本地文件读取元数据，缓存未命中时通过 `backend` 上传
+    #[instrument(
+        name = "sharefile_get",
+        skip(backend),
+        fields(
+            module = "sharefile",
+            file   = %file_name,
+        )
+    )]
+    pub async fn get(file_name: &str, backend: &dyn UploadBackend) -> Result<Self> {
+        let safe_name = Self::checked_name(file_name).await?;
+        let config = AppConfig::global();
+
+        // 缓存未命中时读取并上传；并发请求同一文件时只上传一次
+        let ttl = Duration::seconds(config.file_share.file_ttl_secs);
         let file_path = config.file_share.path.join(&safe_name);
+        let verify_link = config.file_share.verify_cached_links;
+        Self::cached_or_load(ShareFileKey::new(&safe_name), ttl, verify_link, || {
+            Self::load(&file_path, &safe_name, backend)
+        })
+        .await
+    }
+
+    /// 下载链接是否已失效
+    pub fn is_expired(&self) -> bool {
+        Utc::now().timestamp() >= self.expires_at
+    }
+
+    /// 下载链接是否仍可访问：HEAD 请求返回非 2xx 时视为失效
+    ///
+    /// 请求本身失败（超时、网络错误）时无法判断，仍沿用缓存，避免远端抖动时反复上传
+    async fn link_alive(&self) -> bool {
+        match http::client()
+            .head(&self.download_link)
+            .timeout(LINK_CHECK_TIMEOUT)
+            .send()
+            .await
+        {
+            Ok(resp) => resp.status().is_success(),
+            Err(e) => {
+                warn!(
+                    "SHAREFILE_GET: link check failed for {}: {}",
+                    self.file_name, e
+                );
+                true
+            }
+        }
+    }
+
+    /// 读取缓存的文件信息；下载链接已失效时丢弃缓存，重新加载
+    ///
+    /// `verify_link` 为 true 时额外向下载链接发送 HEAD 请求确认远端文件仍在
+    async fn cached_or_load<F, Fut>(
+        key: ShareFileKey,
+        ttl: Duration,
+        verify_link: bool,
+        load: F,
+    ) -> Result<Self>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Self>>,
+    {
+        let cache = MemMap::global();
+        if let Some(cached) = cache.get::<_, Self>(&key) {
+            let stale = if cached.is_expired() {
+                info!(
+                    "SHAREFILE_GET: download link expired for {}, reloading",
+                    cached.file_name
+                );
+                true
+            } else if verify_link && !cached.link_alive().await {
+                warn!(
+                    "SHAREFILE_GET: download link dead for {}, reloading",
+                    cached.file_name
+                );
+                true
+            } else {
+                false
+            };
+            if stale {
+                cache.remove(&key);
+            }
+        }
+
+        cache.try_get_or_insert_with(key, ttl, load).await
+    }
+
+    /// 读取本地文件并通过上传后端上传，同时计算摘要
+    async fn load(
+        file_path: &PathBuf,
+        safe_name: &str,
+        backend: &dyn UploadBackend,
+    ) -> Result<Self> {
+        debug!("SHAREFILE_GET: cache miss for {}, reading from disk", safe_name);
 
         // 文件是否存在
         if !file_path.exists() {
@@ -142,20 +366,25 @@ impl ShareFile {
             return Err(anyhow!("文件不存在: {}", file_path.display()));
         }
 
-        // 1. 构造“带 md5 副作用”的流
-        let (stream, md5_handle) = file_stream_with_md5(&file_path).await?;
-        debug!("SHAREFILE_GET: stream with md5 created for {}", safe_name);
+        // 1. 构造“带摘要副作用”的流，同时计算 md5 与 sha256
+        let (stream, digest_handle) =
+            file_stream_with_digests(file_path, &[StreamDigest::Md5, StreamDigest::Sha256])
+                .await?;
+        debug!("SHAREFILE_GET: stream with digests created for {}", safe_name);
 
         // 2. 流式上传
-        let upload_info = Self::upload_stream_to_tmpfile(&safe_name, stream).await?;
+        let upload_info = backend.upload_stream(safe_name, stream.boxed()).await?;
         info!(
             "SHAREFILE_GET: upload completed, file={}, size={}",
             upload_info.file_name, upload_info.size
         );
 
-        // 3. 上传结束后再 finalize md5
-        let md5 = md5_handle.finalize()?;
-        debug!(%md5, "SHAREFILE_GET: md5 finalized");
+        // 3. 上传结束后再 finalize 摘要
+        let digests = digest_handle.finalize()?;
+        let md5 = digests.md5.ok_or_else(|| anyhow!("缺少 md5 摘要"))?;
+        let sha256 = digests.sha256.ok_or_else(|| anyhow!("缺少 sha256 摘要"))?;
+        // 摘要不写入日志
+        debug!("SHAREFILE_GET: digests finalized for {}", safe_name);
 
         let share_file = ShareFile {
             file_name: safe_name.to_string(),
@@ -165,59 +394,22 @@ impl ShareFile {
             size: upload_info.size,
             mime_type: upload_info.mime_type,
             md5,
+            sha256,
+            expires_at: upload_info.expires_at,
         };
-
-        // 更新到cache
-        cache.insert(file_key, share_file.clone(), FILE_TTL);
-        debug!("SHAREFILE_GET: cache updated for {}", share_file.file_name);
+        debug!("SHAREFILE_GET: file loaded for {}", share_file.file_name);
 
         Ok(share_file)
     }
 
-    /// 通过任意字节流上传到 tmpfile.link（流式）
-    #[instrument(
-        name = "sharefile_upload_stream",
-        skip(stream),
-        fields(
-            module   = "sharefile",
-            filename = %filename,
-        )
-    )]
-    pub async fn upload_stream_to_tmpfile<S>(
-        filename: &str,
-        stream: S,
-    ) -> Result<TmpfileResponse>
-    where
-        S: Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
-    {
-        debug!("SHAREFILE_UPLOAD: building request body");
-
-        // 用 stream 构造 reqwest Body
-        let body = Body::wrap_stream(stream);
-
-        // multipart 的 file part 使用 stream
-        let part = multipart::Part::stream(body)
-            .file_name(filename.to_string())
-            .mime_str("application/octet-stream")?;
-
-        let form = multipart::Form::new().part("file", part);
-        let client = Client::new();
-
-        debug!("SHAREFILE_UPLOAD: sending request to tmpfile.link");
-        let resp = client
-            .post("https://tmpfile.link/api/upload")
-            .multipart(form)
-            .send()
-            .await?
-            .error_for_status()?;
-
-        let tmp_resp = resp.json::<TmpfileResponse>().await?;
-        info!(
-            "SHAREFILE_UPLOAD: upload finished, remote_file={}, size={}",
-            tmp_resp.file_name, tmp_resp.size
-        );
+    /// 文件列表缓存的剩余有效时间
+    pub fn list_ttl_remaining() -> Option<Duration> {
+        MemMap::global().ttl_remaining(&ShareFileListKey::new())
+    }
 
-        Ok(tmp_resp)
+    /// 详细文件列表缓存的剩余有效时间
+    pub fn list_detailed_ttl_remaining() -> Option<Duration> {
+        MemMap::global().ttl_remaining(&ShareFileListKey::detailed())
     }
 
     /// 获取文件列表（带缓存）
@@ -226,43 +418,358 @@ impl ShareFile {
         fields(module = "sharefile")
     )]
     pub async fn list() -> Result<Vec<String>> {
-        let cache = MemMap::global();
-        let list_key = ShareFileListKey::new();
+        // 并发的未命中请求只扫描一次目录
+        MemMap::global()
+            .try_get_or_insert_with(ShareFileListKey::new(), list_ttl(), || async {
+                debug!("SHAREFILE_LIST: cache miss, reading directory");
+
+                let config = AppConfig::global();
+                let file_names =
+                    Self::scan_dir(&config.file_share.path, &config.file_share.allowed_extensions)
+                        .await?;
+
+                debug!(
+                    "SHAREFILE_LIST: directory scan finished, count={}",
+                    file_names.len()
+                );
+                Ok(file_names)
+            })
+            .await
+    }
 
-        if let Some(v) = cache.get::<ShareFileListKey, Vec<String>>(&list_key) {
-            debug!("SHAREFILE_LIST: cache hit, count={}", v.len());
-            return Ok(v);
-        }
-        debug!("SHAREFILE_LIST: cache miss, reading directory");
+    /// 获取带大小与 MIME 类型的文件列表（带缓存）
+    #[instrument(
+        name = "sharefile_list_detailed",
+        fields(module = "sharefile")
+    )]
+    pub async fn list_detailed() -> Result<Vec<ShareFileEntry>> {
+        MemMap::global()
+            .try_get_or_insert_with(ShareFileListKey::detailed(), list_ttl(), || async {
+                debug!("SHAREFILE_LIST: detailed cache miss, reading directory");
+
+                let config = AppConfig::global();
+                let files = Self::scan_dir_detailed(
+                    &config.file_share.path,
+                    &config.file_share.allowed_extensions,
+                )
+                .await?;
+
+                debug!(
+                    "SHAREFILE_LIST: detailed scan finished, count={}",
+                    files.len()
+                );
+                Ok(files)
+            })
+            .await
+    }
 
-        let config = AppConfig::global();
-        let dir_path = &config.file_share.path;
+    /// 扫描目录下允许分享的普通文件
+    async fn scan_dir(dir_path: &Path, allowed: &[String]) -> Result<Vec<String>> {
+        let entries = Self::scan_dir_detailed(dir_path, allowed).await?;
+        Ok(entries.into_iter().map(|e| e.file_name).collect())
+    }
 
+    /// 扫描目录下允许分享的普通文件，并读取大小与 MIME 类型
+    async fn scan_dir_detailed(
+        dir_path: &Path,
+        allowed: &[String],
+    ) -> Result<Vec<ShareFileEntry>> {
         let mut entries = fs::read_dir(dir_path)
             .await
             .with_context(|| format!("读取目录失败: {}", dir_path.display()))?;
 
-        let mut file_names = Vec::new();
+        let mut files = Vec::new();
 
         while let Some(entry) = entries.next_entry().await.context("读取目录项失败")? {
             let path = entry.path();
 
             if path.is_file()
                 && let Some(name) = path.file_name().and_then(|n| n.to_str())
+                && is_allowed_extension(name, allowed)
             {
-                file_names.push(name.to_string());
+                let metadata = entry
+                    .metadata()
+                    .await
+                    .with_context(|| format!("读取文件信息失败: {}", path.display()))?;
+                files.push(ShareFileEntry {
+                    file_name: name.to_string(),
+                    size: metadata.len(),
+                    mime_type: mime_guess::from_path(name)
+                        .first_or_octet_stream()
+                        .to_string(),
+                    modified: metadata
+                        .modified()
+                        .map(|t| DateTime::<Utc>::from(t).timestamp())
+                        .unwrap_or(0),
+                });
             }
         }
 
-        debug!(
-            "SHAREFILE_LIST: directory scan finished, count={}",
-            file_names.len()
+        // 目录遍历顺序不固定，排序后分页结果才稳定
+        files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::upload::{UploadResult, UploadStream};
+    use futures_util::future::BoxFuture;
+
+    #[test]
+    fn test_allowed_extensions() {
+        let allowed = vec!["pdf".to_string(), ".EPUB".to_string()];
+        assert!(is_allowed_extension("book.pdf", &allowed));
+        assert!(is_allowed_extension("Book.PDF", &allowed));
+        assert!(is_allowed_extension("novel.epub", &allowed));
+        assert!(!is_allowed_extension("server.key", &allowed));
+        assert!(!is_allowed_extension(".env", &allowed));
+        assert!(!is_allowed_extension("README", &allowed));
+
+        // 未配置时不限制
+        assert!(is_allowed_extension("server.key", &[]));
+    }
+
+    #[tokio::test]
+    async fn test_scan_dir_filters_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["book.pdf", "server.key", ".env"] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+
+        let allowed = vec!["pdf".to_string()];
+        let files = ShareFile::scan_dir(dir.path(), &allowed).await.unwrap();
+        assert_eq!(files, vec!["book.pdf".to_string()]);
+    }
+
+    fn share_file(name: &str, expires_at: i64) -> ShareFile {
+        ShareFile {
+            file_name: name.to_string(),
+            timestamp: 0,
+            download_link: format!("https://tmpfile.link/{}", expires_at),
+            download_link_encoded: String::new(),
+            size: 1,
+            mime_type: "application/pdf".to_string(),
+            md5: String::new(),
+            sha256: String::new(),
+            expires_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_cached_file_is_reloaded() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let name = "expired-link-test.pdf";
+        let fresh_expiry = (Utc::now() + Duration::hours(24)).timestamp();
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok(share_file(name, fresh_expiry))
+        };
+
+        // 缓存仍在有效期内，但下载链接已失效
+        MemMap::global().insert(
+            ShareFileKey::new(name),
+            share_file(name, Utc::now().timestamp() - 1),
+            Duration::hours(1),
         );
+        let key = || ShareFileKey::new(name);
+        let file = ShareFile::cached_or_load(key(), Duration::hours(1), false, load)
+            .await
+            .unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(file.expires_at, fresh_expiry);
+        assert!(!file.is_expired());
 
-        // 更新缓存
-        cache.insert(list_key, file_names.clone(), LIST_TTL);
-        debug!("SHAREFILE_LIST: cache updated");
+        // 链接未失效时直接使用缓存
+        let file = ShareFile::cached_or_load(key(), Duration::hours(1), false, load)
+            .await
+            .unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(file.expires_at, fresh_expiry);
+    }
+
+    #[tokio::test]
+    async fn test_dead_cached_link_is_reuploaded() {
+        use axum::Router;
+        use axum::http::StatusCode;
+        use axum::routing::get;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        crate::config::test_global();
+
+        // 模拟下载服务：/dead 已被远端清理，/live 仍可下载
+        let app = Router::new()
+            .route("/dead", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/live", get(|| async { "ok" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let name = "dead-link-test.pdf";
+        let future_expiry = (Utc::now() + Duration::hours(24)).timestamp();
+        let cached_with = |link: &str| ShareFile {
+            download_link: format!("{}/{}", base, link),
+            ..share_file(name, future_expiry)
+        };
+        let uploads = AtomicUsize::new(0);
+        let upload = || async {
+            uploads.fetch_add(1, Ordering::SeqCst);
+            Ok(cached_with("live"))
+        };
+        let get = |verify_link| {
+            let key = ShareFileKey::new(name);
+            ShareFile::cached_or_load(key, Duration::hours(1), verify_link, upload)
+        };
 
-        Ok(file_names)
+        // 未开启检查时直接信任缓存
+        MemMap::global().insert(
+            ShareFileKey::new(name),
+            cached_with("dead"),
+            Duration::hours(1),
+        );
+        let file = get(false).await.unwrap();
+        assert!(file.download_link.ends_with("/dead"));
+        assert_eq!(uploads.load(Ordering::SeqCst), 0);
+
+        // 开启检查后失效链接触发重新上传
+        let file = get(true).await.unwrap();
+        assert!(file.download_link.ends_with("/live"));
+        assert_eq!(uploads.load(Ordering::SeqCst), 1);
+
+        // 新链接可访问，继续使用缓存
+        let file = get(true).await.unwrap();
+        assert!(file.download_link.ends_with("/live"));
+        assert_eq!(uploads.load(Ordering::SeqCst), 1);
+    }
+
+    /// 在内存中收集上传内容并返回固定链接的上传后端
+    #[derive(Default)]
+    struct MockBackend {
+        received: std::sync::Mutex<Vec<u8>>,
+    }
+
+    impl UploadBackend for MockBackend {
+        fn upload_stream<'a>(
+            &'a self,
+            filename: &'a str,
+            mut stream: UploadStream,
+        ) -> BoxFuture<'a, Result<UploadResult>> {
+            Box::pin(async move {
+                let mut size = 0;
+                while let Some(chunk) = stream.next().await {
+                    let chunk = chunk?;
+                    size += chunk.len() as u64;
+                    self.received.lock().unwrap().extend_from_slice(&chunk);
+                }
+                Ok(UploadResult {
+                    file_name: filename.to_string(),
+                    download_link: format!("https://files.example.com/{}", filename),
+                    download_link_encoded: format!("https://files.example.com/{}", filename),
+                    size,
+                    mime_type: "text/plain".to_string(),
+                    expires_at: 4_102_444_800,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_uploads_through_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, b"hello world").unwrap();
+
+        let backend = MockBackend::default();
+        let file = ShareFile::load(&path, "notes.txt", &backend).await.unwrap();
+
+        assert_eq!(*backend.received.lock().unwrap(), b"hello world");
+        assert_eq!(file.download_link, "https://files.example.com/notes.txt");
+        assert_eq!(file.size, 11);
+        assert_eq!(file.md5, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        assert_eq!(file.expires_at, 4_102_444_800);
+        assert!(!file.is_expired());
+    }
+
+    #[tokio::test]
+    async fn test_local_info_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, b"hello world").unwrap();
+
+        let info = ShareFile::local_info(&path, "notes.txt").await.unwrap();
+        assert_eq!(info.size, 11);
+        assert_eq!(info.mime_type, "text/plain");
+        assert_eq!(info.md5, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        assert_eq!(
+            info.sha256,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_dir_detailed_reports_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"hello").unwrap();
+        std::fs::write(dir.path().join("book.pdf"), vec![0u8; 1024]).unwrap();
+        std::fs::write(dir.path().join("data.unknownext"), b"").unwrap();
+        std::fs::create_dir(dir.path().join("sub.pdf")).unwrap();
+
+        let files = ShareFile::scan_dir_detailed(dir.path(), &[]).await.unwrap();
+        let summary: Vec<_> = files
+            .iter()
+            .map(|f| (f.file_name.as_str(), f.size, f.mime_type.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("book.pdf", 1024, "application/pdf"),
+                ("data.unknownext", 0, "application/octet-stream"),
+                ("notes.txt", 5, "text/plain"),
+            ]
+        );
+        assert!(files.iter().all(|f| f.modified > 0));
+    }
+
+    /// 在临时目录中创建文件并设置修改时间
+    fn write_with_mtime(dir: &Path, name: &str, modified: i64) {
+        let file = std::fs::File::create(dir.join(name)).unwrap();
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(modified as u64);
+        file.set_modified(time).unwrap();
+    }
+
+    async fn sorted_names(dir: &Path, sort: ListSort, order: SortOrder) -> Vec<String> {
+        let mut files = ShareFile::scan_dir_detailed(dir, &[]).await.unwrap();
+        sort_entries(&mut files, sort, order);
+        files.into_iter().map(|f| f.file_name).collect()
+    }
+
+    #[tokio::test]
+    async fn test_sort_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        write_with_mtime(dir.path(), "b.pdf", 1_700_000_300);
+        write_with_mtime(dir.path(), "c.pdf", 1_700_000_100);
+        write_with_mtime(dir.path(), "a.pdf", 1_700_000_200);
+        write_with_mtime(dir.path(), "d.pdf", 1_700_000_200);
+
+        assert_eq!(
+            sorted_names(dir.path(), ListSort::Name, SortOrder::Asc).await,
+            ["a.pdf", "b.pdf", "c.pdf", "d.pdf"]
+        );
+        assert_eq!(
+            sorted_names(dir.path(), ListSort::Name, SortOrder::Desc).await,
+            ["d.pdf", "c.pdf", "b.pdf", "a.pdf"]
+        );
+        // 修改时间相同的 a、d 按文件名升序
+        assert_eq!(
+            sorted_names(dir.path(), ListSort::Mtime, SortOrder::Asc).await,
+            ["c.pdf", "a.pdf", "d.pdf", "b.pdf"]
+        );
+        assert_eq!(
+            sorted_names(dir.path(), ListSort::Mtime, SortOrder::Desc).await,
+            ["b.pdf", "a.pdf", "d.pdf", "c.pdf"]
+        );
     }
 }
@@ -1,24 +1,204 @@
-use crate::config::AppConfig;
+use crate::config::{AnimatedImagePolicy, AppConfig, ImageConfig, SubmissionConfig};
 use crate::handler::submit::SubmissionRequest;
-use crate::utils::markdown::{Markdown, ToHexo};
-use crate::utils::picture::Base64Image;
+use crate::response::FieldError;
+use crate::utils::email::{escape_html, is_valid_email};
+use crate::utils::http;
+use crate::utils::markdown::{Markdown, ToHexo, join_tags, url_slug};
+use crate::utils::messages::{self, Lang, Msg};
+use crate::utils::notify::{Notification, NotificationEvent};
+use crate::utils::picture::RawImage;
 use anyhow::{Context, Result, anyhow};
-use octocrab::Octocrab;
-use octocrab::models::repos::Object;
-use octocrab::params::repos::Reference;
+use axum::http::header::USER_AGENT;
+use axum::http::{Response, StatusCode};
+use base64::{Engine as _, engine::general_purpose};
+use chrono::Utc;
+use chrono::{DateTime, Local};
+use octocrab::{FromResponse, Octocrab};
 use secrecy::ExposeSecret;
-use urlencoding::encode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// 检查字符串能否安全地作为仓库路径中的一段
+///
+/// `#`、`?` 等字符即使经过百分号编码也可能让 GitHub 接口返回 422，这里提前拒绝
+pub fn check_path_segment(segment: &str) -> Result<(), String> {
+    if segment == "." || segment == ".." {
+        return Err("不能为 . 或 ..".to_string());
+    }
+    if let Some(c) = segment
+        .chars()
+        .find(|c| matches!(c, '#' | '?' | '/' | '\\') || c.is_control())
+    {
+        return Err(format!("包含不允许的字符 {:?}", c));
+    }
+    Ok(())
+}
+
+/// 规范化编辑指定的 slug：去除首尾空白、空白替换为 `-`，无法作为文件名时返回 None
+pub fn safe_post_slug(input: &str) -> Option<String> {
+    let slug = input.split_whitespace().collect::<Vec<_>>().join("-");
+    if slug.is_empty() || slug.starts_with('.') || check_path_segment(&slug).is_err() {
+        return None;
+    }
+    Some(slug)
+}
+
+/// 规范化投稿标签：去除首尾空白、转为小写，丢弃空标签并按首次出现的顺序去重
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// GitHub REST API 地址
+const GITHUB_API_BASE: &str = "https://api.github.com";
+
+/// 使用配置中的 PAT 构造 GitHub 客户端，请求带上统一的 User-Agent
+fn github_client() -> Result<Octocrab> {
+    let config = AppConfig::global();
+    build_github_client(
+        GITHUB_API_BASE,
+        config.github.personal_access_token.expose_secret(),
+        &http::user_agent(),
+    )
+}
+
+/// 构造访问 `base_uri` 的客户端；octocrab 固定发送的 `octocrab` 之外再附加配置的 User-Agent
+fn build_github_client(base_uri: &str, token: &str, user_agent: &str) -> Result<Octocrab> {
+    Octocrab::builder()
+        .base_uri(base_uri)?
+        .personal_token(token.to_string())
+        .add_header(USER_AGENT, user_agent.to_string())
+        .build()
+        .context("构建 Octocrab 客户端失败")
+}
+
+/// 从仓库地址中解析 `(owner, repo)`
+///
+/// 支持 `https://github.com/owner/repo`、`git@github.com:owner/repo.git`、
+/// `ssh://git@github.com/owner/repo` 以及末尾多余的 `/`
+pub fn parse_owner_repo(repo_url: &str) -> Result<(String, String)> {
+    let trimmed = repo_url.trim().trim_end_matches('/');
+    let trimmed = trimmed.strip_suffix(".git").unwrap_or(trimmed);
+
+    let path = match trimmed.split_once("://") {
+        // https://host/owner/repo、ssh://git@host/owner/repo
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        // git@host:owner/repo
+        None => trimmed.split_once(':').map_or("", |(_, path)| path),
+    };
+
+    let mut segments = path.split('/');
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some(owner), Some(repo), None) if !owner.is_empty() && !repo.is_empty() => {
+            Ok((owner.to_string(), repo.to_string()))
+        }
+        _ => Err(anyhow!("无法从仓库地址解析 owner/repo: {}", repo_url)),
+    }
+}
+
+/// 创建投稿 PR 时的附加设置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PullRequestOptions {
+    pub draft: bool,
+    pub labels: Vec<String>,
+    /// 请求审阅的 GitHub 用户名
+    pub reviewers: Vec<String>,
+}
+
+impl PullRequestOptions {
+    /// 从配置读取，审阅者为 `admin.email` 中配置了 GitHub 用户名的管理员
+    pub fn from_config(config: &AppConfig) -> Self {
+        let reviewers = config
+            .admin
+            .email
+            .iter()
+            .filter_map(|email| {
+                config
+                    .github
+                    .reviewers
+                    .iter()
+                    .find(|m| m.email.eq_ignore_ascii_case(email))
+                    .map(|m| m.github.clone())
+            })
+            .collect();
+        Self {
+            draft: config.github.is_draft,
+            labels: config.github.labels.clone(),
+            reviewers,
+        }
+    }
+}
+
+/// 待提交到仓库的单个文件
+pub struct RepoFile<'a> {
+    pub path: String,
+    pub content: Cow<'a, [u8]>,
+    /// 出错时用于描述该文件
+    pub label: String,
+}
+
+/// 投稿校验失败的单条原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ValidationError> for FieldError {
+    fn from(e: ValidationError) -> Self {
+        FieldError {
+            field: e.field.to_string(),
+            message: e.message,
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
 pub struct Submission {
     pub author: String,
     pub email: String,
     pub title: String,
     pub tags: Vec<String>,
     pub content: String,
-    pub cover: Base64Image,
-    pub images: Vec<Base64Image>,
+    pub cover: RawImage,
+    pub images: Vec<RawImage>,
     pub branch: String,
+    /// `branch` 是此前投稿已创建的分支，提交更新到该分支及其 PR，而不是新建
+    pub resubmission: bool,
+    /// 发给投稿人的邮件所用语言
+    pub lang: Lang,
+    pub tags_placeholder: String,
+    /// 编辑指定的 slug，用作文件名与资源目录；为空时使用标题
+    pub slug: Option<String>,
+    /// 写入 front-matter 的分类
+    pub categories: Vec<String>,
+    /// 投稿时间
+    pub submitted_at: DateTime<Local>,
 }
 
 impl Submission {
@@ -26,11 +206,35 @@ impl Submission {
         Markdown {
             author: self.author.clone(),
             title: self.title.clone(),
+            date: self.submitted_at,
+            slug: url_slug(&self.post_slug()),
+            categories: self.categories.clone(),
             tags: self.tags.clone(),
+            tags_placeholder: self.tags_placeholder.clone(),
+            cover: self.cover_name(),
+            photos: self
+                .image_names()
+                .iter()
+                .map(|name| format!("/photos/{}/{}", self.post_slug(), name))
+                .collect(),
             content: self.content.clone(),
         }
     }
 
+    /// 封面文件名，扩展名与图片实际格式一致
+    pub fn cover_name(&self) -> String {
+        format!("cover.{}", self.cover.extension())
+    }
+
+    /// 图集图片的文件名，按投稿顺序编号并补零，保证排序与重试结果一致
+    pub fn image_names(&self) -> Vec<String> {
+        self.images
+            .iter()
+            .enumerate()
+            .map(|(idx, img)| format!("{:03}.{}", idx + 1, img.extension()))
+            .collect()
+    }
+
     pub fn to_info(&self) -> String {
         let additional_images = if self.images.is_empty() {
             "无".to_string()
@@ -56,46 +260,114 @@ impl Submission {
             self.author,
             self.email,
             self.title,
-            self.tags.join(", "),
+            self.tags_text(", "),
             self.content.chars().count(),
             self.cover.name,
             additional_images,
             self.branch
         )
     }
+    /// 新投稿的管理员通知
+    pub fn to_notification(&self, pr_url: &str) -> Notification {
+        Notification {
+            event: NotificationEvent::Submission,
+            subject: self.to_title(),
+            body: self.to_info(),
+            url: Some(pr_url.to_string()),
+            reply_to: Some(self.email.clone()),
+        }
+    }
+
+    /// 拼接标签，空标签时使用配置的占位文本
+    pub fn tags_text(&self, sep: &str) -> String {
+        join_tags(&self.tags, sep, &self.tags_placeholder)
+    }
+
+    pub fn to_pr_body(&self) -> String {
+        format!(
+            "Automated submission from contribution form.\n\n\
+            **Title:** {}\n\
+            **Author:** {}\n\
+            **Email:** {}\n\
+            **Tags:** {}\n\
+            **Images:** {} (including cover)\n",
+            self.title,
+            self.author,
+            self.email,
+            self.tags_text(", "),
+            1 + self.images.len(),
+        )
+    }
+
     pub fn to_title(&self) -> String {
         format!("{}-{}-{}", self.author, self.email, self.title)
     }
 
+    /// 投稿确认邮件，按投稿人的语言选择文案
     pub fn to_contributor(&self, pr_url: &str) -> String {
-        format!(
-            r#"感谢您的投稿！
+        let tags = self.tags_text(messages::text(self.lang, Msg::TagSeparator));
+        messages::render(
+            self.lang,
+            Msg::ContributorText,
+            &[
+                ("title", &self.title),
+                ("author", &self.author),
+                ("tags", &tags),
+                ("email", &self.email),
+                ("url", pr_url),
+            ],
+        )
+    }
 
-投稿详情：
-- 文章标题：《{}》
-- 作者：{}
-- 标签：{}
-- 投稿邮箱：{}
+    /// 投稿确认邮件的 HTML 版本，内容与 `to_contributor` 一致，PR 地址可直接点击
+    pub fn to_contributor_html(&self, pr_url: &str) -> String {
+        let tags = self.tags_text(messages::text(self.lang, Msg::TagSeparator));
+        messages::render(
+            self.lang,
+            Msg::ContributorHtml,
+            &[
+                ("title", &escape_html(&self.title)),
+                ("author", &escape_html(&self.author)),
+                ("tags", &escape_html(&tags)),
+                ("email", &escape_html(&self.email)),
+                ("url", &escape_html(pr_url)),
+            ],
+        )
+    }
+}
 
-您的投稿已成功提交！我们已创建 GitHub Pull Request 进行审核处理。
+impl Submission {
+    /// 投稿在后台处理失败时的管理员通知，错误原因由调用方追加到 `body`
+    pub fn to_failure_notification(&self, submission_id: Uuid) -> Notification {
+        Notification {
+            event: NotificationEvent::SubmissionFailed,
+            subject: format!("投稿处理失败：{}", self.to_title()),
+            body: format!("submission_id: {}\n{}", submission_id, self.to_info()),
+            url: None,
+            reply_to: Some(self.email.clone()),
+        }
+    }
 
-🔗 查看处理进度：{}
-（如链接无法点击，请复制到浏览器打开）
+    /// 投稿处理失败时发给投稿人的邮件，不包含内部错误细节
+    pub fn to_contributor_failure(&self, submission_id: Uuid) -> String {
+        format!(
+            r#"很抱歉，您的投稿《{}》在处理过程中出现错误，未能成功提交。
 
-审核流程：
-1. 管理员将会审核您的投稿内容
-2. 审核通过后，您的文章将会被发布
-3. 如有需要修改的地方，我们会通过邮件与您沟通
+投稿编号：{}
 
-预计审核时间：1-3个工作日
-如有任何问题，请回复此邮件与我们联系。
+管理员已收到通知并会尽快排查。您可以稍后重新投稿，或回复此邮件并附上投稿编号与我们联系。"#,
+            self.title, submission_id
+        )
+    }
 
-再次感谢您对科幻文学的支持！"#,
-            self.title,
-            self.author,
-            self.tags.join("、"),
-            self.email,
-            pr_url
+    /// 投稿失败邮件的 HTML 版本，内容与 `to_contributor_failure` 一致
+    pub fn to_contributor_failure_html(&self, submission_id: Uuid) -> String {
+        format!(
+            r#"<p>很抱歉，您的投稿《{}》在处理过程中出现错误，未能成功提交。</p>
+<p>投稿编号：{}</p>
+<p>管理员已收到通知并会尽快排查。您可以稍后重新投稿，或回复此邮件并附上投稿编号与我们联系。</p>"#,
+            escape_html(&self.title),
+            submission_id
         )
     }
 }
@@ -106,6 +378,251 @@ impl ToHexo for Submission {
     }
 }
 
+/// 触发限流后最多重试的次数
+const RATE_LIMIT_MAX_RETRIES: u32 = 3;
+/// 单个请求因限流累计等待的上限，避免投稿任务长时间挂起
+const RATE_LIMIT_MAX_WAIT: Duration = Duration::from_secs(120);
+/// 限流响应未给出等待时间时，按 GitHub 文档建议至少等待一分钟
+const RATE_LIMIT_DEFAULT_WAIT: Duration = Duration::from_secs(60);
+
+/// 判断响应是否因限流被拒绝，是则返回需要等待的时间
+///
+/// 429，或带 `Retry-After` / `x-ratelimit-remaining: 0` 的 403 视为限流；
+/// 404、422 以及权限不足等普通的 403 返回 None
+fn rate_limit_wait<B>(response: &Response<B>) -> Option<Duration> {
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+    let retry_after = header("retry-after")
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let exhausted = header("x-ratelimit-remaining") == Some("0");
+
+    let limited = match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => retry_after.is_some() || exhausted,
+        _ => false,
+    };
+    if !limited {
+        return None;
+    }
+
+    if let Some(wait) = retry_after {
+        return Some(wait);
+    }
+    let reset = header("x-ratelimit-reset").and_then(|v| v.parse::<i64>().ok());
+    match reset {
+        Some(reset) if exhausted => Some(Duration::from_secs(
+            (reset - Utc::now().timestamp()).max(1) as u64,
+        )),
+        _ => Some(RATE_LIMIT_DEFAULT_WAIT),
+    }
+}
+
+/// 发送请求，遇到限流时按响应头等待后重试
+///
+/// 超过重试次数或累计等待上限时直接返回最后一次的限流响应，由调用方按普通错误处理
+async fn send_with_retry<B, F, Fut>(route: &str, send: F) -> octocrab::Result<Response<B>>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = octocrab::Result<Response<B>>>,
+{
+    let mut retries = 0;
+    let mut waited = Duration::ZERO;
+    loop {
+        let response = send().await?;
+        let Some(wait) = rate_limit_wait(&response) else {
+            return Ok(response);
+        };
+        if retries >= RATE_LIMIT_MAX_RETRIES || waited + wait > RATE_LIMIT_MAX_WAIT {
+            warn!(
+                "GITHUB_API: {} still rate limited after {} retries, giving up",
+                route, retries
+            );
+            return Ok(response);
+        }
+        retries += 1;
+        waited += wait;
+        warn!(
+            "GITHUB_API: {} rate limited, retry {} in {:?}",
+            route, retries, wait
+        );
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// `GET` GitHub 接口，限流时自动重试
+async fn github_get<R: FromResponse>(octocrab: &Octocrab, route: &str) -> octocrab::Result<R> {
+    let response = send_with_retry(route, || octocrab._get(route)).await?;
+    R::from_response(octocrab::map_github_error(response).await?).await
+}
+
+/// `POST` GitHub 接口，限流时自动重试
+async fn github_post<B, R>(octocrab: &Octocrab, route: &str, body: &B) -> octocrab::Result<R>
+where
+    B: Serialize + ?Sized,
+    R: FromResponse,
+{
+    let response = send_with_retry(route, || octocrab._post(route, Some(body))).await?;
+    R::from_response(octocrab::map_github_error(response).await?).await
+}
+
+/// `DELETE` GitHub 接口，限流时自动重试
+async fn github_delete(octocrab: &Octocrab, route: &str) -> octocrab::Result<()> {
+    let response = send_with_retry(route, || octocrab._delete(route, None::<&()>)).await?;
+    octocrab::map_github_error(response).await?;
+    Ok(())
+}
+
+/// `PATCH` GitHub 接口，限流时自动重试
+async fn github_patch<B, R>(octocrab: &Octocrab, route: &str, body: &B) -> octocrab::Result<R>
+where
+    B: Serialize + ?Sized,
+    R: FromResponse,
+{
+    let response = send_with_retry(route, || octocrab._patch(route, Some(body))).await?;
+    R::from_response(octocrab::map_github_error(response).await?).await
+}
+
+/// Git 数据接口返回的对象，只关心 sha
+#[derive(Debug, Deserialize)]
+struct GitObject {
+    sha: String,
+}
+
+/// `GET /git/ref/{ref}` 的响应
+#[derive(Debug, Deserialize)]
+struct GitRef {
+    object: GitRefObject,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitRefObject {
+    #[serde(rename = "type")]
+    kind: String,
+    sha: String,
+}
+
+/// 新建 PR 的响应，只关心编号与页面地址
+#[derive(Debug, Deserialize)]
+struct CreatedPullRequest {
+    number: u64,
+    html_url: Option<String>,
+}
+
+/// `GET /pulls` 列表中的 PR，只关心状态相关字段
+#[derive(Debug, Deserialize)]
+struct ListedPullRequest {
+    number: u64,
+    html_url: String,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    merged_at: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// `GET /pulls/{number}/reviews` 中的单条审阅
+#[derive(Debug, Deserialize)]
+struct PullRequestReview {
+    state: String,
+}
+
+/// `GET /git/commits/{sha}` 的响应
+#[derive(Debug, Deserialize)]
+struct GitCommit {
+    tree: GitObject,
+}
+
+/// 把文件内容上传为 blob，返回 blob sha
+async fn create_blob(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    file: &RepoFile<'_>,
+) -> Result<String> {
+    let route = format!("/repos/{}/{}/git/blobs", owner, repo);
+    let body = serde_json::json!({
+        "content": general_purpose::STANDARD.encode(&file.content),
+        "encoding": "base64",
+    });
+    let blob: GitObject = github_post(octocrab, &route, &body)
+        .await
+        .with_context(|| format!("上传{}失败（路径: {}）", file.label, file.path))?;
+    Ok(blob.sha)
+}
+
+/// 在 `parent` 的基础上把全部文件写入同一个提交，返回新提交的 sha
+///
+/// 只创建 Git 对象，不移动任何分支；任一文件失败时仓库中不会留下半成品分支
+async fn commit_files(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    parent: &str,
+    message: &str,
+    files: &[RepoFile<'_>],
+) -> Result<String> {
+    let route = format!("/repos/{}/{}/git/commits/{}", owner, repo, parent);
+    let parent_commit: GitCommit = github_get(octocrab, &route)
+        .await
+        .context("获取基准分支提交失败")?;
+
+    let mut entries = Vec::with_capacity(files.len());
+    for file in files {
+        let sha = create_blob(octocrab, owner, repo, file).await?;
+        entries.push(serde_json::json!({
+            "path": file.path,
+            "mode": "100644",
+            "type": "blob",
+            "sha": sha,
+        }));
+    }
+
+    let route = format!("/repos/{}/{}/git/trees", owner, repo);
+    let body = serde_json::json!({
+        "base_tree": parent_commit.tree.sha,
+        "tree": entries,
+    });
+    let tree: GitObject = github_post(octocrab, &route, &body)
+        .await
+        .context("创建 Git tree 失败")?;
+
+    let route = format!("/repos/{}/{}/git/commits", owner, repo);
+    let body = serde_json::json!({
+        "message": message,
+        "tree": tree.sha,
+        "parents": [parent],
+    });
+    let commit: GitObject = github_post(octocrab, &route, &body)
+        .await
+        .context("创建提交失败")?;
+
+    Ok(commit.sha)
+}
+
+/// 把已有分支快进到 `sha`，不强制覆盖
+async fn fast_forward_branch(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+    sha: &str,
+) -> Result<()> {
+    let route = format!("/repos/{}/{}/git/refs/heads/{}", owner, repo, branch);
+    let body = serde_json::json!({ "sha": sha, "force": false });
+    github_patch::<_, serde_json::Value>(octocrab, &route, &body)
+        .await
+        .context("更新分支失败")?;
+    info!("GITHUB_BRANCH: updated '{}'", branch);
+    Ok(())
+}
+
 impl Submission {
     pub fn new(
         author: String,
@@ -113,10 +630,14 @@ impl Submission {
         title: String,
         tags: Vec<String>,
         content: String,
-        cover: Base64Image,
-        images: Vec<Base64Image>,
+        cover: RawImage,
+        images: Vec<RawImage>,
     ) -> Self {
         let branch = format!("contrib-{}", Uuid::new_v4());
+        let tags = normalize_tags(tags);
+        let config = &AppConfig::global().submission;
+        let tags_placeholder = config.empty_tags_placeholder.clone();
+        let categories = config.categories.clone();
         Self {
             author,
             email,
@@ -126,10 +647,17 @@ impl Submission {
             cover,
             images,
             branch,
+            resubmission: false,
+            lang: Lang::default(),
+            tags_placeholder,
+            slug: None,
+            categories,
+            submitted_at: Local::now(),
         }
     }
     pub fn from_request(submission_request: SubmissionRequest) -> Self {
-        Submission::new(
+        let slug = submission_request.slug;
+        let mut submission = Submission::new(
             submission_request.author,
             submission_request.email,
             submission_request.title,
@@ -137,147 +665,540 @@ impl Submission {
             submission_request.content,
             submission_request.cover,
             submission_request.images,
-        )
+        );
+        submission.slug = slug;
+        if let Some(branch) = submission_request.branch {
+            submission.branch = branch;
+            submission.resubmission = true;
+        }
+        submission
+    }
+
+    /// 标题作为仓库路径段时的形式：去除首尾空白，不能以 `.` 开头，
+    /// 不能包含 `..`、路径分隔符、`#`、`?` 或控制字符；中文等 Unicode 字符保留
+    pub fn safe_title(&self) -> Result<String, String> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err("不能为空".to_string());
+        }
+        if title.contains("..") {
+            return Err("不能包含 ..".to_string());
+        }
+        if title.starts_with('.') {
+            return Err("不能以 . 开头".to_string());
+        }
+        check_path_segment(title)?;
+        Ok(title.to_string())
+    }
+
+    /// 文章在仓库中的路径名：优先使用合法的 slug，其次使用合法的标题，都不可用时退回分支名
+    pub fn post_slug(&self) -> String {
+        self.slug
+            .as_deref()
+            .and_then(safe_post_slug)
+            .or_else(|| self.safe_title().ok())
+            .unwrap_or_else(|| self.branch.clone())
+    }
+    /// 检查封面与附加图片能否正常解码
+    pub fn validate_images(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        if let Err(e) = self.cover.check_decodable() {
+            errors.push(ValidationError::new("cover", format!("{:#}", e)));
+        }
+        for (idx, img) in self.images.iter().enumerate() {
+            if let Err(e) = img.check_decodable() {
+                errors.push(ValidationError::new(
+                    "images",
+                    format!("第 {} 张图片无效: {:#}", idx + 1, e),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 集中执行全部投稿校验，返回所有未通过的规则
+    pub fn validate(&self, cfg: &SubmissionConfig) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        if !is_valid_email(&self.email) {
+            errors.push(ValidationError::new("email", "邮箱格式不正确"));
+        }
+        let title_chars = self.title.trim().chars().count();
+        if title_chars == 0 {
+            errors.push(ValidationError::new("title", "标题不能为空"));
+        } else if title_chars > cfg.title_max_chars {
+            errors.push(ValidationError::new(
+                "title",
+                format!(
+                    "标题过长：{} 字符，上限 {} 字符",
+                    title_chars, cfg.title_max_chars
+                ),
+            ));
+        }
+        match &self.slug {
+            // 指定了 slug 时标题只用于展示，不参与路径
+            Some(slug) => {
+                if safe_post_slug(slug).is_none() {
+                    errors.push(ValidationError::new("slug", "slug 无法作为文件名"));
+                }
+            }
+            None => {
+                if !self.title.trim().is_empty()
+                    && let Err(e) = self.safe_title()
+                {
+                    errors.push(ValidationError::new("title", e));
+                }
+            }
+        }
+        if self.resubmission && !is_contrib_branch(&self.branch) {
+            errors.push(ValidationError::new(
+                "branch",
+                "分支名无效，应为 contrib-<uuid>",
+            ));
+        }
+        let author_chars = self.author.trim().chars().count();
+        if author_chars == 0 && cfg.author_min_chars > 0 {
+            errors.push(ValidationError::new("author", "作者不能为空"));
+        } else if author_chars < cfg.author_min_chars {
+            errors.push(ValidationError::new(
+                "author",
+                format!(
+                    "作者名过短：{} 字符，至少 {} 字符",
+                    author_chars, cfg.author_min_chars
+                ),
+            ));
+        } else if author_chars > cfg.author_max_chars {
+            errors.push(ValidationError::new(
+                "author",
+                format!(
+                    "作者名过长：{} 字符，上限 {} 字符",
+                    author_chars, cfg.author_max_chars
+                ),
+            ));
+        }
+
+        let content_chars = self.content.chars().count();
+        if self.content.trim().is_empty() {
+            errors.push(ValidationError::new("content", "正文不能为空"));
+        } else if content_chars > cfg.content_max_chars {
+            errors.push(ValidationError::new(
+                "content",
+                format!(
+                    "正文过长：{} 字符，上限 {} 字符",
+                    content_chars, cfg.content_max_chars
+                ),
+            ));
+        }
+
+        if self.tags.len() > cfg.max_tags {
+            errors.push(ValidationError::new(
+                "tags",
+                format!("标签过多：{} 个，上限 {} 个", self.tags.len(), cfg.max_tags),
+            ));
+        }
+        for tag in &self.tags {
+            let tag_chars = tag.chars().count();
+            if tag_chars > cfg.tag_max_chars {
+                errors.push(ValidationError::new(
+                    "tags",
+                    format!(
+                        "标签 {} 过长：{} 字符，上限 {} 字符",
+                        tag, tag_chars, cfg.tag_max_chars
+                    ),
+                ));
+            } else if !cfg.allowed_tags.is_empty()
+                && !cfg
+                    .allowed_tags
+                    .iter()
+                    .any(|t| t.to_lowercase() == tag.to_lowercase())
+            {
+                errors.push(ValidationError::new(
+                    "tags",
+                    format!("标签 {} 不在允许的标签列表中", tag),
+                ));
+            }
+        }
+        if self.images.len() > cfg.max_images {
+            errors.push(ValidationError::new(
+                "images",
+                format!(
+                    "图片过多：{} 张，上限 {} 张",
+                    self.images.len(),
+                    cfg.max_images
+                ),
+            ));
+        }
+
+        if self.cover.bytes.is_empty() {
+            errors.push(ValidationError::new("cover", "封面图片不能为空"));
+        } else if self.cover.bytes.len() > cfg.max_image_bytes {
+            errors.push(ValidationError::new(
+                "cover",
+                format!(
+                    "封面图片过大：{} 字节，上限 {} 字节",
+                    self.cover.bytes.len(),
+                    cfg.max_image_bytes
+                ),
+            ));
+        }
+        for (idx, img) in self.images.iter().enumerate() {
+            if img.bytes.len() > cfg.max_image_bytes {
+                errors.push(ValidationError::new(
+                    "images",
+                    format!(
+                        "第 {} 张图片过大：{} 字节，上限 {} 字节",
+                        idx + 1,
+                        img.bytes.len(),
+                        cfg.max_image_bytes
+                    ),
+                ));
+            }
+        }
+
+        if cfg.animated_images == AnimatedImagePolicy::Reject {
+            if self.cover.is_animated() {
+                errors.push(ValidationError::new("cover", "封面不支持动图"));
+            }
+            for (idx, img) in self.images.iter().enumerate() {
+                if img.is_animated() {
+                    errors.push(ValidationError::new(
+                        "images",
+                        format!("第 {} 张图片为动图，当前不支持", idx + 1),
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 把封面与图集中的静态图片缩小到尺寸上限内并重新编码为 WebP，
+    /// 使提交的内容与 `.webp` 扩展名一致
+    pub fn convert_images_to_webp(&mut self, limits: &ImageConfig) -> Result<()> {
+        self.cover = self
+            .cover
+            .to_webp(limits.max_width, limits.max_height, limits.webp_quality)
+            .context("封面转换为 WebP 失败")?;
+        for (idx, img) in self.images.iter_mut().enumerate() {
+            *img = img
+                .to_webp(limits.max_width, limits.max_height, limits.webp_quality)
+                .with_context(|| format!("第 {} 张图片转换为 WebP 失败", idx + 1))?;
+        }
+        Ok(())
+    }
+
+    /// 去除封面与图集中的 EXIF 元数据，避免拍摄位置等信息公开到仓库
+    pub fn strip_image_metadata(&mut self) -> Result<()> {
+        self.cover = self.cover.strip_metadata().context("封面去除元数据失败")?;
+        for (idx, img) in self.images.iter_mut().enumerate() {
+            *img = img
+                .strip_metadata()
+                .with_context(|| format!("第 {} 张图片去除元数据失败", idx + 1))?;
+        }
+        Ok(())
+    }
+
+    /// 作者、邮箱与标题的摘要，用于识别重复投稿；邮箱不区分大小写
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.author.trim(),
+            &self.email.trim().to_lowercase(),
+            self.title.trim(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// 本次投稿需要提交到仓库的全部文件（路径未编码）
+    pub fn files(&self) -> Vec<RepoFile<'_>> {
+        let slug = self.post_slug();
+        let mut files = vec![
+            RepoFile {
+                path: format!("source/_posts/{}.md", slug),
+                content: Cow::Owned(self.to_hexo().into_bytes()),
+                label: "Markdown 文件".to_string(),
+            },
+            RepoFile {
+                path: format!("source/_posts/{}/{}", slug, self.cover_name()),
+                content: Cow::Borrowed(&self.cover.bytes),
+                label: "封面文件".to_string(),
+            },
+        ];
+
+        for (idx, (img, name)) in self.images.iter().zip(self.image_names()).enumerate() {
+            files.push(RepoFile {
+                path: format!("source/photos/{}/{}", slug, name),
+                content: Cow::Borrowed(&img.bytes),
+                label: format!("第 {} 张图片", idx + 1),
+            });
+        }
+
+        files
     }
+
     pub async fn push_branch(&self) -> Result<()> {
-        let config = AppConfig::global();
-        let repo_url = config.github.repo_path.clone();
-        let pat = config.github.personal_access_token.expose_secret().clone();
-
-        // 提取 owner/repo
-        let parts: Vec<String> = repo_url
-            .trim_end_matches(".git")
-            .rsplitn(3, '/')
-            .map(|p| p.to_string())
-            .collect();
-        let repo_name = parts[0].clone();
-        let owner_name = parts[1].clone();
+        // 标题或 slug 会作为仓库路径的一部分，不安全时在创建分支前直接拒绝
+        match &self.slug {
+            Some(slug) if safe_post_slug(slug).is_none() => {
+                return Err(anyhow!("slug 无法作为文件路径: {}", slug));
+            }
+            Some(_) => {}
+            None => {
+                self.safe_title()
+                    .map_err(|e| anyhow!("标题无法作为文件路径（{}）: {}", e, self.title))?;
+            }
+        }
 
-        let octocrab = Octocrab::builder()
-            .personal_token(pat.clone())
-            .build()
-            .context("构建 Octocrab 客户端失败")?;
+        let config = AppConfig::global();
+        let (owner_name, repo_name) = parse_owner_repo(&config.github.repo_path)?;
+        let octocrab = github_client()?;
 
-        // 1 获取 main 分支最新 SHA
-        let main_ref = octocrab
-            .repos(owner_name.clone(), repo_name.clone())
-            .get_ref(&Reference::Branch("main".to_string()))
-            .await
-            .context("获取 main 分支引用失败")?;
+        self.push_branch_with(
+            &octocrab,
+            &owner_name,
+            &repo_name,
+            &config.github.default_branch,
+            config.submission.max_total_image_bytes,
+        )
+        .await
+    }
 
-        let main_sha = match main_ref.object {
-            Object::Commit { sha, .. } => sha,
-            _ => return Err(anyhow!("heads/main 未指向 Commit 对象")),
-        };
+    /// 封面与图集解码后的总字节数超过 `max_bytes` 时返回错误
+    pub fn check_total_image_bytes(&self, max_bytes: usize) -> Result<()> {
+        let total =
+            self.cover.bytes.len() + self.images.iter().map(|img| img.bytes.len()).sum::<usize>();
+        if total > max_bytes {
+            return Err(anyhow!(
+                "图片总大小 {} 字节，超过上限 {} 字节",
+                total,
+                max_bytes
+            ));
+        }
+        Ok(())
+    }
 
-        // 2 创建唯一分支（指向 main）
-        octocrab
-            .repos(owner_name.clone(), repo_name.clone())
-            .create_ref(&Reference::Branch(self.branch.clone()), main_sha)
-            .await
-            .context("创建分支失败")?;
+    /// 使用给定的客户端基于 `base_branch` 推送到 `owner/repo`；图片总大小超限时在创建分支前拒绝
+    async fn push_branch_with(
+        &self,
+        octocrab: &Octocrab,
+        owner_name: &str,
+        repo_name: &str,
+        base_branch: &str,
+        max_total_image_bytes: usize,
+    ) -> Result<()> {
+        self.check_total_image_bytes(max_total_image_bytes)?;
 
-        // 工具闭包：对 URL 的每个路径段做百分号编码
-        let encode_path = |p: &str| {
-            p.split('/')
-                .map(|seg| encode(seg).into_owned())
-                .collect::<Vec<_>>()
-                .join("/")
+        // 1 获取基准分支最新 SHA；重新投稿时以原投稿分支为基准，同路径的文件被新内容覆盖
+        let parent_branch = if self.resubmission {
+            self.branch.as_str()
+        } else {
+            base_branch
         };
-
-        // 3 提交 Markdown
-        let md_path_encoded = encode_path(&format!("source/_posts/{}.md", self.title));
-        let md_bytes = self.to_hexo().into_bytes();
-        octocrab
-            .repos(owner_name.clone(), repo_name.clone())
-            .create_file(md_path_encoded, "Add new submission: markdown", md_bytes)
-            .branch(&self.branch)
-            .send()
+        let route = format!(
+            "/repos/{}/{}/git/ref/heads/{}",
+            owner_name, repo_name, parent_branch
+        );
+        let base_ref: GitRef = github_get(octocrab, &route)
             .await
-            .context("提交 Markdown 文件失败")?;
+            .with_context(|| format!("获取 {} 分支引用失败", parent_branch))?;
 
-        // 4 保存 cover
-        let cover_path_encoded = encode_path(&format!("source/_posts/{}/cover.webp", self.title));
-        let cover_bytes = self.cover.to_bytes().context("封面图片编码失败")?;
+        if base_ref.object.kind != "commit" {
+            return Err(anyhow!("heads/{} 未指向 Commit 对象", parent_branch));
+        }
+        let base_sha = base_ref.object.sha;
 
-        octocrab
-            .repos(owner_name.clone(), repo_name.clone())
-            .create_file(cover_path_encoded, "Add new submission: cover", cover_bytes)
-            .branch(&self.branch)
-            .send()
-            .await
-            .context("提交封面文件失败")?;
+        // 2 把 Markdown、封面与其他图片写入同一个提交
+        let message = format!("Add new submission: {}", self.title);
+        let commit_sha = commit_files(
+            octocrab,
+            owner_name,
+            repo_name,
+            &base_sha,
+            &message,
+            &self.files(),
+        )
+        .await?;
 
-        // 5 保存其他图片
-        for (idx, img) in self.images.iter().enumerate() {
-            let img_path_encoded =
-                encode_path(&format!("source/photos/{}/{}.webp", self.title, idx + 1));
-            let img_bytes = img.to_bytes().context("附加图片编码失败")?;
-            octocrab
-                .repos(owner_name.clone(), repo_name.clone())
-                .create_file(img_path_encoded, "Add new submission: image", img_bytes)
-                .branch(&self.branch)
-                .send()
-                .await
-                .with_context(|| format!("提交第 {} 张图片失败", idx + 1))?;
+        // 3 重新投稿时快进原分支，已有的 PR 随之更新
+        if self.resubmission {
+            fast_forward_branch(octocrab, owner_name, repo_name, &self.branch, &commit_sha).await?;
+            return Ok(());
+        }
+
+        // 创建唯一分支，直接指向新提交
+        let route = format!("/repos/{}/{}/git/refs", owner_name, repo_name);
+        let body = serde_json::json!({
+            "ref": format!("refs/heads/{}", self.branch),
+            "sha": commit_sha,
+        });
+        match github_post::<_, serde_json::Value>(octocrab, &route, &body).await {
+            Ok(_) => {}
+            // 上一次请求在 GitHub 侧已创建分支（如响应丢失后重试），在其基础上重新提交并快进
+            Err(octocrab::Error::GitHub { source, .. })
+                if source.status_code == StatusCode::UNPROCESSABLE_ENTITY =>
+            {
+                warn!(
+                    "GITHUB_BRANCH: '{}' already exists, fast-forwarding: {}",
+                    self.branch, source.message
+                );
+                let route = format!(
+                    "/repos/{}/{}/git/ref/heads/{}",
+                    owner_name, repo_name, self.branch
+                );
+                let existing: GitRef = github_get(octocrab, &route)
+                    .await
+                    .with_context(|| format!("获取 {} 分支引用失败", self.branch))?;
+                let commit_sha = commit_files(
+                    octocrab,
+                    owner_name,
+                    repo_name,
+                    &existing.object.sha,
+                    &message,
+                    &self.files(),
+                )
+                .await?;
+                fast_forward_branch(octocrab, owner_name, repo_name, &self.branch, &commit_sha)
+                    .await?;
+            }
+            Err(e) => return Err(anyhow::Error::new(e).context("创建分支失败")),
         }
 
-        // 6 完成
+        // 4 完成
         println!("push branch '{}' success", self.branch);
         Ok(())
     }
 
     pub async fn pull_request(&self) -> Result<String> {
         let config = AppConfig::global();
-        let pat = config.github.personal_access_token.expose_secret().clone();
+        let options = PullRequestOptions::from_config(&config);
 
-        let repo_url_clone = AppConfig::global().github.repo_path.clone();
-        let parts: Vec<String> = repo_url_clone
-            .trim_end_matches(".git")
-            .rsplitn(3, '/')
-            .map(|p| p.to_string())
-            .collect();
-        let repo_name = parts[0].clone();
-        let owner_name = parts[1].clone();
+        let (owner_name, repo_name) = parse_owner_repo(&config.github.repo_path)?;
+        let octocrab = github_client()?;
 
-        let pr_title = format!("{}-{}", self.title, self.author);
-        // PR body 包含基本信息
-        let tags_str = if self.tags.is_empty() {
-            "None".to_string()
-        } else {
-            self.tags.join(", ")
-        };
-        let pr_body = format!(
-            "Automated submission from contribution form.\n\n\
-            **Title:** {}\n\
-            **Author:** {}\n\
-            **Email:** {}\n\
-            **Tags:** {}\n\
-            **Images:** {} (including cover)\n",
-            self.title,
-            self.author,
-            self.email,
-            tags_str,
-            1 + self.images.len(),
+        self.pull_request_with(
+            &octocrab,
+            &owner_name,
+            &repo_name,
+            &config.github.default_branch,
+            &options,
+        )
+        .await
+    }
+
+    /// 删除本次投稿创建的分支，用于后续步骤失败时回滚
+    ///
+    /// 尽力而为：删除失败只记录日志
+    pub async fn delete_branch(&self) {
+        let config = AppConfig::global();
+
+        let client = parse_owner_repo(&config.github.repo_path).and_then(|(owner, repo)| {
+            let octocrab = github_client()?;
+            Ok((octocrab, owner, repo))
+        });
+        match client {
+            Ok((octocrab, owner_name, repo_name)) => {
+                self.delete_branch_with(&octocrab, &owner_name, &repo_name)
+                    .await
+            }
+            Err(e) => warn!(
+                "GITHUB_BRANCH: build client to delete '{}' failed: {}",
+                self.branch, e
+            ),
+        }
+    }
+
+    async fn delete_branch_with(&self, octocrab: &Octocrab, owner_name: &str, repo_name: &str) {
+        let route = format!(
+            "/repos/{}/{}/git/refs/heads/{}",
+            owner_name, repo_name, self.branch
         );
+        if let Err(e) = github_delete(octocrab, &route).await {
+            warn!("GITHUB_BRANCH: delete '{}' failed: {}", self.branch, e);
+        }
+    }
 
-        let octocrab = Octocrab::builder()
-            .personal_token(pat.to_string())
-            .build()
-            .context("构建 Octocrab 客户端失败")?;
+    /// 使用给定的客户端在 `owner/repo` 创建合并到 `base_branch` 的 PR，随后添加标签并请求审阅
+    ///
+    /// 添加标签与请求审阅失败（如标签不存在）只记录日志，不影响投稿结果
+    async fn pull_request_with(
+        &self,
+        octocrab: &Octocrab,
+        owner_name: &str,
+        repo_name: &str,
+        base_branch: &str,
+        options: &PullRequestOptions,
+    ) -> Result<String> {
+        // 重新投稿的分支已有打开的 PR 时，推送即已更新该 PR
+        if self.resubmission
+            && let Some(pr) = find_pull_request(octocrab, owner_name, repo_name, &self.branch)
+                .await?
+                .filter(|pr| pr.state == "open")
+        {
+            info!("GITHUB_PR: #{} updated by '{}'", pr.number, self.branch);
+            return Ok(pr.html_url);
+        }
+
+        let pr_title = format!("{}-{}", self.title, self.author);
+        // PR body 包含基本信息
+        let pr_body = self.to_pr_body();
 
-        let pr = octocrab
-            .pulls(owner_name.clone(), repo_name.clone())
-            .create(pr_title, self.branch.clone(), "main")
-            .body(pr_body)
-            .send()
+        let route = format!("/repos/{}/{}/pulls", owner_name, repo_name);
+        let body = serde_json::json!({
+            "title": pr_title,
+            "head": self.branch,
+            "base": base_branch,
+            "body": pr_body,
+            "draft": options.draft,
+        });
+        let pr: CreatedPullRequest = github_post(octocrab, &route, &body)
             .await
             .context("创建 Pull Request 失败")?;
 
-        let url = pr.html_url.map(|url| url.to_string()).unwrap_or_else(|| {
+        if !options.labels.is_empty() {
+            let route = format!(
+                "/repos/{}/{}/issues/{}/labels",
+                owner_name, repo_name, pr.number
+            );
+            let body = serde_json::json!({ "labels": options.labels });
+            if let Err(e) = github_post::<_, serde_json::Value>(octocrab, &route, &body).await {
+                warn!(
+                    "GITHUB_PR: add labels {:?} to #{} failed: {}",
+                    options.labels, pr.number, e
+                );
+            }
+        }
+
+        if !options.reviewers.is_empty() {
+            let route = format!(
+                "/repos/{}/{}/pulls/{}/requested_reviewers",
+                owner_name, repo_name, pr.number
+            );
+            let body = serde_json::json!({ "reviewers": options.reviewers });
+            if let Err(e) = github_post::<_, serde_json::Value>(octocrab, &route, &body).await {
+                warn!(
+                    "GITHUB_PR: request reviewers {:?} for #{} failed: {}",
+                    options.reviewers, pr.number, e
+                );
+            }
+        }
+
+        let url = pr.html_url.unwrap_or_else(|| {
             format!(
                 "https://github.com/{}/{}/pull/{}",
-                &owner_name, &repo_name, pr.number
+                owner_name, repo_name, pr.number
             )
         });
 
@@ -285,3 +1206,1345 @@ impl Submission {
         Ok(url)
     }
 }
+
+/// 判断分支名是否为投稿创建的 `contrib-<uuid>` 分支
+pub fn is_contrib_branch(branch: &str) -> bool {
+    branch
+        .strip_prefix("contrib-")
+        .is_some_and(|id| Uuid::try_parse(id).is_ok())
+}
+
+/// 投稿 PR 的当前状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PullRequestStatus {
+    pub number: u64,
+    pub url: String,
+    /// `open` 或 `closed`
+    pub state: String,
+    pub merged: bool,
+    pub draft: bool,
+    /// 最近一次给出结论的审阅（`APPROVED`、`CHANGES_REQUESTED` 等），尚无时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_state: Option<String>,
+}
+
+/// 查询投稿分支对应的 PR 及其合并、审阅状态，分支尚无 PR 时返回 None
+pub async fn pull_request_status(branch: &str) -> Result<Option<PullRequestStatus>> {
+    let config = AppConfig::global();
+    let (owner_name, repo_name) = parse_owner_repo(&config.github.repo_path)?;
+    let octocrab = github_client()?;
+
+    pull_request_status_with(&octocrab, &owner_name, &repo_name, branch).await
+}
+
+/// PR 描述中记录的投稿邮箱，格式见 [`Submission::to_pr_body`]
+fn pr_submitter_email(body: &str) -> Option<&str> {
+    body.lines()
+        .find_map(|line| line.trim().strip_prefix("**Email:**"))
+        .map(str::trim)
+        .filter(|email| !email.is_empty())
+}
+
+/// 重新投稿前确认分支归属：分支最新 PR 中记录的投稿邮箱须与本次验证的邮箱一致
+///
+/// `contrib-*` 分支名在 GitHub 上公开可见，不校验归属时任何人都能改写他人的投稿；
+/// 分支没有 PR 时无法确认归属，视为不属于该邮箱
+pub async fn is_branch_owner(branch: &str, email: &str) -> Result<bool> {
+    let config = AppConfig::global();
+    let (owner_name, repo_name) = parse_owner_repo(&config.github.repo_path)?;
+    let octocrab = github_client()?;
+
+    is_branch_owner_with(&octocrab, &owner_name, &repo_name, branch, email).await
+}
+
+/// 使用给定的客户端确认 `owner/repo` 中 `branch` 的投稿邮箱是否为 `email`
+async fn is_branch_owner_with(
+    octocrab: &Octocrab,
+    owner_name: &str,
+    repo_name: &str,
+    branch: &str,
+    email: &str,
+) -> Result<bool> {
+    let pr = find_pull_request(octocrab, owner_name, repo_name, branch).await?;
+    Ok(pr
+        .as_ref()
+        .and_then(|pr| pr.body.as_deref())
+        .and_then(pr_submitter_email)
+        .is_some_and(|owner| owner.eq_ignore_ascii_case(email.trim())))
+}
+
+/// 查找 `owner/repo` 中来自 `branch` 的最新 PR，已合并或关闭的也包括在内
+async fn find_pull_request(
+    octocrab: &Octocrab,
+    owner_name: &str,
+    repo_name: &str,
+    branch: &str,
+) -> Result<Option<ListedPullRequest>> {
+    let route = format!(
+        "/repos/{}/{}/pulls?head={}:{}&state=all",
+        owner_name, repo_name, owner_name, branch
+    );
+    let pulls: Vec<ListedPullRequest> = github_get(octocrab, &route)
+        .await
+        .context("查询 Pull Request 失败")?;
+    Ok(pulls.into_iter().next())
+}
+
+/// 使用给定的客户端查询 `owner/repo` 中来自 `branch` 的 PR
+async fn pull_request_status_with(
+    octocrab: &Octocrab,
+    owner_name: &str,
+    repo_name: &str,
+    branch: &str,
+) -> Result<Option<PullRequestStatus>> {
+    let Some(pr) = find_pull_request(octocrab, owner_name, repo_name, branch).await? else {
+        return Ok(None);
+    };
+
+    let route = format!(
+        "/repos/{}/{}/pulls/{}/reviews?per_page=100",
+        owner_name, repo_name, pr.number
+    );
+    let reviews: Vec<PullRequestReview> = github_get(octocrab, &route)
+        .await
+        .context("查询 Pull Request 审阅失败")?;
+    // 仅评论或尚未提交的审阅不改变结论
+    let review_state = reviews
+        .into_iter()
+        .rev()
+        .map(|r| r.state)
+        .find(|state| state != "COMMENTED" && state != "PENDING");
+
+    Ok(Some(PullRequestStatus {
+        number: pr.number,
+        url: pr.html_url,
+        state: pr.state,
+        merged: pr.merged_at.is_some(),
+        draft: pr.draft,
+        review_state,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn sample_submission(tags: Vec<String>) -> Submission {
+        Submission {
+            author: "作者".to_string(),
+            email: "author@example.com".to_string(),
+            title: "标题".to_string(),
+            tags,
+            content: "正文".to_string(),
+            cover: RawImage {
+                name: "cover.png".to_string(),
+                bytes: vec![1, 2, 3],
+            },
+            images: vec![],
+            branch: "contrib-test".to_string(),
+            resubmission: false,
+            lang: Lang::default(),
+            tags_placeholder: "无".to_string(),
+            slug: None,
+            categories: vec![],
+            submitted_at: Local::now(),
+        }
+    }
+
+    fn test_config() -> SubmissionConfig {
+        SubmissionConfig {
+            empty_tags_placeholder: "无".to_string(),
+            categories: vec![],
+            title_max_chars: 10,
+            content_max_chars: 10,
+            max_tags: 2,
+            tag_max_chars: 5,
+            allowed_tags: vec![],
+            author_min_chars: 1,
+            author_max_chars: 5,
+            max_images: 1,
+            max_image_bytes: 4,
+            max_total_image_bytes: 8,
+            maintenance_message: String::new(),
+            dry_run: false,
+            animated_images: AnimatedImagePolicy::Preserve,
+            duplicate_window_secs: 0,
+            idempotency_window_secs: 0,
+            test_shortcut: false,
+            test_title: String::new(),
+            test_author: String::new(),
+        }
+    }
+
+    fn image(len: usize) -> RawImage {
+        RawImage {
+            name: "image.png".to_string(),
+            bytes: vec![0; len],
+        }
+    }
+
+    /// 断言校验只因指定字段失败
+    fn assert_only_fails_on(submission: &Submission, field: &str) {
+        let errors = submission.validate(&test_config()).unwrap_err();
+        assert!(!errors.is_empty());
+        assert!(
+            errors.iter().all(|e| e.field == field),
+            "unexpected errors: {:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn test_markdown_references_images_in_order() {
+        let mut submission = sample_submission(vec![]);
+        submission.images = (0..12u8)
+            .map(|i| RawImage {
+                name: format!("{}.png", i),
+                bytes: vec![i],
+            })
+            .collect();
+
+        let files = submission.files();
+        let markdown = String::from_utf8(files[0].content.to_vec()).unwrap();
+
+        // 图片文件按投稿顺序编号，内容与原数组一一对应
+        for (i, file) in files[2..].iter().enumerate() {
+            let name = format!("{:03}.webp", i + 1);
+            assert_eq!(file.path, format!("source/photos/标题/{}", name));
+            assert_eq!(file.content.as_ref(), &[i as u8]);
+        }
+
+        // front matter 中的图集顺序与提交的文件一致
+        let photos: Vec<&str> = markdown
+            .lines()
+            .skip_while(|l| *l != "photos:")
+            .skip(1)
+            .take_while(|l| l.starts_with("- "))
+            .map(|l| l.trim_start_matches("- "))
+            .collect();
+        let expected: Vec<String> = (1..=12)
+            .map(|n| format!("/photos/标题/{:03}.webp", n))
+            .collect();
+        assert_eq!(photos, expected);
+
+        // 多次生成结果相同
+        let again = submission.files();
+        let paths: Vec<_> = again.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            files.iter().map(|f| f.path.clone()).collect::<Vec<_>>()
+        );
+    }
+
+    /// 生成一张两帧的 GIF 动图
+    fn animated_gif() -> RawImage {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, Frame, Rgba, RgbaImage};
+
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            for color in [[255, 0, 0, 255], [0, 0, 255, 255]] {
+                let frame = Frame::from_parts(
+                    RgbaImage::from_pixel(2, 2, Rgba(color)),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(100, 1),
+                );
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+        RawImage {
+            name: "anim.gif".to_string(),
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_animated_gif_is_preserved() {
+        let gif = animated_gif();
+        assert!(gif.is_animated());
+
+        let mut submission = sample_submission(vec![]);
+        submission.images = vec![gif.clone()];
+        let mut cfg = test_config();
+        cfg.max_image_bytes = usize::MAX;
+        assert!(submission.validate(&cfg).is_ok());
+
+        // 原样提交，扩展名与实际格式一致
+        let files = submission.files();
+        assert_eq!(files[2].path, "source/photos/标题/001.gif");
+        assert_eq!(files[2].content.as_ref(), gif.bytes.as_slice());
+        let markdown = String::from_utf8(files[0].content.to_vec()).unwrap();
+        assert!(markdown.contains("- /photos/标题/001.gif"));
+
+        // 配置为拒绝时在校验阶段报错
+        cfg.animated_images = AnimatedImagePolicy::Reject;
+        let errors = submission.validate(&cfg).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "images");
+    }
+
+    #[test]
+    fn test_converted_images_match_extension() {
+        use image::{DynamicImage, ImageFormat};
+        use std::io::Cursor;
+
+        let mut png = Vec::new();
+        DynamicImage::new_rgb8(4, 4)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let mut submission = sample_submission(vec![]);
+        submission.cover = RawImage {
+            name: "cover.png".to_string(),
+            bytes: png,
+        };
+        submission.images = vec![animated_gif()];
+        let limits = ImageConfig {
+            max_width: 2,
+            max_height: 0,
+            strip_metadata: true,
+            webp_quality: 80,
+        };
+        submission.convert_images_to_webp(&limits).unwrap();
+
+        let files = submission.files();
+        assert_eq!(files[1].path, "source/_posts/标题/cover.webp");
+        let cover = image::load_from_memory(&files[1].content).unwrap();
+        assert_eq!(
+            image::guess_format(&files[1].content).unwrap(),
+            ImageFormat::WebP
+        );
+        assert_eq!((cover.width(), cover.height()), (2, 2));
+        // 动图保持原格式
+        assert_eq!(files[2].path, "source/photos/标题/001.gif");
+    }
+
+    #[test]
+    fn test_explicit_slug() {
+        let mut submission = sample_submission(vec![]);
+        submission.title = "C# 入门：第一章".to_string();
+        submission.slug = Some("  csharp intro  ".to_string());
+        submission.images = vec![image(1)];
+        assert!(submission.validate(&test_config()).is_ok());
+
+        // 路径使用 slug，front matter 仍为展示标题
+        let files = submission.files();
+        assert_eq!(files[0].path, "source/_posts/csharp-intro.md");
+        assert_eq!(files[1].path, "source/_posts/csharp-intro/cover.webp");
+        assert_eq!(files[2].path, "source/photos/csharp-intro/001.webp");
+        let markdown = String::from_utf8(files[0].content.to_vec()).unwrap();
+        assert!(markdown.contains("title: C# 入门：第一章"));
+        assert!(markdown.contains("- /photos/csharp-intro/001.webp"));
+
+        for slug in ["a/b", "what?", "..", "   ", ".hidden"] {
+            submission.slug = Some(slug.to_string());
+            assert_only_fails_on(&submission, "slug");
+        }
+    }
+
+    #[test]
+    fn test_contributor_mail_zh() {
+        let submission = sample_submission(vec!["科幻".to_string(), "短篇".to_string()]);
+        let url = "https://github.com/o/r/pull/1";
+
+        let text = submission.to_contributor(url);
+        assert!(text.starts_with("感谢您的投稿！"));
+        assert!(text.contains("- 文章标题：《标题》"));
+        assert!(text.contains("- 标签：科幻、短篇"));
+        assert!(text.contains(&format!("🔗 查看处理进度：{url}")));
+
+        let html = submission.to_contributor_html(url);
+        assert!(html.contains("<li>作者：作者</li>"));
+        assert!(html.contains(&format!(r#"<a href="{url}">查看处理进度</a>"#)));
+    }
+
+    #[test]
+    fn test_contributor_mail_en() {
+        let mut submission = sample_submission(vec!["科幻".to_string(), "短篇".to_string()]);
+        submission.lang = Lang::En;
+        submission.title = "<Dune> {author}".to_string();
+        let url = "https://github.com/o/r/pull/1";
+
+        let text = submission.to_contributor(url);
+        assert!(text.starts_with("Thank you for your submission!"));
+        assert!(text.contains("- Title: \"<Dune> {author}\""));
+        assert!(text.contains("- Tags: 科幻, 短篇"));
+        assert!(text.contains("- Email: author@example.com"));
+        assert!(!text.contains("感谢"));
+
+        let html = submission.to_contributor_html(url);
+        assert!(html.contains("<li>Title: \"&lt;Dune&gt; {author}\"</li>"));
+        assert!(html.contains(&format!(r#"<a href="{url}">Track its progress</a>"#)));
+    }
+
+    #[test]
+    fn test_safe_title() {
+        let mut submission = sample_submission(vec![]);
+        submission.images = vec![image(1)];
+
+        // Unicode 标题原样保留，首尾空白去除
+        submission.title = "  深空探索：第 2 期  ".to_string();
+        assert_eq!(submission.safe_title().unwrap(), "深空探索：第 2 期");
+        let files = submission.files();
+        assert_eq!(files[0].path, "source/_posts/深空探索：第 2 期.md");
+        assert_eq!(files[1].path, "source/_posts/深空探索：第 2 期/cover.webp");
+        assert_eq!(files[2].path, "source/photos/深空探索：第 2 期/001.webp");
+        // front matter 保留原始标题
+        let markdown = String::from_utf8(files[0].content.to_vec()).unwrap();
+        assert!(
+            markdown.contains("title: \"  深空探索：第 2 期  \""),
+            "{}",
+            markdown
+        );
+
+        for (title, reason) in [
+            ("a/b", "'/'"),
+            ("a\\b", "'\\\\'"),
+            ("..", ".."),
+            ("上..下", ".."),
+            (".hidden", "不能以 . 开头"),
+            ("第一行\n第二行", "'\\n'"),
+        ] {
+            submission.title = title.to_string();
+            let err = submission.safe_title().unwrap_err();
+            assert!(err.contains(reason), "{:?}: {}", title, err);
+            assert_only_fails_on(&submission, "title");
+            // 不会用不安全的标题拼接路径
+            assert_eq!(submission.post_slug(), "contrib-test");
+        }
+    }
+
+    /// 记录 mock 接口收到的请求体
+    type Recorded = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    /// 模拟 GitHub Git 数据接口，记录每个 POST 请求的路径与请求体；
+    /// 第 `max_blobs` 个之后的 blob 上传返回 422，重复创建同名分支也返回 422
+    async fn mock_git_api(max_blobs: usize) -> (String, Recorded) {
+        use axum::extract::{Path, State};
+        use axum::http::{StatusCode, Uri};
+        use axum::routing::{delete, get, post};
+        use axum::{Json, Router};
+
+        type Limited = (Recorded, usize);
+
+        fn git_ref(name: &str, sha: &str) -> serde_json::Value {
+            serde_json::json!({
+                "ref": name,
+                "node_id": "ref",
+                "url": "http://localhost/",
+                "object": {"type": "commit", "sha": sha, "url": "http://localhost/"}
+            })
+        }
+
+        async fn record(
+            State((calls, max_blobs)): State<Limited>,
+            uri: Uri,
+            Json(body): Json<serde_json::Value>,
+        ) -> (StatusCode, Json<serde_json::Value>) {
+            let path = uri.path().to_string();
+            let mut calls = calls.lock().unwrap();
+            calls.push(serde_json::json!({"path": path, "body": body}));
+            let response = match path.rsplit('/').next().unwrap() {
+                "blobs" => {
+                    let blobs = calls.iter().filter(|c| c["path"] == path).count();
+                    if blobs > max_blobs {
+                        return (
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            Json(serde_json::json!({"message": "Invalid blob"})),
+                        );
+                    }
+                    serde_json::json!({"sha": format!("blob-{}", blobs)})
+                }
+                "refs"
+                    if calls
+                        .iter()
+                        .filter(|c| c["path"] == path && c["body"]["ref"] == body["ref"])
+                        .count()
+                        > 1 =>
+                {
+                    return (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        Json(serde_json::json!({"message": "Reference already exists"})),
+                    );
+                }
+                "trees" => serde_json::json!({"sha": "tree"}),
+                "commits" => serde_json::json!({"sha": "commit"}),
+                _ => git_ref(
+                    body["ref"].as_str().unwrap_or_default(),
+                    body["sha"].as_str().unwrap_or_default(),
+                ),
+            };
+            (StatusCode::CREATED, Json(response))
+        }
+
+        let calls: Recorded = Arc::default();
+        let app = Router::new()
+            .route(
+                "/repos/{owner}/{repo}/git/ref/{*reference}",
+                get(
+                    |State((calls, _)): State<Limited>,
+                     Path((_, _, reference)): Path<(String, String, String)>,
+                     uri: Uri| async move {
+                        let path = uri.path().to_string();
+                        calls
+                            .lock()
+                            .unwrap()
+                            .push(serde_json::json!({"path": path, "method": "GET"}));
+                        Json(git_ref(&format!("refs/{}", reference), "main-sha"))
+                    },
+                ),
+            )
+            .route(
+                "/repos/{owner}/{repo}/git/commits/{sha}",
+                get(
+                    |Path((_, _, sha)): Path<(String, String, String)>| async move {
+                        Json(serde_json::json!({"sha": sha, "tree": {"sha": "main-tree"}}))
+                    },
+                ),
+            )
+            .route("/repos/{owner}/{repo}/git/blobs", post(record))
+            .route("/repos/{owner}/{repo}/git/trees", post(record))
+            .route("/repos/{owner}/{repo}/git/commits", post(record))
+            .route("/repos/{owner}/{repo}/git/refs", post(record))
+            .route(
+                "/repos/{owner}/{repo}/git/refs/{*reference}",
+                delete(|State((calls, _)): State<Limited>, uri: Uri| async move {
+                    let path = uri.path().to_string();
+                    calls
+                        .lock()
+                        .unwrap()
+                        .push(serde_json::json!({"path": path, "method": "DELETE"}));
+                    StatusCode::NO_CONTENT
+                })
+                .patch(record),
+            )
+            .with_state((calls.clone(), max_blobs));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), calls)
+    }
+
+    /// 已记录请求中路径以 `suffix` 结尾的请求体
+    fn bodies(calls: &Recorded, suffix: &str) -> Vec<serde_json::Value> {
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c["path"].as_str().unwrap().ends_with(suffix))
+            .map(|c| c["body"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_is_contrib_branch() {
+        assert!(is_contrib_branch(&format!("contrib-{}", Uuid::new_v4())));
+        assert!(!is_contrib_branch("main"));
+        assert!(!is_contrib_branch("contrib-"));
+        assert!(!is_contrib_branch("contrib-not-a-uuid"));
+        assert!(!is_contrib_branch(&format!("feature-{}", Uuid::new_v4())));
+    }
+
+    /// 模拟 PR 列表与审阅接口，记录列表查询使用的 query
+    async fn mock_pull_status_api(merged: bool) -> (String, Arc<Mutex<Vec<String>>>) {
+        use axum::Json;
+        use axum::Router;
+        use axum::extract::State;
+        use axum::http::Uri;
+        use axum::routing::get;
+
+        let queries: Arc<Mutex<Vec<String>>> = Arc::default();
+        let app = Router::new()
+            .route(
+                "/repos/{owner}/{repo}/pulls",
+                get(
+                    move |State(queries): State<Arc<Mutex<Vec<String>>>>, uri: Uri| async move {
+                        let query = uri.query().unwrap_or_default().to_string();
+                        let known = query.contains("head=o:contrib-");
+                        queries.lock().unwrap().push(query);
+                        if !known {
+                            return Json(serde_json::json!([]));
+                        }
+                        Json(serde_json::json!([{
+                            "number": 42,
+                            "html_url": "https://github.com/o/r/pull/42",
+                            "state": if merged { "closed" } else { "open" },
+                            "draft": false,
+                            "merged_at": if merged { Some("2024-01-01T00:00:00Z") } else { None },
+                            "body": "Automated submission from contribution form.\n\n\
+                                **Title:** t\n**Author:** a\n**Email:** owner@example.com\n",
+                        }]))
+                    },
+                ),
+            )
+            .route(
+                "/repos/{owner}/{repo}/pulls/{number}/reviews",
+                get(|| async {
+                    Json(serde_json::json!([
+                        {"state": "CHANGES_REQUESTED"},
+                        {"state": "APPROVED"},
+                        {"state": "COMMENTED"},
+                    ]))
+                }),
+            )
+            .with_state(queries.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), queries)
+    }
+
+    #[tokio::test]
+    async fn test_pull_request_status() {
+        let branch = format!("contrib-{}", Uuid::new_v4());
+
+        let (base, queries) = mock_pull_status_api(false).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let status = pull_request_status_with(&octocrab, "o", "r", &branch)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            status,
+            PullRequestStatus {
+                number: 42,
+                url: "https://github.com/o/r/pull/42".to_string(),
+                state: "open".to_string(),
+                merged: false,
+                draft: false,
+                review_state: Some("APPROVED".to_string()),
+            }
+        );
+        assert_eq!(
+            queries.lock().unwrap()[0],
+            format!("head=o:{}&state=all", branch)
+        );
+
+        let (base, _) = mock_pull_status_api(true).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let status = pull_request_status_with(&octocrab, "o", "r", &branch)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(status.merged);
+        assert_eq!(status.state, "closed");
+
+        // 分支没有对应的 PR
+        let status = pull_request_status_with(&octocrab, "other", "r", &branch)
+            .await
+            .unwrap();
+        assert!(status.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_requires_branch_owner() {
+        let submission = sample_submission(vec![]);
+        assert_eq!(
+            pr_submitter_email(&submission.to_pr_body()),
+            Some(submission.email.as_str())
+        );
+
+        let branch = format!("contrib-{}", Uuid::new_v4());
+        let (base, _) = mock_pull_status_api(false).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+
+        for email in ["owner@example.com", "Owner@Example.com "] {
+            assert!(
+                is_branch_owner_with(&octocrab, "o", "r", &branch, email)
+                    .await
+                    .unwrap()
+            );
+        }
+        // 其他邮箱即使通过了验证码校验，也不能改写该分支
+        assert!(
+            !is_branch_owner_with(&octocrab, "o", "r", &branch, "attacker@example.com")
+                .await
+                .unwrap()
+        );
+        // 分支没有 PR，无法确认归属
+        assert!(
+            !is_branch_owner_with(&octocrab, "other", "r", &branch, "owner@example.com")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_push_branch_creates_single_commit() {
+        let (base, calls) = mock_git_api(usize::MAX).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let mut submission = sample_submission(vec![]);
+        submission.images = vec![image(1), image(2)];
+        submission
+            .push_branch_with(&octocrab, "o", "r", "main", usize::MAX)
+            .await
+            .unwrap();
+
+        // 每个文件一个 blob，内容与 files() 一致
+        let files = submission.files();
+        let blobs = bodies(&calls, "/git/blobs");
+        assert_eq!(blobs.len(), files.len());
+        for (blob, file) in blobs.iter().zip(&files) {
+            let content = general_purpose::STANDARD
+                .decode(blob["content"].as_str().unwrap())
+                .unwrap();
+            assert_eq!(content, file.content.as_ref());
+            assert_eq!(blob["encoding"], "base64");
+        }
+
+        // 全部文件在同一个 tree 中，基于 main 的 tree
+        let trees = bodies(&calls, "/git/trees");
+        assert_eq!(trees.len(), 1);
+        assert_eq!(trees[0]["base_tree"], "main-tree");
+        let entries = trees[0]["tree"].as_array().unwrap();
+        assert_eq!(entries.len(), files.len());
+        for (idx, (entry, file)) in entries.iter().zip(&files).enumerate() {
+            assert_eq!(entry["path"], file.path.as_str());
+            assert_eq!(entry["sha"], format!("blob-{}", idx + 1));
+            assert_eq!(entry["mode"], "100644");
+        }
+
+        // 只创建一个提交，分支直接指向它
+        let commits = bodies(&calls, "/git/commits");
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0]["tree"], "tree");
+        assert_eq!(commits[0]["parents"], serde_json::json!(["main-sha"]));
+        let refs = bodies(&calls, "/git/refs");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0]["ref"], "refs/heads/contrib-test");
+        assert_eq!(refs[0]["sha"], "commit");
+    }
+
+    #[tokio::test]
+    async fn test_push_branch_is_idempotent() {
+        let (base, calls) = mock_git_api(usize::MAX).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let submission = sample_submission(vec![]);
+
+        submission
+            .push_branch_with(&octocrab, "o", "r", "main", usize::MAX)
+            .await
+            .unwrap();
+        // 模拟重试：分支已在上一次请求中创建，应在其基础上快进而不是报错
+        submission
+            .push_branch_with(&octocrab, "o", "r", "main", usize::MAX)
+            .await
+            .unwrap();
+
+        assert_eq!(bodies(&calls, "/git/refs").len(), 2);
+        assert!(
+            calls
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|c| c["path"] == "/repos/o/r/git/ref/heads/contrib-test")
+        );
+        let updates = bodies(&calls, "/git/refs/heads/contrib-test");
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0]["sha"], "commit");
+        assert_eq!(updates[0]["force"], false);
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_updates_existing_branch() {
+        let branch = format!("contrib-{}", Uuid::new_v4());
+        let mut submission = sample_submission(vec![]);
+        submission.branch = branch.clone();
+        submission.resubmission = true;
+
+        let (base, calls) = mock_git_api(usize::MAX).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        submission
+            .push_branch_with(&octocrab, "o", "r", "main", usize::MAX)
+            .await
+            .unwrap();
+
+        // 以原分支为基准提交，并快进原分支
+        let ref_path = format!("/repos/o/r/git/ref/heads/{}", branch);
+        let refs_path = format!("/repos/o/r/git/refs/heads/{}", branch);
+        {
+            let calls = calls.lock().unwrap();
+            assert!(calls.iter().any(|c| c["path"] == ref_path.as_str()));
+            assert!(
+                !calls
+                    .iter()
+                    .any(|c| c["path"] == "/repos/o/r/git/ref/heads/main")
+            );
+        }
+        assert_eq!(bodies(&calls, "/git/blobs").len(), submission.files().len());
+        assert_eq!(bodies(&calls, "/git/commits").len(), 1);
+        let updates = bodies(&calls, &refs_path);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0]["sha"], "commit");
+        assert_eq!(updates[0]["force"], false);
+        // 不创建新分支
+        assert!(bodies(&calls, "/git/refs").is_empty());
+
+        // 已有打开的 PR，直接返回其地址；模拟接口不接受 POST，新建 PR 会失败
+        let (base, queries) = mock_pull_status_api(false).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let url = submission
+            .pull_request_with(&octocrab, "o", "r", "main", &PullRequestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(url, "https://github.com/o/r/pull/42");
+        assert_eq!(queries.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_branch_failure_leaves_no_branch() {
+        let (base, calls) = mock_git_api(2).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let mut submission = sample_submission(vec![]);
+        submission.images = vec![image(1), image(2)];
+
+        let err = submission
+            .push_branch_with(&octocrab, "o", "r", "main", usize::MAX)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("上传第 1 张图片失败"),
+            "{:#}",
+            err
+        );
+
+        // 图片上传失败时既没有提交，也没有创建分支
+        assert_eq!(bodies(&calls, "/git/blobs").len(), 3);
+        assert!(bodies(&calls, "/git/commits").is_empty());
+        assert!(bodies(&calls, "/git/refs").is_empty());
+    }
+
+    #[test]
+    fn test_validate_ok() {
+        let mut submission = sample_submission(vec!["科幻".to_string()]);
+        submission.images = vec![image(4)];
+        assert!(submission.validate(&test_config()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_email() {
+        let mut submission = sample_submission(vec![]);
+        submission.email = "not-an-email".to_string();
+        assert_only_fails_on(&submission, "email");
+    }
+
+    #[test]
+    fn test_validate_title_and_author() {
+        let mut submission = sample_submission(vec![]);
+        submission.title = "  ".to_string();
+        assert_only_fails_on(&submission, "title");
+
+        let mut submission = sample_submission(vec![]);
+        submission.author = String::new();
+        assert_only_fails_on(&submission, "author");
+    }
+
+    #[test]
+    fn test_validate_title_length() {
+        // 按字符计数：10 个汉字恰好不超限，11 个超限
+        let mut submission = sample_submission(vec![]);
+        submission.title = "题".repeat(10);
+        assert!(submission.validate(&test_config()).is_ok());
+        submission.title = "题".repeat(11);
+        assert_only_fails_on(&submission, "title");
+        let errors = submission.validate(&test_config()).unwrap_err();
+        assert!(errors[0].message.contains("11 字符"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_validate_author_length() {
+        // 按字符计数：5 个汉字恰好不超限，6 个超限
+        let mut submission = sample_submission(vec![]);
+        submission.author = "刘慈欣刘慈".to_string();
+        assert!(submission.validate(&test_config()).is_ok());
+        submission.author = "刘慈欣刘慈欣".to_string();
+        assert_only_fails_on(&submission, "author");
+        let errors = submission.validate(&test_config()).unwrap_err();
+        assert!(errors[0].message.contains("6 字符"), "{}", errors[0]);
+
+        // 首尾空白不计入
+        submission.author = "  刘慈欣刘慈  ".to_string();
+        assert!(submission.validate(&test_config()).is_ok());
+
+        let mut cfg = test_config();
+        cfg.author_min_chars = 2;
+        submission.author = "刘".to_string();
+        let errors = submission.validate(&cfg).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("1 字符"), "{}", errors[0]);
+        submission.author = "刘慈".to_string();
+        assert!(submission.validate(&cfg).is_ok());
+    }
+
+    #[test]
+    fn test_validate_content() {
+        let mut submission = sample_submission(vec![]);
+        submission.content = String::new();
+        assert_only_fails_on(&submission, "content");
+
+        // 按字符计数：10 个汉字恰好不超限，11 个超限
+        let mut submission = sample_submission(vec![]);
+        submission.content = "字".repeat(10);
+        assert!(submission.validate(&test_config()).is_ok());
+        submission.content = "字".repeat(11);
+        assert_only_fails_on(&submission, "content");
+    }
+
+    #[test]
+    fn test_validate_title_path() {
+        for title in ["C# 入门", "为什么?", "a/b", ".."] {
+            let mut submission = sample_submission(vec![]);
+            submission.title = title.to_string();
+            assert_only_fails_on(&submission, "title");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_branch_rejects_unsafe_title() {
+        let mut submission = sample_submission(vec![]);
+        submission.title = "C# 入门".to_string();
+
+        // 在读取配置、访问 GitHub 之前即被拒绝
+        let err = submission.push_branch().await.unwrap_err().to_string();
+        assert!(err.contains("标题无法作为文件路径"), "{}", err);
+        assert!(err.contains('#'), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_owner_repo() {
+        let expected = ("qidiankepukehuan".to_string(), "blog".to_string());
+        for url in [
+            "https://github.com/qidiankepukehuan/blog",
+            "https://github.com/qidiankepukehuan/blog.git",
+            "https://github.com/qidiankepukehuan/blog/",
+            "https://github.com/qidiankepukehuan/blog.git/",
+            "git@github.com:qidiankepukehuan/blog.git",
+            "git@github.com:qidiankepukehuan/blog",
+            "ssh://git@github.com/qidiankepukehuan/blog.git",
+        ] {
+            assert_eq!(parse_owner_repo(url).unwrap(), expected, "{}", url);
+        }
+
+        for url in [
+            "",
+            "qidiankepukehuan/blog",
+            "https://github.com",
+            "https://github.com/qidiankepukehuan",
+            "https://github.com/qidiankepukehuan/blog/tree/main",
+            "https://github.com//blog",
+            "git@github.com:blog.git",
+        ] {
+            let err = parse_owner_repo(url).unwrap_err().to_string();
+            assert!(err.contains("无法从仓库地址解析"), "{}: {}", url, err);
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        let response = |status: u16, headers: &[(&str, &str)]| {
+            let mut builder = Response::builder().status(status);
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            builder.body(()).unwrap()
+        };
+
+        assert_eq!(
+            rate_limit_wait(&response(403, &[("retry-after", "5")])),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            rate_limit_wait(&response(429, &[])),
+            Some(RATE_LIMIT_DEFAULT_WAIT)
+        );
+        let reset = (Utc::now().timestamp() + 30).to_string();
+        let wait = rate_limit_wait(&response(
+            403,
+            &[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", &reset),
+            ],
+        ))
+        .unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+
+        // 权限不足的 403 与 404、422 等不是限流
+        assert_eq!(rate_limit_wait(&response(403, &[])), None);
+        assert_eq!(
+            rate_limit_wait(&response(404, &[("retry-after", "5")])),
+            None
+        );
+        assert_eq!(rate_limit_wait(&response(422, &[])), None);
+        assert_eq!(rate_limit_wait(&response(201, &[])), None);
+    }
+
+    /// 模拟前 `limited` 次请求返回 403 + `Retry-After` 的接口，返回请求计数
+    async fn mock_rate_limited_api(
+        limited: usize,
+        retry_after: u64,
+    ) -> (String, Arc<Mutex<usize>>) {
+        use axum::extract::State;
+        use axum::http::header::RETRY_AFTER;
+        use axum::response::IntoResponse;
+        use axum::routing::post;
+        use axum::{Json, Router};
+
+        let calls: Arc<Mutex<usize>> = Arc::default();
+        let app = Router::new()
+            .route(
+                "/limited",
+                post(move |State(calls): State<Arc<Mutex<usize>>>| async move {
+                    let mut calls = calls.lock().unwrap();
+                    *calls += 1;
+                    if *calls <= limited {
+                        let body = serde_json::json!({
+                            "message": "You have exceeded a secondary rate limit."
+                        });
+                        return (
+                            StatusCode::FORBIDDEN,
+                            [(RETRY_AFTER, retry_after.to_string())],
+                            Json(body),
+                        )
+                            .into_response();
+                    }
+                    (StatusCode::CREATED, Json(serde_json::json!({"sha": "ok"}))).into_response()
+                }),
+            )
+            .with_state(calls.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), calls)
+    }
+
+    #[tokio::test]
+    async fn test_post_retries_after_rate_limit() {
+        let (base, calls) = mock_rate_limited_api(1, 1).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+
+        let started = std::time::Instant::now();
+        let object: GitObject = github_post(&octocrab, "/limited", &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(object.sha, "ok");
+        assert_eq!(*calls.lock().unwrap(), 2);
+        // 按 Retry-After 等待后才重试
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_post_gives_up_after_max_retries() {
+        let (base, calls) = mock_rate_limited_api(usize::MAX, 0).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+
+        let err = github_post::<_, GitObject>(&octocrab, "/limited", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        // 放弃后按普通的 403 错误返回
+        match err {
+            octocrab::Error::GitHub { source, .. } => {
+                assert_eq!(source.status_code, StatusCode::FORBIDDEN);
+                assert!(source.message.contains("secondary rate limit"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert_eq!(*calls.lock().unwrap(), RATE_LIMIT_MAX_RETRIES as usize + 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_configured_user_agent() {
+        use axum::http::HeaderMap;
+        use axum::routing::get;
+        use axum::{Json, Router};
+
+        let agents: Arc<Mutex<Vec<String>>> = Arc::default();
+        let recorded = agents.clone();
+        let app = Router::new().route(
+            "/ua",
+            get(move |headers: HeaderMap| async move {
+                recorded.lock().unwrap().extend(
+                    headers
+                        .get_all(USER_AGENT)
+                        .iter()
+                        .map(|v| v.to_str().unwrap().to_string()),
+                );
+                Json(serde_json::json!({"sha": "ok"}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let octocrab =
+            build_github_client(&format!("http://{}", addr), "token", "qidian-mini/test").unwrap();
+        let object: GitObject = github_get(&octocrab, "/ua").await.unwrap();
+        assert_eq!(object.sha, "ok");
+        // octocrab 自带的 User-Agent 无法去掉，配置的值附加在其后
+        let agents = agents.lock().unwrap();
+        assert!(
+            agents.iter().any(|ua| ua == "qidian-mini/test"),
+            "{:?}",
+            agents
+        );
+    }
+
+    /// 模拟创建 PR、添加标签与请求审阅的接口，记录请求路径与请求体；
+    /// 添加标签时返回 422，模拟标签不存在
+    async fn mock_pulls_api() -> (String, Recorded) {
+        use axum::extract::State;
+        use axum::http::{StatusCode, Uri};
+        use axum::routing::post;
+        use axum::{Json, Router};
+
+        async fn record(
+            State(calls): State<Recorded>,
+            uri: Uri,
+            Json(body): Json<serde_json::Value>,
+        ) -> (StatusCode, Json<serde_json::Value>) {
+            let path = uri.path().to_string();
+            calls
+                .lock()
+                .unwrap()
+                .push(serde_json::json!({"path": path, "body": body}));
+            if path.ends_with("/labels") {
+                return (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(serde_json::json!({"message": "Label does not exist"})),
+                );
+            }
+            (
+                StatusCode::CREATED,
+                Json(serde_json::json!({
+                    "url": "http://localhost/repos/o/r/pulls/7",
+                    "id": 1,
+                    "number": 7,
+                    "locked": false,
+                    "html_url": "https://github.com/o/r/pull/7",
+                    "head": {"ref": "contrib-test", "sha": "head"},
+                    "base": {"ref": "main", "sha": "base"}
+                })),
+            )
+        }
+
+        let calls: Recorded = Arc::default();
+        let app = Router::new()
+            .route("/repos/{owner}/{repo}/pulls", post(record))
+            .route("/repos/{owner}/{repo}/issues/{number}/labels", post(record))
+            .route(
+                "/repos/{owner}/{repo}/pulls/{number}/requested_reviewers",
+                post(record),
+            )
+            .with_state(calls.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), calls)
+    }
+
+    #[tokio::test]
+    async fn test_pull_request_draft_labels_and_reviewers() {
+        let (base, calls) = mock_pulls_api().await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let options = PullRequestOptions {
+            draft: true,
+            labels: vec!["submission".to_string(), "needs-review".to_string()],
+            reviewers: vec!["octocat".to_string()],
+        };
+
+        // 标签不存在时仍返回 PR 地址
+        let url = sample_submission(vec![])
+            .pull_request_with(&octocrab, "o", "r", "main", &options)
+            .await
+            .unwrap();
+        assert_eq!(url, "https://github.com/o/r/pull/7");
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0]["path"], "/repos/o/r/pulls");
+        assert_eq!(calls[0]["body"]["draft"], true);
+        assert_eq!(calls[0]["body"]["head"], "contrib-test");
+        assert_eq!(calls[1]["path"], "/repos/o/r/issues/7/labels");
+        assert_eq!(
+            calls[1]["body"]["labels"],
+            serde_json::json!(["submission", "needs-review"])
+        );
+        assert_eq!(calls[2]["path"], "/repos/o/r/pulls/7/requested_reviewers");
+        assert_eq!(
+            calls[2]["body"]["reviewers"],
+            serde_json::json!(["octocat"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_request_without_options() {
+        let (base, calls) = mock_pulls_api().await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+
+        sample_submission(vec![])
+            .pull_request_with(&octocrab, "o", "r", "main", &PullRequestOptions::default())
+            .await
+            .unwrap();
+
+        // 未配置标签与审阅者时只创建 PR
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0]["body"]["draft"], false);
+    }
+
+    #[tokio::test]
+    async fn test_non_main_base_branch() {
+        let (base, calls) = mock_git_api(usize::MAX).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let submission = sample_submission(vec![]);
+        submission
+            .push_branch_with(&octocrab, "o", "r", "trunk", usize::MAX)
+            .await
+            .unwrap();
+
+        // 从配置的基准分支获取引用
+        let fetched: Vec<_> = calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c["method"] == "GET")
+            .map(|c| c["path"].clone())
+            .collect();
+        assert_eq!(fetched, vec!["/repos/o/r/git/ref/heads/trunk"]);
+
+        // PR 合并到同一分支
+        let (base, calls) = mock_pulls_api().await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        submission
+            .pull_request_with(&octocrab, "o", "r", "trunk", &PullRequestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(calls.lock().unwrap()[0]["body"]["base"], "trunk");
+    }
+
+    #[tokio::test]
+    async fn test_delete_branch() {
+        let (base, calls) = mock_git_api(usize::MAX).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+        let submission = sample_submission(vec![]);
+
+        submission.delete_branch_with(&octocrab, "o", "r").await;
+        assert_eq!(
+            calls.lock().unwrap().as_slice(),
+            &[serde_json::json!({
+                "path": "/repos/o/r/git/refs/heads/contrib-test",
+                "method": "DELETE"
+            })]
+        );
+
+        // 删除失败（此处为连接失败）只记录日志，不会 panic 或返回错误
+        let octocrab = Octocrab::builder()
+            .base_uri("http://127.0.0.1:9")
+            .unwrap()
+            .build()
+            .unwrap();
+        submission.delete_branch_with(&octocrab, "o", "r").await;
+    }
+
+    #[tokio::test]
+    async fn test_push_branch_total_image_bytes() {
+        let (base, calls) = mock_git_api(usize::MAX).await;
+        let octocrab = Octocrab::builder().base_uri(base).unwrap().build().unwrap();
+
+        // 封面 3 字节 + 图片 5 字节，恰好等于上限
+        let mut submission = sample_submission(vec![]);
+        submission.images = vec![image(5)];
+        submission
+            .push_branch_with(&octocrab, "o", "r", "main", 8)
+            .await
+            .unwrap();
+        assert_eq!(bodies(&calls, "/git/refs").len(), 1);
+        assert_eq!(bodies(&calls, "/git/blobs").len(), 3);
+
+        // 多 1 字节即被拒绝，不会上传文件或创建分支
+        submission.images = vec![image(6)];
+        let err = submission
+            .push_branch_with(&octocrab, "o", "r", "main", 8)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("图片总大小 9 字节"), "{}", err);
+        assert_eq!(bodies(&calls, "/git/refs").len(), 1);
+        assert_eq!(bodies(&calls, "/git/blobs").len(), 3);
+    }
+
+    #[test]
+    fn test_validate_tags_and_images_count() {
+        let submission = sample_submission(vec!["a".into(), "b".into(), "c".into()]);
+        assert_only_fails_on(&submission, "tags");
+
+        let mut submission = sample_submission(vec![]);
+        submission.images = vec![image(1), image(1)];
+        assert_only_fails_on(&submission, "images");
+    }
+
+    #[test]
+    fn test_validate_tag_length_and_allowlist() {
+        let submission = sample_submission(vec!["rust".into(), "toolong".into()]);
+        assert_only_fails_on(&submission, "tags");
+
+        let mut cfg = test_config();
+        cfg.allowed_tags = vec!["Rust".into(), "go".into()];
+        let submission = sample_submission(vec!["rust".into(), "go".into()]);
+        assert!(submission.validate(&cfg).is_ok());
+
+        let submission = sample_submission(vec!["rust".into(), "java".into()]);
+        let errors = submission.validate(&cfg).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "tags");
+        assert!(errors[0].message.contains("java"), "{}", errors[0].message);
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = vec![
+            " Rust ".to_string(),
+            "rust".to_string(),
+            "".to_string(),
+            "  ".to_string(),
+            "Web".to_string(),
+            "RUST".to_string(),
+        ];
+        assert_eq!(normalize_tags(tags), vec!["rust", "web"]);
+    }
+
+    #[test]
+    fn test_validate_image_size() {
+        let mut submission = sample_submission(vec![]);
+        submission.cover = image(5);
+        assert_only_fails_on(&submission, "cover");
+
+        let mut submission = sample_submission(vec![]);
+        submission.images = vec![image(5)];
+        assert_only_fails_on(&submission, "images");
+    }
+
+    #[test]
+    fn test_empty_tags_use_same_placeholder() {
+        let submission = sample_submission(vec![]);
+
+        assert!(submission.to_hexo().contains("tags:\n- 无\n"));
+        assert!(submission.to_pr_body().contains("**Tags:** 无\n"));
+        assert!(submission.to_info().contains("标签: 无\n"));
+    }
+
+    #[test]
+    fn test_non_empty_tags() {
+        let submission = sample_submission(vec!["科幻".to_string(), "短篇".to_string()]);
+
+        assert!(submission.to_hexo().contains("tags:\n- 科幻\n- 短篇\n"));
+        assert!(submission.to_pr_body().contains("**Tags:** 科幻, 短篇\n"));
+        assert!(submission.to_info().contains("标签: 科幻, 短篇\n"));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::config::AppConfig;
+use arc_swap::ArcSwap;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use std::sync::Arc;
+
+/// 出站请求统一使用的 User-Agent，配置中的 `{version}` 会替换为当前版本号
+pub fn user_agent() -> String {
+    render_user_agent(&AppConfig::global().user_agent)
+}
+
+fn render_user_agent(template: &str) -> String {
+    template.replace("{version}", env!("CARGO_PKG_VERSION"))
+}
+
+/// 构建带有指定 User-Agent 的 reqwest 客户端
+pub fn build_client(user_agent: &str) -> reqwest::Result<Client> {
+    Client::builder().user_agent(user_agent).build()
+}
+
+static CLIENT: Lazy<ArcSwap<Client>> = Lazy::new(|| {
+    ArcSwap::from_pointee(build_client(&user_agent()).expect("初始化 HTTP 客户端失败"))
+});
+
+/// 获取全局共享的 reqwest 客户端
+pub fn client() -> Client {
+    Client::clone(&CLIENT.load())
+}
+
+/// 按当前配置的 User-Agent 重新创建全局客户端，重新加载配置后调用
+pub fn reload_client() -> reqwest::Result<()> {
+    CLIENT.store(Arc::new(build_client(&user_agent())?));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::http::{HeaderMap, header};
+    use axum::routing::get;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_render_user_agent() {
+        let ua = render_user_agent("QidianMini/{version}");
+        assert_eq!(ua, format!("QidianMini/{}", env!("CARGO_PKG_VERSION")));
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_sent_to_server() {
+        // 本地 mock 服务：原样返回收到的 User-Agent
+        let app = Router::new().route(
+            "/",
+            get(|headers: HeaderMap| async move {
+                headers
+                    .get(header::USER_AGENT)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let ua = render_user_agent("QidianMini/{version} (+https://qidian.space)");
+        let client = build_client(&ua).unwrap();
+        let received = client
+            .get(format!("http://{}/", addr))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(received, ua);
+        assert!(received.contains(env!("CARGO_PKG_VERSION")));
+    }
+}
@@ -1,74 +1,373 @@
-use std::fs;
-use std::sync::OnceLock;
+use std::io;
+use std::path::Path;
 
-use tracing_subscriber::{EnvFilter, fmt};
+use once_cell::sync::OnceCell;
+use tracing::Level;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry, fmt, reload};
 
-use crate::config::{AppConfig, LogFormat};
+use crate::config::{AppConfig, LogConfig, LogFormat, LogLevel};
 
-static GUARD: OnceLock<tracing_appender::non_blocking::WorkerGuard> = OnceLock::new();
+type BoxSubscriber = Box<dyn tracing::Subscriber + Send + Sync>;
+type BoxLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// 无论全局级别如何都单独落盘的级别，文件中包含该级别及更严重的事件
+const SEVERITY_FILES: [LogLevel; 2] = [LogLevel::Error, LogLevel::Warn];
+
+/// `init_tracing` 安装的级别过滤器，重新加载配置时通过 [`set_level`] 调整
+static LEVEL: OnceCell<LevelHandle> = OnceCell::new();
+
+/// 主日志文件与 stdout 的 EnvFilter 句柄，可在运行时替换过滤级别
+#[derive(Clone, Default)]
+struct LevelHandle(Vec<reload::Handle<EnvFilter, Registry>>);
+
+impl LevelHandle {
+    /// 创建按 `level` 过滤、之后可通过本句柄调整的过滤器
+    fn filter(&mut self, level: LogLevel) -> reload::Layer<EnvFilter, Registry> {
+        let (filter, handle) = reload::Layer::new(env_filter(level));
+        self.0.push(handle);
+        filter
+    }
+
+    fn set(&self, level: LogLevel) -> Result<(), reload::Error> {
+        for handle in &self.0 {
+            handle.reload(env_filter(level))?;
+        }
+        Ok(())
+    }
+}
 
 /// 初始化全局 tracing（在 main() 里调用一次）
-pub fn init_tracing() {
+///
+/// 返回的 guard 需要在 main() 中一直持有，退出时丢弃它们才会把缓冲的日志写入文件
+pub fn init_tracing() -> Vec<WorkerGuard> {
     let cfg = AppConfig::global();
     let log_cfg = &cfg.log;
 
-    // 1. 确保日志目录存在
-    if let Err(e) = fs::create_dir_all(&log_cfg.dir) {
-        eprintln!("Failed to create log directory {:?}: {e}", log_cfg.dir);
-    }
+    let (subscriber, guards, level) = build_subscriber(log_cfg);
+    subscriber.init();
+    let _ = LEVEL.set(level);
 
-    // 2. 计算日志文件路径：/var/log/qidian/{level}.log
-    let log_file_path = log_cfg.file_for_level(log_cfg.level);
+    tracing::info!(
+        level = %log_cfg.level,
+        format = %log_cfg.format,
+        dir = ?log_cfg.dir,
+        "tracing initialized",
+    );
+    guards
+}
+
+/// 把主日志文件与 stdout 的过滤级别调整为 `level`，设置了 RUST_LOG 时仍以其为准
+///
+/// 各级别日志文件的文件名在启动时确定，不随之改变
+pub fn set_level(level: LogLevel) {
+    let Some(handle) = LEVEL.get() else {
+        return;
+    };
+    match handle.set(level) {
+        Ok(()) => tracing::info!(level = %level, "log level updated"),
+        Err(e) => tracing::warn!("LOG: failed to update log level: {}", e),
+    }
+}
 
-    let file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_file_path)
+/// 按天滚动的日志文件：{dir}/{level}.YYYY-MM-DD.log，目录不存在时自动创建
+fn rolling_file(dir: &Path, level: LogLevel) -> RollingFileAppender {
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(level.as_str())
+        .filename_suffix("log")
+        .build(dir)
         .unwrap_or_else(|e| {
-            panic!("Failed to open log file {:?}: {e}", log_file_path);
-        });
+            panic!("Failed to open {level} log file in {:?}: {e}", dir);
+        })
+}
 
+/// 按日志配置构建写入文件（以及按需写入 stdout）的 subscriber，返回的 guard 被丢弃后不再写入文件
+fn build_subscriber(log_cfg: &LogConfig) -> (BoxSubscriber, Vec<WorkerGuard>, LevelHandle) {
+    let mut guards = Vec::new();
     // non_blocking writer + guard
-    let (non_blocking, guard) = tracing_appender::non_blocking(file);
-    let _ = GUARD.set(guard);
-
-    // 3. EnvFilter：优先用 RUST_LOG，其次用配置里的 level
-    let env_filter = EnvFilter::try_from_default_env()
-        .or_else(|_| EnvFilter::try_new(log_cfg.level.as_str()))
-        .unwrap_or_else(|_| EnvFilter::new("info"));
-
-    // 4. 根据不同 format 构建不同的 subscriber
-    match log_cfg.format {
-        LogFormat::Text => {
-            fmt()
-                .with_env_filter(env_filter)
-                .with_target(false)
-                .with_writer(non_blocking)
-                .init();
+    let mut open = |level| {
+        let (writer, guard) = tracing_appender::non_blocking(rolling_file(&log_cfg.dir, level));
+        guards.push(guard);
+        BoxMakeWriter::new(writer)
+    };
+
+    let main = open(log_cfg.level);
+    let severity = SEVERITY_FILES
+        .into_iter()
+        .filter(|level| *level != log_cfg.level)
+        .map(|level| (level, open(level)))
+        .collect();
+
+    let (subscriber, level) =
+        layered_subscriber(log_cfg, main, severity, BoxMakeWriter::new(io::stdout));
+    (subscriber, guards, level)
+}
+
+/// 主日志文件与 stdout 使用同一级别的 EnvFilter（可通过返回的句柄调整），
+/// 各严重级别文件只按自身级别过滤
+fn layered_subscriber(
+    log_cfg: &LogConfig,
+    main: BoxMakeWriter,
+    severity: Vec<(LogLevel, BoxMakeWriter)>,
+    stdout: BoxMakeWriter,
+) -> (BoxSubscriber, LevelHandle) {
+    let mut level = LevelHandle::default();
+    let mut layers = vec![
+        fmt_layer(log_cfg.format, main)
+            .with_filter(level.filter(log_cfg.level))
+            .boxed(),
+    ];
+    if log_cfg.stdout {
+        layers.push(
+            fmt_layer(log_cfg.format, stdout)
+                .with_filter(level.filter(log_cfg.level))
+                .boxed(),
+        );
+    }
+    for (level, writer) in severity {
+        layers.push(
+            fmt_layer(log_cfg.format, writer)
+                .with_filter(level_filter(level))
+                .boxed(),
+        );
+    }
+
+    (Box::new(Registry::default().with(layers)), level)
+}
+
+/// 优先用 RUST_LOG，其次用配置里的 level
+fn env_filter(level: LogLevel) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(level.as_str()))
+        .unwrap_or_else(|_| EnvFilter::new("info"))
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    LevelFilter::from_level(match level {
+        LogLevel::Error => Level::ERROR,
+        LogLevel::Warn => Level::WARN,
+        LogLevel::Info => Level::INFO,
+        LogLevel::Debug => Level::DEBUG,
+        LogLevel::Trace => Level::TRACE,
+    })
+}
+
+/// 根据不同 format 构建输出 layer
+fn fmt_layer(format: LogFormat, writer: BoxMakeWriter) -> BoxLayer {
+    let layer = fmt::layer().with_target(false).with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(layer),
+        LogFormat::Compact => Box::new(layer.compact()),
+        // 输出当前 span 及其所有父 span 的字段，request_id 等关联字段由此带出
+        LogFormat::Json => Box::new(layer.json().with_current_span(true).with_span_list(true)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    /// 读取目录中某个级别的滚动日志文件
+    fn read_log(dir: &Path, level: LogLevel) -> String {
+        let prefix = format!("{}.", level.as_str());
+        let path = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".log"))
+            })
+            .unwrap_or_else(|| panic!("no {level} log file in {:?}", dir));
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_build_subscriber_writes_to_log_dir() {
+        for format in [LogFormat::Text, LogFormat::Compact, LogFormat::Json] {
+            let dir = tempfile::tempdir().unwrap();
+            let log_cfg = LogConfig {
+                level: LogLevel::Info,
+                format,
+                // 不存在的子目录会被自动创建
+                dir: dir.path().join("logs"),
+                stdout: false,
+                slow_request_ms: 3000,
+            };
+
+            let (subscriber, guards, _) = build_subscriber(&log_cfg);
+            tracing::subscriber::with_default(subscriber, || {
+                tracing::info!("tracing smoke test");
+            });
+            // 丢弃 guard 会把缓冲的日志刷入文件
+            drop(guards);
+
+            let written = read_log(&log_cfg.dir, LogLevel::Info);
+            assert!(written.contains("tracing smoke test"), "format={format}");
+        }
+    }
+
+    #[test]
+    fn test_severity_files_ignore_global_level() {
+        for level in [LogLevel::Info, LogLevel::Error] {
+            let dir = tempfile::tempdir().unwrap();
+            let log_cfg = LogConfig {
+                level,
+                format: LogFormat::Text,
+                dir: dir.path().to_path_buf(),
+                stdout: false,
+                slow_request_ms: 3000,
+            };
+
+            let (subscriber, guards, _) = build_subscriber(&log_cfg);
+            tracing::subscriber::with_default(subscriber, || {
+                tracing::error!("event-error");
+                tracing::warn!("event-warn");
+                tracing::info!("event-info");
+                tracing::debug!("event-debug");
+            });
+            drop(guards);
+
+            let error = read_log(dir.path(), LogLevel::Error);
+            assert!(error.contains("event-error"), "level={level}");
+            assert!(!error.contains("event-warn"), "level={level}");
+
+            // 全局级别为 error 时，警告仍然写入 warn 文件
+            let warn = read_log(dir.path(), LogLevel::Warn);
+            assert!(warn.contains("event-error"), "level={level}");
+            assert!(warn.contains("event-warn"), "level={level}");
+            assert!(!warn.contains("event-info"), "level={level}");
+
+            if level == LogLevel::Info {
+                let info = read_log(dir.path(), LogLevel::Info);
+                assert!(info.contains("event-warn"));
+                assert!(info.contains("event-info"));
+                assert!(!info.contains("event-debug"));
+            }
         }
-        LogFormat::Compact => {
-            fmt()
-                .with_env_filter(env_filter)
-                .with_target(false)
-                .compact()
-                .with_writer(non_blocking)
-                .init();
+    }
+
+    /// 把写入的内容收集到内存中
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
         }
-        LogFormat::Json => {
-            fmt()
-                .with_env_filter(env_filter)
-                .with_target(false)
-                .json()
-                .with_writer(non_blocking)
-                .init();
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
         }
     }
 
-    tracing::info!(
-        level = %log_cfg.level,
-        format = %log_cfg.format,
-        dir = ?log_cfg.dir,
-        file = ?log_file_path,
-        "tracing initialized",
-    );
+    impl Capture {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+
+        fn writer(&self) -> BoxMakeWriter {
+            let capture = self.clone();
+            BoxMakeWriter::new(move || capture.clone())
+        }
+    }
+
+    #[test]
+    fn test_json_output_includes_request_id() {
+        let log_cfg = LogConfig {
+            level: LogLevel::Info,
+            format: LogFormat::Json,
+            dir: PathBuf::new(),
+            stdout: false,
+            slow_request_ms: 3000,
+        };
+        let file = Capture::default();
+        let (subscriber, _) =
+            layered_subscriber(&log_cfg, file.writer(), Vec::new(), file.writer());
+
+        let rid = uuid::Uuid::new_v4();
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request", request_id = %rid);
+            let handler = tracing::info_span!(parent: &request, "send_mail");
+            handler.in_scope(|| tracing::info!("mail sent"));
+        });
+
+        let line: serde_json::Value = serde_json::from_str(file.contents().trim()).unwrap();
+        assert_eq!(line["fields"]["message"], "mail sent");
+        // 事件所在的 span 不带 request_id 时，可以从父 span 中找到
+        let spans = line["spans"].as_array().unwrap();
+        assert!(
+            spans
+                .iter()
+                .any(|span| span["request_id"] == rid.to_string())
+        );
+    }
+
+    #[test]
+    fn test_stdout_layer_receives_same_events() {
+        for stdout in [true, false] {
+            let log_cfg = LogConfig {
+                level: LogLevel::Info,
+                format: LogFormat::Text,
+                dir: PathBuf::new(),
+                stdout,
+                slow_request_ms: 3000,
+            };
+            let file = Capture::default();
+            let out = Capture::default();
+
+            let (subscriber, _) =
+                layered_subscriber(&log_cfg, file.writer(), Vec::new(), out.writer());
+            tracing::subscriber::with_default(subscriber, || {
+                tracing::info!("written twice");
+                // 两个 layer 共用同一个过滤器
+                tracing::trace!("filtered out");
+            });
+
+            assert!(file.contents().contains("written twice"));
+            assert!(!file.contents().contains("filtered out"));
+            assert_eq!(out.contents().contains("written twice"), stdout);
+            assert!(!out.contents().contains("filtered out"));
+        }
+    }
+
+    #[test]
+    fn test_level_can_be_changed_at_runtime() {
+        let log_cfg = LogConfig {
+            level: LogLevel::Info,
+            format: LogFormat::Text,
+            dir: PathBuf::new(),
+            stdout: true,
+            slow_request_ms: 3000,
+        };
+        let file = Capture::default();
+        let out = Capture::default();
+        let (subscriber, level) =
+            layered_subscriber(&log_cfg, file.writer(), Vec::new(), out.writer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("before reload");
+            level.set(LogLevel::Debug).unwrap();
+            tracing::debug!("after raising");
+            level.set(LogLevel::Warn).unwrap();
+            tracing::info!("after lowering");
+        });
+
+        for capture in [&file, &out] {
+            let contents = capture.contents();
+            assert!(!contents.contains("before reload"));
+            assert!(contents.contains("after raising"));
+            assert!(!contents.contains("after lowering"));
+        }
+    }
 }
@@ -1,31 +1,130 @@
-use chrono::Local;
+use chrono::{DateTime, Local, SecondsFormat};
+use std::borrow::Cow;
 
 #[derive(Clone)]
 pub struct Markdown {
     pub author: String,
     pub title: String,
+    /// 投稿时间，以 ISO-8601 格式写入 `date`
+    pub date: DateTime<Local>,
+    /// 由标题或编辑指定的 slug 生成，为空时省略
+    pub slug: String,
+    /// 为空时省略 `categories`
+    pub categories: Vec<String>,
     pub tags: Vec<String>,
+    pub tags_placeholder: String,
+    /// 封面文件名
+    pub cover: String,
+    /// 图集图片的站内路径，按投稿顺序排列
+    pub photos: Vec<String>,
     pub content: String,
 }
 
+/// 统一的标签拼接：标签为空时返回占位文本
+pub fn join_tags(tags: &[String], sep: &str, placeholder: &str) -> String {
+    if tags.is_empty() {
+        placeholder.to_string()
+    } else {
+        tags.join(sep)
+    }
+}
+
+/// 由标题生成 URL 中使用的 slug：字母与数字转为小写保留，其余字符合并为单个 `-`
+///
+/// 中文等非 ASCII 文字同样保留，由 Hexo 在生成链接时进行百分号编码
+pub fn url_slug(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// 把字符串写成 YAML 标量：可能被误解析的值（含 `: `、` #`、以引号等符号开头，或形如数字、布尔值）
+/// 使用双引号并转义
+pub fn yaml_scalar(s: &str) -> Cow<'_, str> {
+    const INDICATORS: &[char] = &[
+        '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@',
+        '`',
+    ];
+    let reserved = matches!(
+        s.to_ascii_lowercase().as_str(),
+        "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+    );
+    let needs_quotes = s.is_empty()
+        || reserved
+        || s.parse::<f64>().is_ok()
+        || s.starts_with(INDICATORS)
+        || s.starts_with(char::is_whitespace)
+        || s.ends_with(char::is_whitespace)
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.contains(char::is_control);
+    if !needs_quotes {
+        return Cow::Borrowed(s);
+    }
+
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+/// YAML 列表，每项一行
+fn yaml_list<S: AsRef<str>>(items: &[S]) -> String {
+    items
+        .iter()
+        .map(|item| format!("- {}\n", yaml_scalar(item.as_ref())))
+        .collect()
+}
+
 pub trait ToHexo {
     fn to_hexo(&self) -> String;
 }
 
 impl ToHexo for Markdown {
     fn to_hexo(&self) -> String {
-        // 当前时间作为日期
-        let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let date = self.date.to_rfc3339_opts(SecondsFormat::Secs, false);
+
+        let slug_yaml = if self.slug.is_empty() {
+            String::new()
+        } else {
+            format!("slug: {}\n", yaml_scalar(&self.slug))
+        };
 
-        // 处理 tags
+        let categories_yaml = if self.categories.is_empty() {
+            String::new()
+        } else {
+            format!("categories:\n{}", yaml_list(&self.categories))
+        };
+
+        // 处理 tags，为空时写入占位文本
         let tags_yaml = if self.tags.is_empty() {
-            "".to_string()
+            yaml_list(&[&self.tags_placeholder])
         } else {
-            let mut s = String::new();
-            for tag in &self.tags {
-                s.push_str(&format!("- {}\n", tag));
-            }
-            s
+            yaml_list(&self.tags)
+        };
+
+        // 按顺序列出图集，没有图片时省略
+        let photos_yaml = if self.photos.is_empty() {
+            String::new()
+        } else {
+            format!("photos:\n{}", yaml_list(&self.photos))
         };
 
         // 禁止进行缩进
@@ -34,15 +133,19 @@ impl ToHexo for Markdown {
 title: {title}
 author: {author}
 date: {date}
-tags:
-{tags}cover: cover.webp
----
+{slug}{categories}tags:
+{tags}cover: {cover}
+{photos}---
 {content}
 "#,
-            title = self.title,
-            author = self.author,
-            date = now,
+            title = yaml_scalar(&self.title),
+            author = yaml_scalar(&self.author),
+            date = date,
+            slug = slug_yaml,
+            categories = categories_yaml,
             tags = tags_yaml,
+            cover = yaml_scalar(&self.cover),
+            photos = photos_yaml,
             content = self.content,
         )
     }
@@ -50,40 +153,94 @@ tags:
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::markdown::{Markdown, ToHexo};
-    use chrono::NaiveDateTime;
+    use crate::utils::markdown::{Markdown, ToHexo, url_slug, yaml_scalar};
+    use chrono::{DateTime, Local, TimeZone};
+    use yaml_rust2::{Yaml, YamlLoader};
 
-    #[test]
-    fn test_markdown_to_hexo() {
-        let md = Markdown {
+    fn sample() -> Markdown {
+        Markdown {
             author: "Alice".to_string(),
             title: "My Post".to_string(),
+            date: Local.with_ymd_and_hms(2024, 5, 1, 8, 30, 0).unwrap(),
+            slug: "my-post".to_string(),
+            categories: vec![],
             tags: vec!["rust".to_string(), "hexo".to_string()],
+            tags_placeholder: "无".to_string(),
+            cover: "cover.webp".to_string(),
+            photos: vec![],
             content: "Hello, world!".to_string(),
-        };
+        }
+    }
+
+    /// 取出 front-matter 并按 YAML 解析
+    fn front_matter(hexo: &str) -> Yaml {
+        let yaml = hexo
+            .strip_prefix("---\n")
+            .and_then(|rest| rest.split_once("\n---\n"))
+            .map(|(yaml, _)| yaml)
+            .expect("front-matter should be delimited by ---");
+        YamlLoader::load_from_str(yaml)
+            .expect("front-matter should be valid YAML")
+            .remove(0)
+    }
 
+    #[test]
+    fn test_markdown_to_hexo() {
+        let md = sample();
         let hexo_str = md.to_hexo();
 
         // 检查 front matter 基础结构
         assert!(hexo_str.contains("---"));
         assert!(hexo_str.contains("title: My Post"));
         assert!(hexo_str.contains("author: Alice"));
+        assert!(hexo_str.contains("slug: my-post"));
         assert!(hexo_str.contains("tags:"));
         assert!(hexo_str.contains("- rust"));
         assert!(hexo_str.contains("- hexo"));
         assert!(hexo_str.contains("Hello, world!"));
+        assert!(hexo_str.contains("cover: cover.webp"));
 
-        // 检查 date 格式是否是 yyyy-MM-dd HH:mm:ss
-        let date_line = hexo_str
-            .lines()
-            .find(|l| l.starts_with("date: "))
-            .expect("date line should exist");
-
-        let date_str = date_line.trim_start_matches("date: ").trim();
-        assert!(
-            NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S").is_ok(),
-            "date format should be valid, got {}",
-            date_str
+        assert!(!hexo_str.contains("photos:"));
+        assert!(!hexo_str.contains("categories:"));
+
+        // date 为 ISO-8601 格式的投稿时间
+        let yaml = front_matter(&hexo_str);
+        let date_str = yaml["date"].as_str().expect("date should be a string");
+        assert_eq!(
+            DateTime::parse_from_rfc3339(date_str).expect("date should be ISO-8601"),
+            md.date
         );
     }
+
+    #[test]
+    fn test_front_matter_escapes_title() {
+        let mut md = sample();
+        md.title = r#"Rust: "所有权" #1 \ 入门"#.to_string();
+        md.author = "true".to_string();
+        md.slug = url_slug(&md.title);
+        md.categories = vec!["科普".to_string(), "编程: 入门".to_string()];
+        md.tags = vec!["C#".to_string(), "2024".to_string()];
+        md.photos = vec!["/photos/a/001.webp".to_string()];
+
+        let yaml = front_matter(&md.to_hexo());
+        assert_eq!(yaml["title"].as_str(), Some(md.title.as_str()));
+        assert_eq!(yaml["author"].as_str(), Some("true"));
+        assert_eq!(yaml["slug"].as_str(), Some("rust-所有权-1-入门"));
+        assert_eq!(yaml["categories"][1].as_str(), Some("编程: 入门"));
+        assert_eq!(yaml["tags"][0].as_str(), Some("C#"));
+        assert_eq!(yaml["tags"][1].as_str(), Some("2024"));
+        assert_eq!(yaml["photos"][0].as_str(), Some("/photos/a/001.webp"));
+        assert!(yaml["date"].as_str().is_some());
+    }
+
+    #[test]
+    fn test_url_slug_and_yaml_scalar() {
+        assert_eq!(url_slug("  Hello, World!  "), "hello-world");
+        assert_eq!(url_slug("深空 探索：第 2 期"), "深空-探索-第-2-期");
+        assert_eq!(url_slug("!!!"), "");
+
+        assert_eq!(yaml_scalar("普通标题"), "普通标题");
+        assert_eq!(yaml_scalar("a: b"), r#""a: b""#);
+        assert_eq!(yaml_scalar("say \"hi\"\n"), r#""say \"hi\"\n""#);
+    }
 }
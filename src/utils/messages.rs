@@ -0,0 +1,278 @@
+use axum::http::{HeaderMap, header};
+
+/// 面向用户的邮件与提示所用的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Lang {
+    /// 解析 `zh`、`zh-CN`、`en-US` 等语言标签，只看主语言，不支持的语言返回 None
+    pub fn parse(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next().unwrap_or_default();
+        if primary.eq_ignore_ascii_case("zh") {
+            Some(Self::Zh)
+        } else if primary.eq_ignore_ascii_case("en") {
+            Some(Self::En)
+        } else {
+            None
+        }
+    }
+
+    /// 按 `Accept-Language` 中的权重选出支持的语言，权重相同时取靠前的
+    pub fn from_accept_language(headers: &HeaderMap) -> Option<Self> {
+        let value = headers.get(header::ACCEPT_LANGUAGE)?.to_str().ok()?;
+        let mut best: Option<(Self, f32)> = None;
+        for item in value.split(',') {
+            let mut parts = item.split(';');
+            let Some(lang) = parts.next().and_then(Self::parse) else {
+                continue;
+            };
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+                best = Some((lang, quality));
+            }
+        }
+        best.map(|(lang, _)| lang)
+    }
+
+    /// 请求中的 `lang` 优先，其次 `Accept-Language`，都不支持时使用中文
+    pub fn negotiate(lang: Option<&str>, headers: &HeaderMap) -> Self {
+        lang.and_then(Self::parse)
+            .or_else(|| Self::from_accept_language(headers))
+            .unwrap_or_default()
+    }
+}
+
+/// 文案目录中的条目，`{name}` 形式的占位符由 [`render`] 替换
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    /// 标签之间的分隔符
+    TagSeparator,
+    /// 投稿确认邮件：`{title}` `{author}` `{tags}` `{email}` `{url}`
+    ContributorText,
+    /// 投稿确认邮件的 HTML 版本，占位符同上
+    ContributorHtml,
+    /// 文件分享邮件主题：`{file}`
+    ShareSubject,
+    /// 文件分享邮件：`{applicant}` `{link}` `{file}` `{size}` `{md5}` `{sha256}` `{time}`
+    ShareBody,
+    /// 验证码邮件主题：`{code}` `{ttl_minutes}`
+    CodeSubject,
+    /// 验证码邮件，占位符同上
+    CodeBody,
+    /// 验证码发送成功的提示：`{email}`
+    CodeSent,
+}
+
+/// 查找文案模板
+pub fn text(lang: Lang, msg: Msg) -> &'static str {
+    match (lang, msg) {
+        (Lang::Zh, Msg::TagSeparator) => "、",
+        (Lang::En, Msg::TagSeparator) => ", ",
+        (Lang::Zh, Msg::ContributorText) => {
+            r#"感谢您的投稿！
+
+投稿详情：
+- 文章标题：《{title}》
+- 作者：{author}
+- 标签：{tags}
+- 投稿邮箱：{email}
+
+您的投稿已成功提交！我们已创建 GitHub Pull Request 进行审核处理。
+
+🔗 查看处理进度：{url}
+（如链接无法点击，请复制到浏览器打开）
+
+审核流程：
+1. 管理员将会审核您的投稿内容
+2. 审核通过后，您的文章将会被发布
+3. 如有需要修改的地方，我们会通过邮件与您沟通
+
+预计审核时间：1-3个工作日
+如有任何问题，请回复此邮件与我们联系。
+
+再次感谢您对科幻文学的支持！"#
+        }
+        (Lang::En, Msg::ContributorText) => {
+            r#"Thank you for your submission!
+
+Submission details:
+- Title: "{title}"
+- Author: {author}
+- Tags: {tags}
+- Email: {email}
+
+Your submission has been received, and a GitHub Pull Request has been opened for review.
+
+🔗 Track its progress: {url}
+(If the link is not clickable, copy it into your browser.)
+
+Review process:
+1. An editor will review your submission
+2. Once approved, your article will be published
+3. If changes are needed, we will contact you by email
+
+Reviews usually take 1-3 business days.
+If you have any questions, just reply to this email.
+
+Thank you again for supporting science fiction!"#
+        }
+        (Lang::Zh, Msg::ContributorHtml) => {
+            r#"<p>感谢您的投稿！</p>
+<p>投稿详情：</p>
+<ul>
+<li>文章标题：《{title}》</li>
+<li>作者：{author}</li>
+<li>标签：{tags}</li>
+<li>投稿邮箱：{email}</li>
+</ul>
+<p>您的投稿已成功提交！我们已创建 GitHub Pull Request 进行审核处理。</p>
+<p>🔗 <a href="{url}">查看处理进度</a></p>
+<p>审核流程：</p>
+<ol>
+<li>管理员将会审核您的投稿内容</li>
+<li>审核通过后，您的文章将会被发布</li>
+<li>如有需要修改的地方，我们会通过邮件与您沟通</li>
+</ol>
+<p>预计审核时间：1-3个工作日<br>如有任何问题，请回复此邮件与我们联系。</p>
+<p>再次感谢您对科幻文学的支持！</p>"#
+        }
+        (Lang::En, Msg::ContributorHtml) => {
+            r#"<p>Thank you for your submission!</p>
+<p>Submission details:</p>
+<ul>
+<li>Title: "{title}"</li>
+<li>Author: {author}</li>
+<li>Tags: {tags}</li>
+<li>Email: {email}</li>
+</ul>
+<p>Your submission has been received, and a GitHub Pull Request has been opened for review.</p>
+<p>🔗 <a href="{url}">Track its progress</a></p>
+<p>Review process:</p>
+<ol>
+<li>An editor will review your submission</li>
+<li>Once approved, your article will be published</li>
+<li>If changes are needed, we will contact you by email</li>
+</ol>
+<p>Reviews usually take 1-3 business days.<br>If you have any questions, just reply to this email.</p>
+<p>Thank you again for supporting science fiction!</p>"#
+        }
+        (Lang::Zh, Msg::ShareSubject) => "文件分享通知 - {file}",
+        (Lang::En, Msg::ShareSubject) => "File ready for download - {file}",
+        (Lang::Zh, Msg::ShareBody) => {
+            "尊敬的 {applicant}，您好：\n\n\
+            您申请的文件已准备就绪，可通过以下链接下载：\n\n\
+            下载地址：{link}\n\
+            文件名：{file}\n\
+            文件大小：{size} 字节\n\
+            MD5：{md5}\n\
+            SHA-256：{sha256}\n\
+            生成时间：{time}\n\n\
+            下载后可核对以上校验值，确认文件完整。\n\
+            链接有效期为 24 小时，请尽快下载。\n\n\
+            —— 系统自动发送，请勿回复。"
+        }
+        (Lang::En, Msg::ShareBody) => {
+            "Dear {applicant},\n\n\
+            The file you requested is ready and can be downloaded from the link below:\n\n\
+            Download: {link}\n\
+            File name: {file}\n\
+            Size: {size} bytes\n\
+            MD5: {md5}\n\
+            SHA-256: {sha256}\n\
+            Generated at: {time}\n\n\
+            You can compare the checksums above to verify the download.\n\
+            The link expires in 24 hours, so please download it soon.\n\n\
+            -- This is an automated message, please do not reply."
+        }
+        (Lang::Zh, Msg::CodeSubject) => "您的验证码",
+        (Lang::En, Msg::CodeSubject) => "Your verification code",
+        (Lang::Zh, Msg::CodeBody) => "您的验证码是：{code}\n有效期 {ttl_minutes} 分钟，请勿泄露。",
+        (Lang::En, Msg::CodeBody) => {
+            "Your verification code is: {code}\n\
+            It expires in {ttl_minutes} minutes. Do not share it with anyone."
+        }
+        (Lang::Zh, Msg::CodeSent) => "验证码已发送到 {email}",
+        (Lang::En, Msg::CodeSent) => "Verification code sent to {email}",
+    }
+}
+
+/// 替换模板中的 `{name}` 占位符，未知的占位符原样保留
+///
+/// 只扫描模板一次，参数值中的花括号不会被再次替换
+pub fn fill(template: &str, args: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let value = tail.find('}').and_then(|end| {
+            let name = &tail[1..end];
+            args.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 按语言取出文案并替换占位符
+pub fn render(lang: Lang, msg: Msg, args: &[(&str, &str)]) -> String {
+    fill(text(lang, msg), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_negotiate_lang() {
+        assert_eq!(Lang::parse("en-US"), Some(Lang::En));
+        assert_eq!(Lang::parse("zh_CN"), Some(Lang::Zh));
+        assert_eq!(Lang::parse("fr"), None);
+
+        let headers = accept("fr-FR, en-US;q=0.8, zh;q=0.5");
+        assert_eq!(Lang::from_accept_language(&headers), Some(Lang::En));
+        let headers = accept("en;q=0.3, zh-CN");
+        assert_eq!(Lang::from_accept_language(&headers), Some(Lang::Zh));
+        assert_eq!(Lang::from_accept_language(&accept("fr, de")), None);
+
+        // 请求中的 lang 优先，其余情况默认中文
+        assert_eq!(Lang::negotiate(Some("zh"), &accept("en")), Lang::Zh);
+        assert_eq!(Lang::negotiate(Some("fr"), &accept("en")), Lang::En);
+        assert_eq!(Lang::negotiate(None, &HeaderMap::new()), Lang::Zh);
+    }
+
+    #[test]
+    fn test_fill_placeholders_once() {
+        let filled = fill("{a}-{b}-{missing}-{", &[("a", "{b}"), ("b", "2")]);
+        assert_eq!(filled, "{b}-2-{missing}-{");
+    }
+}
@@ -1,7 +1,14 @@
+pub mod audit;
 pub mod email;
 pub mod file;
 pub mod github;
+pub mod http;
 pub(crate) mod log;
 pub mod markdown;
+pub mod messages;
+pub mod notify;
 pub mod picture;
+pub mod reload;
+pub mod shutdown;
 mod stream;
+pub mod upload;
@@ -0,0 +1,333 @@
+use crate::config::{AppConfig, NotifyChannel};
+use crate::middleware::background::mail_batch_job;
+use crate::utils::email::{Mailer, SendParams, SmtpMailer};
+use crate::utils::http;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// 通知对应的业务事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationEvent {
+    /// 新投稿已创建 PR
+    Submission,
+    /// 投稿在后台处理失败
+    #[serde(rename = "submission_failed")]
+    SubmissionFailed,
+    /// 用户申请下载分享文件
+    Share,
+}
+
+/// 发给管理员的一条通知
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub event: NotificationEvent,
+    pub subject: String,
+    pub body: String,
+    /// 相关链接：投稿为 PR 地址，文件分享为下载地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 管理员回复通知邮件时的收件地址，即投稿人或申请人的邮箱
+    #[serde(skip)]
+    pub reply_to: Option<String>,
+}
+
+/// 管理员通知渠道
+///
+/// 在后台任务线程中同步调用，实现方自行处理超时
+pub trait Notifier: Send + Sync {
+    fn notify(&self, notification: &Notification) -> Result<()>;
+
+    /// 日志中显示的渠道名
+    fn name(&self) -> &'static str;
+}
+
+/// 邮件通知：发给全部管理员邮箱，失败重试由 `mailer` 负责
+pub struct EmailNotifier {
+    mailer: Arc<dyn Mailer>,
+    recipients: Vec<String>,
+    concurrency: usize,
+    bcc: bool,
+}
+
+impl EmailNotifier {
+    pub fn new(mailer: Arc<dyn Mailer>, recipients: Vec<String>, concurrency: usize) -> Self {
+        Self {
+            mailer,
+            recipients,
+            concurrency,
+            bcc: false,
+        }
+    }
+
+    /// 为 true 时把全部管理员放在密送中只发送一封邮件，默认逐个发送
+    pub fn with_bcc(mut self, bcc: bool) -> Self {
+        self.bcc = bcc;
+        self
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        if self.recipients.is_empty() {
+            debug!("NOTIFY[email]: no admin email configured, notification skipped");
+            return Ok(());
+        }
+        if self.bcc {
+            let params = SendParams {
+                bcc: &self.recipients,
+                reply_to: notification.reply_to.as_deref(),
+                subject: &notification.subject,
+                body: &notification.body,
+                ..Default::default()
+            };
+            return self.mailer.send_full(params);
+        }
+        // 单个收件人的失败只记录日志，不影响其他收件人
+        mail_batch_job(
+            self.mailer.clone(),
+            self.recipients.clone(),
+            notification.subject.clone(),
+            notification.body.clone(),
+            notification.reply_to.clone(),
+            self.concurrency,
+        )();
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "email"
+    }
+}
+
+/// Webhook 请求体，`text` 字段可直接被 Slack 等 Incoming Webhook 展示
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    text: String,
+    #[serde(flatten)]
+    notification: &'a Notification,
+}
+
+/// Webhook 通知：以 JSON POST 到配置的地址
+pub struct WebhookNotifier {
+    url: String,
+    timeout: Duration,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            url: url.into(),
+            timeout,
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        let payload = WebhookPayload {
+            text: format!("{}\n{}", notification.subject, notification.body),
+            notification,
+        };
+
+        // 后台任务线程上没有 tokio 运行时，这里临时创建一个；
+        // 客户端也随之新建，避免连接池绑定到已销毁的运行时
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("创建 Webhook 运行时失败")?;
+        runtime.block_on(async {
+            http::build_client(&http::user_agent())
+                .context("初始化 HTTP 客户端失败")?
+                .post(&self.url)
+                .timeout(self.timeout)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .with_context(|| format!("Webhook 请求失败: {}", self.url))?;
+            Ok(())
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+}
+
+/// 按配置构造启用的通知渠道
+pub fn configured_notifiers() -> Vec<Arc<dyn Notifier>> {
+    let config = AppConfig::global();
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+
+    for channel in &config.notify.channels {
+        match channel {
+            NotifyChannel::Email => notifiers.push(Arc::new(
+                EmailNotifier::new(
+                    SmtpMailer::background(),
+                    config.admin.email.clone(),
+                    config.admin.notify_concurrency,
+                )
+                .with_bcc(config.admin.notify_bcc),
+            )),
+            NotifyChannel::Webhook => match &config.notify.webhook_url {
+                Some(url) => notifiers.push(Arc::new(WebhookNotifier::new(
+                    url,
+                    Duration::from_secs(config.notify.webhook_timeout_secs),
+                ))),
+                None => warn!("NOTIFY: webhook channel enabled but notify.webhook_url is empty"),
+            },
+        }
+    }
+
+    notifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::github::Submission;
+    use crate::utils::picture::RawImage;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    /// 一封邮件的全部收件人与回复地址
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct SentEnvelope {
+        to: Option<String>,
+        cc: Vec<String>,
+        bcc: Vec<String>,
+        reply_to: Option<String>,
+    }
+
+    /// 只记录收件人与回复地址的 mailer
+    #[derive(Clone, Default)]
+    struct EnvelopeMailer {
+        envelopes: Arc<Mutex<Vec<SentEnvelope>>>,
+    }
+
+    impl Mailer for EnvelopeMailer {
+        fn send_full(&self, params: SendParams<'_>) -> anyhow::Result<()> {
+            self.envelopes.lock().unwrap().push(SentEnvelope {
+                to: params.to.map(str::to_string),
+                cc: params.cc.to_vec(),
+                bcc: params.bcc.to_vec(),
+                reply_to: params.reply_to.map(str::to_string),
+            });
+            Ok(())
+        }
+    }
+
+    /// 启动本地 mock webhook，把收到的请求体转发到返回的通道
+    async fn mock_webhook() -> (String, mpsc::UnboundedReceiver<serde_json::Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/hook",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                tx.send(body).unwrap();
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/hook", addr), rx)
+    }
+
+    #[tokio::test]
+    async fn test_webhook_submission_payload() {
+        // User-Agent 需要读取全局配置
+        crate::config::test_global();
+        let (url, mut rx) = mock_webhook().await;
+
+        let submission = Submission::new(
+            "作者".to_string(),
+            "author@example.com".to_string(),
+            "标题".to_string(),
+            vec!["科幻".to_string()],
+            "正文".to_string(),
+            RawImage {
+                name: "cover.png".to_string(),
+                bytes: vec![1, 2, 3],
+            },
+            vec![],
+        );
+        let notification = submission.to_notification("https://github.com/o/r/pull/1");
+
+        let notifier = WebhookNotifier::new(url, Duration::from_secs(5));
+        tokio::task::spawn_blocking(move || notifier.notify(&notification))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let payload = rx.recv().await.unwrap();
+        assert_eq!(payload["event"], "submission");
+        assert_eq!(payload["subject"], submission.to_title());
+        assert_eq!(payload["body"], submission.to_info());
+        assert_eq!(payload["url"], "https://github.com/o/r/pull/1");
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.starts_with(&submission.to_title()), "{}", text);
+        assert!(text.contains("标签: 科幻"), "{}", text);
+    }
+
+    #[test]
+    fn test_send_full_captures_cc_bcc_reply_to() {
+        let mailer = EnvelopeMailer::default();
+        mailer.send("to@example.com", "s", "b").unwrap();
+        let cc = vec!["cc@example.com".to_string()];
+        mailer
+            .send_full(SendParams {
+                to: Some("to@example.com"),
+                cc: &cc,
+                reply_to: Some("author@example.com"),
+                subject: "s",
+                body: "b",
+                ..Default::default()
+            })
+            .unwrap();
+
+        // 密送模式下一封邮件发给全部管理员，回复地址为投稿人
+        let admins = vec!["a@example.com".to_string(), "b@example.com".to_string()];
+        let notification = Notification {
+            event: NotificationEvent::Submission,
+            subject: "新投稿".to_string(),
+            body: "正文".to_string(),
+            url: None,
+            reply_to: Some("author@example.com".to_string()),
+        };
+        EmailNotifier::new(Arc::new(mailer.clone()), admins.clone(), 4)
+            .with_bcc(true)
+            .notify(&notification)
+            .unwrap();
+
+        let envelopes = mailer.envelopes.lock().unwrap();
+        assert_eq!(
+            *envelopes,
+            [
+                SentEnvelope {
+                    to: Some("to@example.com".to_string()),
+                    cc: vec![],
+                    bcc: vec![],
+                    reply_to: None,
+                },
+                SentEnvelope {
+                    to: Some("to@example.com".to_string()),
+                    cc,
+                    bcc: vec![],
+                    reply_to: Some("author@example.com".to_string()),
+                },
+                SentEnvelope {
+                    to: None,
+                    cc: vec![],
+                    bcc: admins,
+                    reply_to: Some("author@example.com".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -1,11 +1,16 @@
 use anyhow::{Context, Result, anyhow};
 use base64::{Engine as _, engine::general_purpose};
-use image::{DynamicImage, ImageFormat};
+use image::codecs::gif::GifDecoder;
+use image::codecs::webp::{WebPDecoder, WebPEncoder};
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage, ImageDecoder, ImageFormat, ImageReader};
 use serde::{Deserialize, Serialize};
+use std::io::Cursor;
 use std::path::Path;
+use utoipa::ToSchema;
 
 /// 表示一个Base64编码的图像请求
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct Base64Image {
     pub base64: String,
     pub name: String,
@@ -33,6 +38,13 @@ impl Base64Image {
             .decode(raw)
             .map_err(|e| anyhow!("Base64 解码失败 ({}): {}", self.name, e))
     }
+
+    /// data URI 中声明的 MIME 类型，例如 `data:image/png;base64,...` 中的 `image/png`
+    pub fn mime_type(&self) -> Option<&str> {
+        let (header, _) = self.base64.strip_prefix("data:")?.split_once(',')?;
+        let mime = header.split(';').next()?.trim();
+        (!mime.is_empty()).then_some(mime)
+    }
     #[allow(dead_code)]
     pub fn save(&self, path: &Path) -> Result<()> {
         self.to_decode_image()
@@ -42,15 +54,186 @@ impl Base64Image {
     }
 }
 
+/// 已从 Base64 解码的原始图片字节
+///
+/// 反序列化时即完成解码，Base64 文本随即释放，避免同一张图片在内存中存在两份
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "Base64Image")]
+pub struct RawImage {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+impl TryFrom<Base64Image> for RawImage {
+    type Error = anyhow::Error;
+
+    fn try_from(image: Base64Image) -> Result<Self> {
+        let bytes = image.to_bytes()?;
+        Ok(Self {
+            name: image.name,
+            bytes,
+        })
+    }
+}
+
+impl RawImage {
+    /// 根据文件头识别的实际格式
+    pub fn format(&self) -> Option<ImageFormat> {
+        image::guess_format(&self.bytes).ok()
+    }
+
+    /// 是否为多帧动图（GIF 或动态 WebP）
+    pub fn is_animated(&self) -> bool {
+        match self.format() {
+            Some(ImageFormat::Gif) => GifDecoder::new(Cursor::new(&self.bytes))
+                .map(|d| d.into_frames().take(2).count() > 1)
+                .unwrap_or(false),
+            Some(ImageFormat::WebP) => WebPDecoder::new(Cursor::new(&self.bytes))
+                .map(|d| d.has_animation())
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// 提交到仓库时使用的扩展名，与实际内容保持一致；无法识别时沿用 webp
+    pub fn extension(&self) -> &'static str {
+        self.format()
+            .and_then(|f| f.extensions_str().first().copied())
+            .unwrap_or("webp")
+    }
+
+    /// 重新编码为 WebP，超出 `max_width` × `max_height` 时先等比缩小，0 表示该方向不限制
+    ///
+    /// `quality` 为 1-100 时有损编码，0 时无损编码；
+    /// 动图无法逐帧编码，原样返回；已是 WebP 且无需缩小时也原样返回
+    pub fn to_webp(&self, max_width: u32, max_height: u32, quality: u8) -> Result<RawImage> {
+        if self.is_animated() {
+            return Ok(self.clone());
+        }
+
+        let format = image::guess_format(&self.bytes)
+            .with_context(|| format!("无法识别图像格式 ({})", self.name))?;
+        let image = image::load_from_memory_with_format(&self.bytes, format)
+            .with_context(|| format!("图像解析失败 ({})", self.name))?;
+
+        let mut decoded = DecodedImage { image, format };
+        let resized = decoded.resize_to_fit(max_width, max_height);
+        if format == ImageFormat::WebP && !resized {
+            return Ok(self.clone());
+        }
+
+        Ok(RawImage {
+            name: self.name.clone(),
+            bytes: self.encode_webp(decoded.image, quality)?,
+        })
+    }
+
+    /// 是否带有 EXIF 元数据（可能包含拍摄位置与设备信息）
+    pub fn has_exif(&self) -> bool {
+        ImageReader::new(Cursor::new(&self.bytes))
+            .with_guessed_format()
+            .ok()
+            .and_then(|reader| reader.into_decoder().ok())
+            .and_then(|mut decoder| decoder.exif_metadata().ok().flatten())
+            .is_some_and(|exif| !exif.is_empty())
+    }
+
+    /// 解码为像素后按原格式重新编码，去除 EXIF 等元数据
+    ///
+    /// 不含 EXIF 的图片原样返回，避免无谓的重新编码；动图无法逐帧编码，同样原样返回
+    pub fn strip_metadata(&self) -> Result<RawImage> {
+        if self.is_animated() || !self.has_exif() {
+            return Ok(self.clone());
+        }
+
+        let format = image::guess_format(&self.bytes)
+            .with_context(|| format!("无法识别图像格式 ({})", self.name))?;
+        let image = image::load_from_memory_with_format(&self.bytes, format)
+            .with_context(|| format!("图像解析失败 ({})", self.name))?;
+
+        Ok(RawImage {
+            name: self.name.clone(),
+            bytes: self.encode(image, format)?,
+        })
+    }
+
+    /// 把像素编码为指定格式，image 的编码器均不会写入元数据
+    fn encode(&self, image: DynamicImage, format: ImageFormat) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        match format {
+            // WebP 编码器只接受 8 位 RGB/RGBA
+            ImageFormat::WebP => {
+                let image = if image.color().has_alpha() {
+                    DynamicImage::ImageRgba8(image.to_rgba8())
+                } else {
+                    DynamicImage::ImageRgb8(image.to_rgb8())
+                };
+                image.write_with_encoder(WebPEncoder::new_lossless(&mut bytes))
+            }
+            // JPEG 不支持透明通道
+            ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_to(&mut Cursor::new(&mut bytes), format),
+            _ => image.write_to(&mut Cursor::new(&mut bytes), format),
+        }
+        .with_context(|| format!("{:?} 编码失败 ({})", format, self.name))?;
+        Ok(bytes)
+    }
+
+    /// 用 libwebp 有损编码为 WebP，`quality` 为 0 时退回 image 的无损编码
+    fn encode_webp(&self, image: DynamicImage, quality: u8) -> Result<Vec<u8>> {
+        if quality == 0 {
+            return self.encode(image, ImageFormat::WebP);
+        }
+
+        let (width, height) = (image.width(), image.height());
+        let encoded = if image.color().has_alpha() {
+            let rgba = image.to_rgba8();
+            webp::Encoder::from_rgba(&rgba, width, height).encode_simple(false, quality as f32)
+        } else {
+            let rgb = image.to_rgb8();
+            webp::Encoder::from_rgb(&rgb, width, height).encode_simple(false, quality as f32)
+        }
+        .map_err(|e| anyhow!("WebP 编码失败 ({}): {:?}", self.name, e))?;
+        Ok(encoded.to_vec())
+    }
+
+    /// 检查字节内容能否被识别并解码为图片
+    pub fn check_decodable(&self) -> Result<()> {
+        let format = image::guess_format(&self.bytes)
+            .with_context(|| format!("无法识别图像格式 ({})", self.name))?;
+        image::load_from_memory_with_format(&self.bytes, format)
+            .with_context(|| format!("图像解析失败 ({})", self.name))?;
+        Ok(())
+    }
+}
+
 /// 表示解码后的图像对象及其格式
 #[derive(Debug)]
-#[allow(dead_code)]
 pub struct DecodedImage {
     pub image: DynamicImage,
     pub format: ImageFormat,
 }
 
 impl DecodedImage {
+    /// 超出 `max_width` × `max_height` 时等比缩小到范围内，未超出时保持不变；
+    /// 0 表示该方向不限制。返回是否进行了缩放
+    pub fn resize_to_fit(&mut self, max_width: u32, max_height: u32) -> bool {
+        let max_width = if max_width == 0 { u32::MAX } else { max_width };
+        let max_height = if max_height == 0 {
+            u32::MAX
+        } else {
+            max_height
+        };
+        if self.image.width() <= max_width && self.image.height() <= max_height {
+            return false;
+        }
+
+        self.image = self
+            .image
+            .resize(max_width, max_height, FilterType::Lanczos3);
+        true
+    }
+
     /// 将图像保存到指定路径
     #[allow(dead_code)]
     pub fn save(&self, output_path: &Path) -> Result<()> {
@@ -73,13 +256,15 @@ impl DecodedImage {
 /// 将Base64图像请求解码为图像对象
 #[allow(dead_code)]
 pub fn decode_base64_image(request: &Base64Image) -> Result<DecodedImage> {
-    // 解码 Base64
-    let bytes = general_purpose::STANDARD
-        .decode(&request.base64)
+    // 解码 Base64，与 to_bytes 一样去掉 data: 前缀
+    let bytes = request
+        .to_bytes()
         .with_context(|| format!("Base64解码失败 ({})", request.name))?;
 
-    // 从文件名推断图像格式
+    // 从文件名推断图像格式，文件名没有可识别的扩展名时使用 data URI 中的 MIME 类型
     let format = ImageFormat::from_path(&request.name)
+        .ok()
+        .or_else(|| request.mime_type().and_then(ImageFormat::from_mime_type))
         .with_context(|| format!("无法从文件名推断图像格式: {}", request.name))?;
 
     // 加载图像
@@ -98,6 +283,22 @@ mod tests {
     const TEST_JPEG_BASE64: &str = "/9j/4AAQSkZJRgABAQAAAQABAAD/2wBDAAMCAgICAgMCAgIDAwMDBAYEBAQEBAgGBgUGCQgKCgkICQkKDA8MCgsOCwkJDRENDg8QEBEQCgwSExIQEw8QEBD/2wBDAQMDAwQDBAgEBAgQCwkLEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBD/wAARCAABAAEDAREAAhEBAxEB/8QAHwAAAQUBAQEBAQEAAAAAAAAAAAECAwQFBgcICQoL/8QAtRAAAgEDAwIEAwUFBAQAAAF9AQIDAAQRBRIhMUEGE1FhByJxFDKBkaEII0KxwRVS0fAkM2JyggkKFhcYGRolJicoKSo0NTY3ODk6Q0RFRkdISUpTVFVWV1hZWmNkZWZnaGlqc3R1dnd4eXqDhIWGh4iJipKTlJWWl5iZmqKjpKWmp6ipqrKztLW2t7i5usLDxMXGx8jJytLT1NXW19jZ2uHi4+Tl5ufo6erx8vP09fb3+Pn6/8QAHwEAAwEBAQEBAQEBAQAAAAAAAAECAwQFBgcICQoL/8QAtREAAgECBAQDBAcFBAQAAQJ3AAECAxEEBSExBhJBUQdhcRMiMoEIFEKRobHBCSMzUvAVYnLRChYkNOEl8RcYGRomJygpKjU2Nzg5OkNERUZHSElKU1RVVldYWVpjZGVmZ2hpanN0dXZ3eHl6goOEhYaHiImKkpOUlZaXmJmaoqOkpaanqKmqsrO0tba3uLm6wsPExcbHyMnK0tPU1dbX2Nna4uPk5ebn6Onq8vP09fb3+Pn6/9oADAMBAAIRAxEAPwD9U6AP/9k=";
     const TEST_WEBP_BASE64: &str = "UklGRh4AAABXRUJQVlA4TBEAAAAvAAAAAAfQ//73v/+BiOh/AAA=";
 
+    #[test]
+    fn test_raw_image_check_decodable() -> Result<()> {
+        let png = RawImage::try_from(Base64Image::new(
+            TEST_PNG_BASE64.to_string(),
+            "test.png".to_string(),
+        ))?;
+        assert!(png.check_decodable().is_ok());
+
+        let garbage = RawImage {
+            name: "broken.png".to_string(),
+            bytes: vec![1, 2, 3],
+        };
+        assert!(garbage.check_decodable().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_decode_png() -> Result<()> {
         let request = Base64Image::new(TEST_PNG_BASE64.to_string(), "test.png".to_string());
@@ -128,6 +329,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_raw_image_from_json() -> Result<()> {
+        let json = format!(
+            r#"{{"name":"test.png","base64":"data:image/png;base64,{}"}}"#,
+            TEST_PNG_BASE64
+        );
+        let raw: RawImage = serde_json::from_str(&json)?;
+        assert_eq!(raw.name, "test.png");
+        assert_eq!(
+            raw.bytes,
+            general_purpose::STANDARD.decode(TEST_PNG_BASE64)?
+        );
+
+        let invalid = r#"{"name":"test.png","base64":"不是Base64"}"#;
+        assert!(serde_json::from_str::<RawImage>(invalid).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_save_image() -> Result<()> {
         let request = Base64Image::new(TEST_PNG_BASE64.to_string(), "test.png".to_string());
@@ -148,6 +367,21 @@ mod tests {
         assert!(msg.contains("Base64解码失败"));
     }
 
+    #[test]
+    fn test_decode_data_uri() -> Result<()> {
+        let data_uri = format!("data:image/png;base64,{}", TEST_PNG_BASE64);
+        let request = Base64Image::new(data_uri.clone(), "test.png".to_string());
+        assert_eq!(request.mime_type(), Some("image/png"));
+        let decoded = decode_base64_image(&request)?;
+        assert_eq!(decoded.format, ImageFormat::Png);
+        assert_eq!(decoded.image.width(), 1);
+
+        // 文件名没有扩展名时按 MIME 类型推断格式
+        let request = Base64Image::new(data_uri, "cover".to_string());
+        assert_eq!(decode_base64_image(&request)?.format, ImageFormat::Png);
+        Ok(())
+    }
+
     #[test]
     fn test_unknown_format() {
         let request = Base64Image::new(TEST_PNG_BASE64.to_string(), "test.unknown".to_string());
@@ -171,4 +405,164 @@ mod tests {
         assert!(output_path.exists());
         Ok(())
     }
+
+    /// 按 RIFF 文件头判断是否为 WebP，不依赖文件名或 image 的格式推断
+    fn is_webp(bytes: &[u8]) -> bool {
+        bytes.len() > 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP"
+    }
+
+    fn raw(base64: &str, name: &str) -> RawImage {
+        RawImage::try_from(Base64Image::new(base64.to_string(), name.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_to_webp_from_png_and_jpeg() -> Result<()> {
+        for original in [
+            raw(TEST_PNG_BASE64, "a.png"),
+            raw(TEST_JPEG_BASE64, "b.jpg"),
+        ] {
+            assert!(!is_webp(&original.bytes));
+
+            let webp = original.to_webp(0, 0, 80)?;
+            assert!(is_webp(&webp.bytes), "{}", original.name);
+            assert_eq!(webp.name, original.name);
+            assert_eq!(webp.extension(), "webp");
+
+            let decoded = image::load_from_memory_with_format(&webp.bytes, ImageFormat::WebP)?;
+            assert_eq!((decoded.width(), decoded.height()), (1, 1));
+        }
+        Ok(())
+    }
+
+    /// 在 JPEG 的 SOI 标记后插入一个带 GPS 字段的 APP1 EXIF 段
+    fn jpeg_with_exif() -> RawImage {
+        let mut exif = b"Exif\0\0II*\0\x08\0\0\0".to_vec();
+        // IFD0：一个 GPSInfo 条目，之后无下一个 IFD
+        exif.extend_from_slice(&[1, 0, 0x25, 0x88, 4, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        exif.extend_from_slice(&[0, 0, 0, 0]);
+
+        let jpeg = raw(TEST_JPEG_BASE64, "photo.jpg");
+        let mut bytes = jpeg.bytes[..2].to_vec();
+        bytes.extend_from_slice(&[0xFF, 0xE1]);
+        bytes.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+        bytes.extend_from_slice(&exif);
+        bytes.extend_from_slice(&jpeg.bytes[2..]);
+        RawImage {
+            name: jpeg.name,
+            bytes,
+        }
+    }
+
+    fn contains_exif_marker(bytes: &[u8]) -> bool {
+        bytes.windows(6).any(|w| w == b"Exif\0\0")
+    }
+
+    #[test]
+    fn test_strip_metadata_removes_exif() -> Result<()> {
+        let photo = jpeg_with_exif();
+        assert!(photo.has_exif());
+        assert!(contains_exif_marker(&photo.bytes));
+
+        let stripped = photo.strip_metadata()?;
+        assert!(!stripped.has_exif());
+        assert!(!contains_exif_marker(&stripped.bytes));
+        assert_eq!(stripped.format(), Some(ImageFormat::Jpeg));
+        assert_eq!(stripped.name, photo.name);
+
+        // 不含 EXIF 的图片原样返回
+        let png = raw(TEST_PNG_BASE64, "a.png");
+        assert_eq!(png.strip_metadata()?.bytes, png.bytes);
+        Ok(())
+    }
+
+    fn decoded(width: u32, height: u32) -> DecodedImage {
+        DecodedImage {
+            image: DynamicImage::new_rgb8(width, height),
+            format: ImageFormat::Png,
+        }
+    }
+
+    #[test]
+    fn test_resize_to_fit_downscales_oversized() {
+        let mut image = decoded(800, 600);
+        assert!(image.resize_to_fit(200, 200));
+        assert_eq!((image.image.width(), image.image.height()), (200, 150));
+
+        // 只限制高度时按高度缩放
+        let mut image = decoded(100, 400);
+        assert!(image.resize_to_fit(0, 200));
+        assert_eq!((image.image.width(), image.image.height()), (50, 200));
+    }
+
+    #[test]
+    fn test_resize_to_fit_keeps_small_images() {
+        let mut image = decoded(80, 60);
+        assert!(!image.resize_to_fit(200, 200));
+        assert_eq!((image.image.width(), image.image.height()), (80, 60));
+
+        // 恰好等于上限也不缩放
+        let mut image = decoded(200, 10);
+        assert!(!image.resize_to_fit(200, 200));
+        assert_eq!(image.image.width(), 200);
+    }
+
+    #[test]
+    fn test_resize_to_fit_preserves_aspect_ratio() {
+        let mut image = decoded(300, 100);
+        assert!(image.resize_to_fit(120, 120));
+        let (w, h) = (image.image.width(), image.image.height());
+        assert!(w <= 120 && h <= 120);
+        assert_eq!(w, 120);
+        // 3:1 的比例保持不变（允许取整误差）
+        assert!((w as f64 / h as f64 - 3.0).abs() < 0.01, "{}x{}", w, h);
+    }
+
+    #[test]
+    fn test_to_webp_downscales_large_images() -> Result<()> {
+        let mut png = Vec::new();
+        DynamicImage::new_rgb8(100, 50).write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+        let original = RawImage {
+            name: "wide.png".to_string(),
+            bytes: png,
+        };
+
+        let webp = original.to_webp(20, 20, 80)?;
+        assert!(is_webp(&webp.bytes));
+        let decoded = image::load_from_memory(&webp.bytes)?;
+        assert_eq!((decoded.width(), decoded.height()), (20, 10));
+
+        // 已是 WebP 且尺寸合适时不再重新编码
+        assert_eq!(webp.to_webp(20, 20, 80)?.bytes, webp.bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_webp_lossy_is_smaller_than_lossless() -> Result<()> {
+        // 带渐变与噪点的类照片图像，无损编码难以压缩
+        let image = image::RgbImage::from_fn(64, 64, |x, y| {
+            let noise = ((x * 7919 + y * 104_729) % 61) as u8;
+            image::Rgb([(x * 4) as u8 ^ noise, (y * 4) as u8, noise.wrapping_mul(3)])
+        });
+        let mut png = Vec::new();
+        DynamicImage::ImageRgb8(image).write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+        let original = RawImage {
+            name: "photo.png".to_string(),
+            bytes: png,
+        };
+
+        let lossless = original.to_webp(0, 0, 0)?;
+        let lossy = original.to_webp(0, 0, 50)?;
+        assert!(is_webp(&lossless.bytes) && is_webp(&lossy.bytes));
+        assert!(
+            lossy.bytes.len() < lossless.bytes.len(),
+            "lossy {} >= lossless {}",
+            lossy.bytes.len(),
+            lossless.bytes.len()
+        );
+
+        // 有损结果仍可解码且尺寸不变
+        let decoded = image::load_from_memory_with_format(&lossy.bytes, ImageFormat::WebP)?;
+        assert_eq!((decoded.width(), decoded.height()), (64, 64));
+        Ok(())
+    }
 }
@@ -0,0 +1,51 @@
+use crate::config::AppConfig;
+use crate::utils::{email, http, log};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 重新加载全局配置，并应用到启动时创建的组件，失败时保留原配置
+///
+/// 日志级别、SMTP 与 HTTP 客户端随之更新；监听端口等只在启动时读取的配置项
+/// 改动后记录警告，需要重启才能生效
+pub fn reload_config() -> Result<Arc<AppConfig>, Box<dyn std::error::Error>> {
+    let previous = AppConfig::global();
+    let current = AppConfig::reload()?;
+
+    for field in current.restart_required_changes(&previous) {
+        warn!("CONFIG: '{}' changed, restart required to apply", field);
+    }
+    log::set_level(current.log.level);
+    if let Err(e) = email::reload_mailers() {
+        warn!(
+            "CONFIG: SMTP mailers not rebuilt, keeping previous: {:#}",
+            e
+        );
+    }
+    if let Err(e) = http::reload_client() {
+        warn!("CONFIG: HTTP client not rebuilt, keeping previous: {}", e);
+    }
+    Ok(current)
+}
+
+/// 收到 SIGHUP 时重新加载全局配置，失败时保留原配置并记录日志
+#[cfg(unix)]
+pub async fn reload_on_sighup() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            warn!("CONFIG: failed to install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        match reload_config() {
+            Ok(_) => info!("CONFIG: reloaded on SIGHUP"),
+            Err(e) => warn!(
+                "CONFIG: reload on SIGHUP failed, keeping current config: {}",
+                e
+            ),
+        }
+    }
+}
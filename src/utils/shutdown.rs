@@ -0,0 +1,64 @@
+use std::future::Future;
+use tracing::info;
+
+/// 触发关闭的信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownSignal {
+    CtrlC,
+    Terminate,
+}
+
+/// 等待 Ctrl+C 或 SIGTERM（仅 unix），用于 `axum::serve` 的优雅关闭
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    wait_for_signal(ctrl_c, terminate).await;
+}
+
+/// 等待任一信号到达并记录日志，返回先到达的信号
+pub async fn wait_for_signal(
+    ctrl_c: impl Future<Output = ()>,
+    terminate: impl Future<Output = ()>,
+) -> ShutdownSignal {
+    let signal = tokio::select! {
+        _ = ctrl_c => ShutdownSignal::CtrlC,
+        _ = terminate => ShutdownSignal::Terminate,
+    };
+    info!(
+        "SHUTDOWN: received {:?}, waiting for in-flight requests to finish",
+        signal
+    );
+    signal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::{pending, ready};
+
+    #[tokio::test]
+    async fn test_wait_for_signal_returns_first_signal() {
+        assert_eq!(
+            wait_for_signal(ready(()), pending()).await,
+            ShutdownSignal::CtrlC
+        );
+        assert_eq!(
+            wait_for_signal(pending(), ready(())).await,
+            ShutdownSignal::Terminate
+        );
+    }
+}
@@ -5,53 +5,120 @@ use anyhow::{anyhow, Result};
 use bytes::Bytes;
 use futures_util::TryStreamExt;
 use md5::{Digest, Md5};
+use sha2::Sha256;
 use tokio::fs::File;
 use tokio::io;
 use tokio_util::io::ReaderStream;
 use tracing::{debug, trace, warn, instrument};
 
+/// 流式计算时可选的摘要算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamDigest {
+    Md5,
+    Sha256,
+}
+
+/// 各算法的十六进制摘要，未请求的算法为 None
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Digests {
+    pub md5: Option<String>,
+    pub sha256: Option<String>,
+}
+
+/// 按请求的算法同时计算的一组 hasher
+#[derive(Default)]
+struct Hashers {
+    md5: Option<Md5>,
+    sha256: Option<Sha256>,
+}
+
+impl Hashers {
+    fn new(algorithms: &[StreamDigest]) -> Self {
+        let mut hashers = Hashers::default();
+        for algorithm in algorithms {
+            match algorithm {
+                StreamDigest::Md5 => hashers.md5 = Some(Md5::new()),
+                StreamDigest::Sha256 => hashers.sha256 = Some(Sha256::new()),
+            }
+        }
+        hashers
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        if let Some(h) = self.md5.as_mut() {
+            h.update(data);
+        }
+        if let Some(h) = self.sha256.as_mut() {
+            h.update(data);
+        }
+    }
+
+    fn finalize(self) -> Digests {
+        Digests {
+            md5: self.md5.map(|h| format!("{:x}", h.finalize())),
+            sha256: self.sha256.map(|h| format!("{:x}", h.finalize())),
+        }
+    }
+}
+
 #[derive(Clone)]
-pub struct Md5Handle(Arc<Mutex<Option<Md5>>>);
+pub struct DigestHandle(Arc<Mutex<Option<Hashers>>>);
 
-impl Md5Handle {
+impl DigestHandle {
     #[instrument(
-        name = "md5_finalize",
+        name = "digest_finalize",
         skip(self),
         level = "debug"
     )]
-    pub fn finalize(self) -> Result<String> {
+    pub fn finalize(self) -> Result<Digests> {
         use std::mem;
 
         let mut guard = self
             .0
             .lock()
-            .map_err(|_| anyhow!("md5 hasher poisoned"))?;
+            .map_err(|_| anyhow!("digest hasher poisoned"))?;
+
+        let hashers = mem::take(&mut *guard)
+            .ok_or_else(|| anyhow!("digest already finalized or never initialized"))?;
+
+        let digests = hashers.finalize();
+        debug!(?digests, "DIGEST_HANDLE: finalize success");
+        Ok(digests)
+    }
+}
 
-        let hasher = mem::take(&mut *guard)
-            .ok_or_else(|| anyhow!("md5 already finalized or never initialized"))?;
+/// 只计算 MD5 的句柄，保留给原有调用方
+#[derive(Clone)]
+#[allow(dead_code)]
+pub struct Md5Handle(DigestHandle);
 
-        let hex = format!("{:x}", hasher.finalize());
-        debug!(md5 = %hex, "MD5_HANDLE: finalize success");
-        Ok(hex)
+#[allow(dead_code)]
+impl Md5Handle {
+    pub fn finalize(self) -> Result<String> {
+        self.0
+            .finalize()?
+            .md5
+            .ok_or_else(|| anyhow!("md5 was not requested"))
     }
 }
 
 #[instrument(
-    name = "with_md5",
+    name = "with_digests",
     skip(stream),
     level = "debug"
 )]
-pub fn with_md5<S>(
+pub fn with_digests<S>(
     stream: S,
+    algorithms: &[StreamDigest],
 ) -> (
     impl futures_util::Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
-    Md5Handle,
+    DigestHandle,
 )
 where
     S: futures_util::Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
 {
-    let handle = Md5Handle(Arc::new(Mutex::new(Some(Md5::new()))));
-    debug!("MD5_HANDLE: created new hasher");
+    let handle = DigestHandle(Arc::new(Mutex::new(Some(Hashers::new(algorithms)))));
+    debug!("DIGEST_HANDLE: created new hashers");
 
     let handle_clone = handle.clone();
 
@@ -63,20 +130,20 @@ where
                     h.update(&chunk[..]);
                     trace!(
                         chunk_len = chunk.len(),
-                        "MD5_HANDLE: updated hasher with chunk"
+                        "DIGEST_HANDLE: updated hashers with chunk"
                     );
                 } else {
                     // 一般不会出现，出现说明 finalize 过早调用
                     warn!(
                         chunk_len = chunk.len(),
-                        "MD5_HANDLE: hasher already taken (md5 may be incomplete)"
+                        "DIGEST_HANDLE: hashers already taken (digest may be incomplete)"
                     );
                 }
             }
             Err(_) => {
                 warn!(
                     chunk_len = chunk.len(),
-                    "MD5_HANDLE: mutex poisoned, md5 will be invalid"
+                    "DIGEST_HANDLE: mutex poisoned, digest will be invalid"
                 );
             }
         }
@@ -85,22 +152,95 @@ where
     (wrapped, handle)
 }
 
+#[allow(dead_code)]
+pub fn with_md5<S>(
+    stream: S,
+) -> (
+    impl futures_util::Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
+    Md5Handle,
+)
+where
+    S: futures_util::Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
+{
+    let (wrapped, handle) = with_digests(stream, &[StreamDigest::Md5]);
+    (wrapped, Md5Handle(handle))
+}
+
 #[instrument(
-    name = "file_stream_with_md5",
+    name = "file_stream_with_digests",
     skip(path),
     fields(path = %path.display()),
     level = "debug"
 )]
-pub async fn file_stream_with_md5(
+pub async fn file_stream_with_digests(
     path: &PathBuf,
+    algorithms: &[StreamDigest],
 ) -> Result<(
     impl futures_util::Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
-    Md5Handle,
+    DigestHandle,
 )> {
-    debug!("MD5_FILE_STREAM: opening file");
+    debug!("DIGEST_FILE_STREAM: opening file");
     let file = File::open(path).await?;
     let stream = ReaderStream::new(file);
-    let (wrapped, handle) = with_md5(stream);
-    debug!("MD5_FILE_STREAM: stream + md5 wrapper created");
+    let (wrapped, handle) = with_digests(stream, algorithms);
+    debug!("DIGEST_FILE_STREAM: stream + digest wrapper created");
     Ok((wrapped, handle))
-}
\ No newline at end of file
+}
+
+#[allow(dead_code)]
+pub async fn file_stream_with_md5(
+    path: &PathBuf,
+) -> Result<(
+    impl futures_util::Stream<Item = Result<Bytes, io::Error>> + Send + 'static,
+    Md5Handle,
+)> {
+    let (wrapped, handle) = file_stream_with_digests(path, &[StreamDigest::Md5]).await?;
+    Ok((wrapped, Md5Handle(handle)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+
+    const ABC_MD5: &str = "900150983cd24fb0d6963f7d28e17f72";
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    /// 把 "abc" 拆成多个分块的流
+    fn abc_stream() -> impl futures_util::Stream<Item = Result<Bytes, io::Error>> + Send + 'static {
+        stream::iter(vec![
+            Ok(Bytes::from_static(b"a")),
+            Ok(Bytes::from_static(b"bc")),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_digests_known_vectors() {
+        let (wrapped, handle) =
+            with_digests(abc_stream(), &[StreamDigest::Md5, StreamDigest::Sha256]);
+        let data: Vec<Bytes> = wrapped.try_collect().await.unwrap();
+        assert_eq!(data.concat(), b"abc");
+
+        let digests = handle.finalize().unwrap();
+        assert_eq!(digests.md5.as_deref(), Some(ABC_MD5));
+        assert_eq!(digests.sha256.as_deref(), Some(ABC_SHA256));
+
+        // 只请求一种算法时另一种为空
+        let (wrapped, handle) = with_digests(abc_stream(), &[StreamDigest::Sha256]);
+        let _: Vec<Bytes> = wrapped.try_collect().await.unwrap();
+        let digests = handle.finalize().unwrap();
+        assert_eq!(digests.md5, None);
+        assert_eq!(digests.sha256.as_deref(), Some(ABC_SHA256));
+    }
+
+    #[tokio::test]
+    async fn test_file_stream_with_md5_compat() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        std::fs::write(&path, b"abc").unwrap();
+
+        let (wrapped, handle) = file_stream_with_md5(&path).await.unwrap();
+        let _: Vec<Bytes> = wrapped.try_collect().await.unwrap();
+        assert_eq!(handle.finalize().unwrap(), ABC_MD5);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::utils::http;
+use anyhow::Result;
+use bytes::Bytes;
+use chrono::{Duration, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use reqwest::{Body, multipart};
+use serde::{Deserialize, Serialize};
+use std::io;
+use tracing::{debug, info, instrument};
+
+// tmpfile.link 下载链接的有效期
+const TMPFILE_LINK_LIFETIME: Duration = Duration::hours(24);
+const TMPFILE_UPLOAD_URL: &str = "https://tmpfile.link/api/upload";
+
+/// 待上传的字节流
+pub type UploadStream = BoxStream<'static, Result<Bytes, io::Error>>;
+
+/// 上传完成后的远端文件信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadResult {
+    pub file_name: String,
+    pub download_link: String,
+    pub download_link_encoded: String,
+    pub size: u64,
+    pub mime_type: String,
+    /// 下载链接失效的时间戳
+    pub expires_at: i64,
+}
+
+/// 分享文件的上传后端，可替换为 S3、自建存储或测试用的实现
+pub trait UploadBackend: Send + Sync {
+    fn upload_stream<'a>(
+        &'a self,
+        filename: &'a str,
+        stream: UploadStream,
+    ) -> BoxFuture<'a, Result<UploadResult>>;
+}
+
+/// tmpfile.link 上传返回结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmpfileResponse {
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    #[serde(rename = "downloadLink")]
+    pub download_link: String,
+    #[serde(rename = "downloadLinkEncoded")]
+    pub download_link_encoded: String,
+    pub size: u64,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+    #[serde(rename = "uploadedTo")]
+    pub uploaded_to: String,
+}
+
+/// 默认的上传后端：tmpfile.link，下载链接 24 小时内有效
+pub struct TmpfileBackend;
+
+impl TmpfileBackend {
+    /// 通过任意字节流上传到 tmpfile.link（流式）
+    #[instrument(
+        name = "sharefile_upload_stream",
+        skip(self, stream),
+        fields(module = "sharefile", filename = %filename)
+    )]
+    async fn upload(&self, filename: &str, stream: UploadStream) -> Result<UploadResult> {
+        debug!("SHAREFILE_UPLOAD: building request body");
+
+        // multipart 的 file part 使用 stream
+        let part = multipart::Part::stream(Body::wrap_stream(stream))
+            .file_name(filename.to_string())
+            .mime_str("application/octet-stream")?;
+
+        let form = multipart::Form::new().part("file", part);
+
+        debug!("SHAREFILE_UPLOAD: sending request to tmpfile.link");
+        let resp = http::client()
+            .post(TMPFILE_UPLOAD_URL)
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let tmp_resp = resp.json::<TmpfileResponse>().await?;
+        info!(
+            "SHAREFILE_UPLOAD: upload finished, remote_file={}, size={}",
+            tmp_resp.file_name, tmp_resp.size
+        );
+
+        Ok(UploadResult {
+            file_name: tmp_resp.file_name,
+            download_link: tmp_resp.download_link,
+            download_link_encoded: tmp_resp.download_link_encoded,
+            size: tmp_resp.size,
+            mime_type: tmp_resp.mime_type,
+            expires_at: (Utc::now() + TMPFILE_LINK_LIFETIME).timestamp(),
+        })
+    }
+}
+
+impl UploadBackend for TmpfileBackend {
+    fn upload_stream<'a>(
+        &'a self,
+        filename: &'a str,
+        stream: UploadStream,
+    ) -> BoxFuture<'a, Result<UploadResult>> {
+        Box::pin(self.upload(filename, stream))
+    }
+}